@@ -1,5 +1,8 @@
-use crate::app::{App, BackendTask, InputKind};
-use crate::domain::{Action, ActionRequest};
+use crate::app::{App, BackendTask, ModalState};
+use crate::audit::ExecutionMode;
+use crate::chattr::ChattrPicker;
+use crate::config::{BatchErrorPolicy, ProtectedPathMode};
+use crate::domain::{Action, ActionRequest, CommandResult};
 use crate::ignore::{chezmoi_ignore_path, run_internal_ignore_action};
 use crate::infra::action_to_args;
 use crate::terminal::{restore_terminal, setup_terminal};
@@ -18,6 +21,25 @@ pub(crate) fn send_task(
     task_tx: &UnboundedSender<BackendTask>,
     task: BackendTask,
 ) -> Result<()> {
+    match &task {
+        BackendTask::RunAction { request } if request.action.is_mutating() => {
+            app.note_own_write(request.target.as_deref());
+        }
+        BackendTask::WriteHunk { target, .. } | BackendTask::SaveMerge { target, .. } => {
+            app.note_own_write(Some(target));
+        }
+        _ => {}
+    }
+    // A full refresh covers every change the watcher queued, so a targeted
+    // one is widened while a full one is pending.
+    let task = match task {
+        BackendTask::RefreshStatusPaths { .. } if app.refresh_queued => BackendTask::RefreshAll,
+        task => task,
+    };
+    if matches!(task, BackendTask::RefreshAll) {
+        app.refresh_queued = false;
+        app.queued_status_targets.clear();
+    }
     app.busy = true;
     task_tx
         .send(task)
@@ -30,17 +52,34 @@ pub(crate) fn run_foreground_action(
     task_tx: &UnboundedSender<BackendTask>,
     request: &ActionRequest,
 ) -> Result<()> {
+    if refuse_in_read_only(app, request) {
+        app.busy = false;
+        return maybe_continue_batch(app, task_tx, Err("read-only mode".to_string()));
+    }
+
     restore_terminal(terminal)?;
 
+    app.note_own_write(request.target.as_deref());
     let result = run_action_foreground(request);
+    app.finish_own_write();
 
-    setup_terminal()?;
+    setup_terminal(app.config.mouse)?;
     terminal.clear()?;
 
     app.busy = false;
 
     match result {
         Ok((code, elapsed)) => {
+            app.record_audit(
+                request,
+                ExecutionMode::Foreground,
+                &CommandResult {
+                    exit_code: code,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    duration_ms: elapsed,
+                },
+            );
             let target = request
                 .target
                 .as_ref()
@@ -54,16 +93,24 @@ pub(crate) fn run_foreground_action(
             ));
 
             if app.batch_in_progress() {
-                maybe_continue_batch(app, task_tx)?;
+                let outcome = if code == 0 {
+                    Ok(())
+                } else {
+                    Err(format!("exit={code}"))
+                };
+                maybe_continue_batch(app, task_tx, outcome)?;
             } else if code == 0 {
-                send_task(app, task_tx, BackendTask::RefreshAll)?;
+                send_task(app, task_tx, refresh_task_after([request]))?;
             }
         }
         Err(err) => {
+            app.record_audit(
+                request,
+                ExecutionMode::Foreground,
+                &CommandResult::not_run(format!("{err:#}")),
+            );
             app.log(format!("foreground action error: {err:#}"));
-            if app.batch_in_progress() {
-                maybe_continue_batch(app, task_tx)?;
-            }
+            maybe_continue_batch(app, task_tx, Err(format!("{err:#}")))?;
         }
     }
 
@@ -82,12 +129,36 @@ pub(crate) fn dispatch_action_request(
     task_tx: &UnboundedSender<BackendTask>,
     request: ActionRequest,
 ) -> Result<()> {
+    if refuse_in_read_only(app, &request) {
+        return maybe_continue_batch(app, task_tx, Err("read-only mode".to_string()));
+    }
     if request.action == Action::Chattr && request.chattr_attrs.is_none() {
-        app.open_input(InputKind::ChattrAttrs, request);
+        let targets = app.chattr_targets_needing_attrs(&request);
+        app.modal = ModalState::ChattrPicker(ChattrPicker::new(request, targets.clone()));
+        if !targets.is_empty() {
+            send_task(app, task_tx, BackendTask::LoadSourceInfo { targets })?;
+        }
+        return Ok(());
+    }
+    if let Some(pattern) = app.protected_pattern_for_request(&request) {
+        match app.config.protected_path_mode {
+            ProtectedPathMode::Confirm => app.open_confirm(request, Some(pattern)),
+            ProtectedPathMode::Refuse => {
+                app.log(format!(
+                    "refused {}: {} protected by {pattern}",
+                    request.action.label(),
+                    request.target.as_ref().map_or_else(
+                        || "managed paths are".to_string(),
+                        |p| format!("{} is", p.display())
+                    )
+                ));
+                maybe_continue_batch(app, task_tx, Err(format!("protected by {pattern}")))?;
+            }
+        }
         return Ok(());
     }
     if request.action.is_dangerous() {
-        app.open_confirm(request);
+        app.open_confirm(request, None);
         return Ok(());
     }
     execute_action_request(app, task_tx, request)
@@ -98,20 +169,22 @@ pub(crate) fn execute_action_request(
     task_tx: &UnboundedSender<BackendTask>,
     request: ActionRequest,
 ) -> Result<()> {
+    if refuse_in_read_only(app, &request) {
+        return maybe_continue_batch(app, task_tx, Err("read-only mode".to_string()));
+    }
     if request.action == Action::Ignore {
+        app.note_own_write(request.target.as_deref());
         match run_internal_ignore_action(app, &request) {
             Ok(()) => {
                 if app.batch_in_progress() {
-                    maybe_continue_batch(app, task_tx)?;
+                    maybe_continue_batch(app, task_tx, Ok(()))?;
                 } else {
                     send_task(app, task_tx, BackendTask::RefreshAll)?;
                 }
             }
             Err(err) => {
                 app.log(format!("ignore action error: {err:#}"));
-                if app.batch_in_progress() {
-                    maybe_continue_batch(app, task_tx)?;
-                }
+                maybe_continue_batch(app, task_tx, Err(format!("{err:#}")))?;
             }
         }
         return Ok(());
@@ -135,26 +208,76 @@ pub(crate) fn execute_action_request(
     Ok(())
 }
 
+/// Logs and returns true when read-only mode forbids `request`.
+fn refuse_in_read_only(app: &mut App, request: &ActionRequest) -> bool {
+    if !app.config.read_only || !request.action.is_mutating() {
+        return false;
+    }
+    app.log(format!(
+        "read-only mode: {} is disabled",
+        request.action.label()
+    ));
+    true
+}
+
+/// Records the outcome of the current batch item, then either dispatches the
+/// next queued request or finishes the batch. A no-op outside of a batch.
 pub(crate) fn maybe_continue_batch(
     app: &mut App,
     task_tx: &UnboundedSender<BackendTask>,
+    outcome: Result<(), String>,
 ) -> Result<()> {
     if !app.batch_in_progress() {
         return Ok(());
     }
 
-    if let Some(next) = app.pop_next_batch_request() {
+    app.record_batch_outcome(outcome);
+    let stop = app.config.batch_error_policy == BatchErrorPolicy::Stop && app.batch_has_failure();
+    if stop {
+        app.log("batch stopped after a failure (batch_error_policy = stop)".to_string());
+    } else if let Some(next) = app.pop_next_batch_request() {
         dispatch_action_request(app, task_tx, next)?;
         return Ok(());
     }
 
-    let action = app
-        .batch_action()
-        .map_or("unknown", super::domain::Action::label);
     let total = app.batch_total();
-    app.log(format!("batch completed: action={action} total={total}"));
-    app.clear_batch();
-    send_task(app, task_tx, BackendTask::RefreshAll)
+    let Some(summary) = app.finish_batch() else {
+        return Ok(());
+    };
+    app.log(format!(
+        "batch completed: action={} total={} ok={} failed={} skipped={}",
+        summary.label,
+        total,
+        summary.passed(),
+        summary.failures().len(),
+        summary.skipped.len()
+    ));
+    let refresh = refresh_task_after(summary.results.iter().map(|result| &result.request));
+    if !summary.retry_requests().is_empty() {
+        app.open_batch_results(summary);
+    }
+    send_task(app, task_tx, refresh)
+}
+
+/// What to reload after `requests` ran: just the status of their targets
+/// when no action can have added or removed entries, otherwise everything.
+pub(crate) fn refresh_task_after<'a>(
+    requests: impl IntoIterator<Item = &'a ActionRequest>,
+) -> BackendTask {
+    let mut targets = Vec::new();
+    for request in requests {
+        match &request.target {
+            Some(target) if request.action.only_changes_target_status() => {
+                targets.push(target.clone());
+            }
+            _ => return BackendTask::RefreshAll,
+        }
+    }
+    if targets.is_empty() {
+        BackendTask::RefreshAll
+    } else {
+        BackendTask::RefreshStatusPaths { targets }
+    }
 }
 
 pub(crate) fn build_action_requests(app: &App, action: Action) -> Vec<ActionRequest> {
@@ -202,7 +325,7 @@ pub(crate) fn validate_action_requests(
             return Some("re-add is available only for files".to_string());
         }
 
-        if !app.readd_selection_is_eligible() {
+        if !app.readd_targets_are_eligible(&targets) {
             return Some("re-add is available only for modified files in status view".to_string());
         }
     }
@@ -218,6 +341,25 @@ pub(crate) fn validate_action_requests(
     None
 }
 
+/// First problem with running a staged or loaded queue; the items of each
+/// action are checked together, as one selection is when it is staged.
+pub(crate) fn validate_queue(app: &App, queue: &[ActionRequest]) -> Option<String> {
+    let mut actions: Vec<Action> = Vec::new();
+    for request in queue {
+        if !actions.contains(&request.action) {
+            actions.push(request.action);
+        }
+    }
+    actions.into_iter().find_map(|action| {
+        let group: Vec<ActionRequest> = queue
+            .iter()
+            .filter(|request| request.action == action)
+            .cloned()
+            .collect();
+        validate_action_requests(app, action, &group)
+    })
+}
+
 fn run_chezmoi_foreground(request: &ActionRequest) -> Result<(i32, u64)> {
     let args = action_to_args(request)?;
     let destination_dir = infer_destination_for_target(request.target.as_deref());
@@ -304,6 +446,38 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
     use tokio::sync::mpsc;
 
+    #[test]
+    fn refresh_after_targeted_edits_only_reloads_their_status() {
+        let request = |action, target: Option<&str>| ActionRequest {
+            action,
+            target: target.map(PathBuf::from),
+            chattr_attrs: None,
+        };
+        let readd = request(Action::ReAdd, Some("/home/user/.zshrc"));
+        let edit = request(Action::Edit, Some("/home/user/.bashrc"));
+
+        match refresh_task_after([&readd, &edit]) {
+            BackendTask::RefreshStatusPaths { targets } => assert_eq!(
+                targets,
+                vec![
+                    PathBuf::from("/home/user/.zshrc"),
+                    PathBuf::from("/home/user/.bashrc"),
+                ]
+            ),
+            other => panic!("unexpected task: {other:?}"),
+        }
+        let forget = request(Action::Forget, Some("/home/user/.zshrc"));
+        assert!(matches!(
+            refresh_task_after([&readd, &forget]),
+            BackendTask::RefreshAll
+        ));
+        assert!(matches!(
+            refresh_task_after([&request(Action::Apply, None)]),
+            BackendTask::RefreshAll
+        ));
+        assert!(matches!(refresh_task_after([]), BackendTask::RefreshAll));
+    }
+
     #[test]
     fn squash_lines_limits_output() {
         let text = "a\n\n b\n c \n d\n e\n f\n";
@@ -1,20 +1,45 @@
+use crate::actions::squash_lines;
+use crate::audit::{AuditLog, AuditSession, ExecutionMode, load_audit_sessions};
+use crate::binary::HexPage;
+use crate::chattr::{ChattrPicker, SourceInfo};
 use crate::config::AppConfig;
+use crate::diff::{Hunk, hunks, split_rows, strip_ansi};
 use crate::domain::{
     Action, ActionRequest, ChangeKind, CommandResult, DiffText, ListView, StatusEntry,
 };
-use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use crate::exclude::UnmanagedExcludes;
+use crate::fuzzy::{FinderCandidate, FuzzyFinder};
+use crate::hunk::HunkDirection;
+use crate::layout::{LogPlacement, PaneAreas, PaneLayout, load_layout, save_layout};
+use crate::merge::MergeView;
+use crate::metadata::EntryMetadata;
+use crate::mouse::ScreenLayout;
+use crate::plan::default_plan_path;
+use crate::policy::protected_pattern;
+use crate::repo_diff::RepoDiffBrowser;
+use crate::search::{ContentMatch, ContentResults, ContentSearch, SearchJump, SearchScope};
+use crate::sort::{
+    GroupMode, SortMode, ViewOrder, ViewOrders, change_group_label, change_rank,
+    directory_group_label, effective_change, load_view_orders, save_view_orders,
+};
+use crate::theme::Theme;
+use crate::unmanaged_index::{IndexBatch, UnmanagedIndexJob};
+use crate::watch::{WatchSet, WatchTree};
+use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
 const MAX_LOG_LINES: usize = 500;
+/// How long after the app's own write watcher reports about the written
+/// paths are dropped: one poll to notice plus one quiet poll, with slack.
+const OWN_WRITE_GRACE: Duration = Duration::from_secs(5);
 const LIST_FILTER_DEBOUNCE_MS: u64 = 120;
-const INITIAL_UNMANAGED_FILTER_INDEX_ENTRIES: usize = 50_000;
-const UNMANAGED_FILTER_INDEX_STEP: usize = 50_000;
-const MAX_UNMANAGED_FILTER_INDEX_ENTRIES: usize = 200_000;
-const LIVE_FILTER_INITIAL_UNMANAGED_INDEX_ENTRIES: usize = 2_000;
-const LIVE_FILTER_UNMANAGED_INDEX_STEP: usize = 2_000;
-const LIVE_FILTER_MAX_UNMANAGED_INDEX_ENTRIES: usize = 20_000;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PaneFocus {
@@ -39,6 +64,12 @@ pub enum DetailKind {
     Preview,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLayout {
+    Unified,
+    Split,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfirmStep {
     Primary,
@@ -60,6 +91,8 @@ pub enum ModalState {
     Ignore {
         requests: Vec<ActionRequest>,
         selected: usize,
+        /// Add the chosen rule to the action queue instead of running it.
+        stage: bool,
     },
     ActionMenu {
         selected: usize,
@@ -69,20 +102,164 @@ pub enum ModalState {
         request: ActionRequest,
         step: ConfirmStep,
         typed: String,
+        protected_by: Option<String>,
     },
     Input {
         kind: InputKind,
         request: ActionRequest,
         value: String,
     },
+    BatchResults {
+        summary: BatchSummary,
+        selected: usize,
+    },
+    ChattrPicker(ChattrPicker),
+    Queue {
+        selected: usize,
+    },
+    HunkConfirm {
+        target: PathBuf,
+        hunk: Hunk,
+        direction: HunkDirection,
+        /// 1-based hunk number and total, for the prompt.
+        position: (usize, usize),
+        protected_by: Option<String>,
+        typed: String,
+    },
+    Merge(MergeView),
+    RepoDiff(RepoDiffBrowser),
+    Finder(FuzzyFinder),
+    ContentSearch(ContentSearch),
+    DetailSearch {
+        value: String,
+        original: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchItemState {
+    Pending,
+    Running,
+    Passed,
+    Failed,
+}
+
+/// Outcome of one request executed as part of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchItemResult {
+    pub request: ActionRequest,
+    pub succeeded: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Action label, or `mixed` for a queue of different actions.
+    pub label: String,
+    pub results: Vec<BatchItemResult>,
+    /// Requests never run because the batch stopped early.
+    pub skipped: Vec<ActionRequest>,
+    /// Whether the batch ran the action queue; a retry then keeps asking
+    /// each `chattr` for its own attributes.
+    pub from_queue: bool,
+}
+
+impl BatchSummary {
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.succeeded)
+            .count()
+    }
+
+    pub fn failures(&self) -> Vec<&BatchItemResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.succeeded)
+            .collect()
+    }
+
+    /// Requests to queue again for "retry failed": failures first, then skipped items.
+    pub fn retry_requests(&self) -> Vec<ActionRequest> {
+        self.failures()
+            .into_iter()
+            .map(|result| result.request.clone())
+            .chain(self.skipped.iter().cloned())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum BackendTask {
     RefreshAll,
-    LoadDiff { target: Option<PathBuf> },
-    LoadPreview { target: PathBuf, absolute: PathBuf },
-    RunAction { request: ActionRequest },
+    /// Only `chezmoi status`; cheap enough to run whenever the terminal
+    /// regains focus.
+    RefreshStatus,
+    /// `chezmoi status` for just these absolute targets, after an action
+    /// that can only have changed their state.
+    RefreshStatusPaths {
+        targets: Vec<PathBuf>,
+    },
+    /// `chezmoi unmanaged` for one directory of the unmanaged view,
+    /// relative to the working directory.
+    RefreshUnmanagedDir {
+        dir: PathBuf,
+    },
+    /// Walks the unmanaged tree off the worker queue, streaming
+    /// `UnmanagedIndexed` batches.
+    IndexUnmanaged {
+        job: UnmanagedIndexJob,
+    },
+    LoadDiff {
+        target: Option<PathBuf>,
+    },
+    LoadPreview {
+        target: PathBuf,
+        absolute: PathBuf,
+        /// Also look up the source entry for the metadata panel.
+        managed: bool,
+    },
+    LoadHexPage {
+        target: PathBuf,
+        absolute: PathBuf,
+        offset: u64,
+    },
+    /// Greps managed files; `targets` pairs each list path with its
+    /// absolute destination.
+    SearchContent {
+        query: String,
+        scope: SearchScope,
+        targets: Vec<(PathBuf, PathBuf)>,
+        /// Set when the search is superseded or its modal closes.
+        cancel: Arc<AtomicBool>,
+    },
+    /// Previews the source or rendered version of a managed file instead of
+    /// the destination.
+    LoadScopedPreview {
+        target: PathBuf,
+        absolute: PathBuf,
+        scope: SearchScope,
+    },
+    RunAction {
+        request: ActionRequest,
+    },
+    LoadSourceInfo {
+        targets: Vec<PathBuf>,
+    },
+    WriteHunk {
+        target: PathBuf,
+        hunk: Hunk,
+        direction: HunkDirection,
+    },
+    LoadRepoDiff,
+    LoadMerge {
+        target: PathBuf,
+    },
+    SaveMerge {
+        target: PathBuf,
+        source: PathBuf,
+        content: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +269,27 @@ pub enum BackendEvent {
         managed: Vec<PathBuf>,
         unmanaged: Vec<PathBuf>,
     },
+    StatusRefreshed {
+        status: Vec<StatusEntry>,
+    },
+    StatusPathsRefreshed {
+        targets: Vec<PathBuf>,
+        status: Vec<StatusEntry>,
+    },
+    UnmanagedDirRefreshed {
+        dir: PathBuf,
+        unmanaged: Vec<PathBuf>,
+    },
+    UnmanagedIndexed {
+        generation: u64,
+        batch: IndexBatch,
+    },
+    /// Watched paths changed on disk since the last poll; those inside the
+    /// source directory are listed apart.
+    FilesChanged {
+        paths: Vec<PathBuf>,
+        source_paths: Vec<PathBuf>,
+    },
     DiffLoaded {
         target: Option<PathBuf>,
         diff: DiffText,
@@ -99,17 +297,80 @@ pub enum BackendEvent {
     PreviewLoaded {
         target: PathBuf,
         content: String,
+        metadata: Option<EntryMetadata>,
+        hex: Option<HexPage>,
+    },
+    HexPageLoaded {
+        target: PathBuf,
+        content: String,
+        page: HexPage,
+    },
+    ContentSearched {
+        query: String,
+        scope: SearchScope,
+        results: ContentResults,
     },
     ActionFinished {
         request: ActionRequest,
         result: CommandResult,
     },
+    SourceInfoLoaded {
+        targets: Vec<PathBuf>,
+        sources: Vec<SourceInfo>,
+    },
+    HunkWritten {
+        target: PathBuf,
+        direction: HunkDirection,
+        written: PathBuf,
+    },
+    /// A hunk write failed; `written` is the file it was aimed at.
+    HunkFailed {
+        direction: HunkDirection,
+        written: PathBuf,
+        message: String,
+    },
+    RepoDiffLoaded {
+        diff: DiffText,
+    },
+    MergeLoaded {
+        target: PathBuf,
+        source: PathBuf,
+        ours: String,
+        base: String,
+        theirs: String,
+    },
+    MergeSaved {
+        target: PathBuf,
+        source: PathBuf,
+    },
+    /// Writing the merged `source` failed.
+    MergeSaveFailed {
+        source: PathBuf,
+        message: String,
+    },
+    /// `request` could not be started, or its worker task failed.
+    ActionFailed {
+        request: ActionRequest,
+        message: String,
+    },
     Error {
         context: String,
         message: String,
     },
 }
 
+/// Paths the app is writing or just wrote, so the watcher does not report
+/// them back as outside edits.
+#[derive(Debug, Default)]
+struct OwnWrites {
+    /// Absolute targets of the writes.
+    targets: BTreeSet<PathBuf>,
+    /// An action without a target may have written any managed file.
+    all_targets: bool,
+    /// Last time such a write started or finished.
+    at: Option<Instant>,
+}
+
 #[derive(Debug, Clone)]
 struct VisibleEntry {
     path: PathBuf,
@@ -117,6 +378,29 @@ struct VisibleEntry {
     is_dir: bool,
     can_expand: bool,
     is_symlink: bool,
+    /// Set on status group header rows, which stand for no path.
+    group: Option<GroupHeader>,
+}
+
+#[derive(Debug, Clone)]
+struct GroupHeader {
+    label: String,
+    count: usize,
+}
+
+/// Sort key of a list path; only the fields of the active mode vary.
+type ListSortKey = (
+    u8,
+    Reverse<Option<SystemTime>>,
+    Reverse<Option<u64>>,
+    String,
+);
+
+/// What the `Modified` and `Size` sorts read from a path's metadata.
+#[derive(Debug, Clone, Copy)]
+struct SortStat {
+    modified: Option<SystemTime>,
+    size: u64,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -126,13 +410,30 @@ struct DirectoryState {
     is_symlink: bool,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
+pub struct AuditHistory {
+    pub sessions: Vec<AuditSession>,
+    pub selected: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum IndexState {
+    /// Invalidated; a job is handed out once the unmanaged view is shown.
+    #[default]
+    Pending,
+    Scanning,
+    Complete,
+}
+
+/// Every unmanaged path under the visible entries, streamed in by a
+/// background `UnmanagedIndexJob` so the list filter can search all of it.
+#[derive(Debug, Default)]
 struct UnmanagedFilterCache {
     entries: Vec<PathBuf>,
-    seen: BTreeSet<PathBuf>,
-    frontier: VecDeque<PathBuf>,
-    initialized: bool,
-    scan_complete: bool,
+    /// Bumped on invalidation; batches of older jobs are dropped.
+    generation: u64,
+    state: IndexState,
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 pub struct App {
@@ -149,31 +450,92 @@ pub struct App {
     pub detail_text: String,
     pub detail_target: Option<PathBuf>,
     pub detail_scroll: usize,
+    /// File and source metadata shown above a preview.
+    pub detail_metadata: Option<EntryMetadata>,
+    /// Current page when the preview is a hex dump.
+    pub detail_hex: Option<HexPage>,
+    /// Pane and footer hint areas of the last frame, for mouse input.
+    pub screen: ScreenLayout,
+    pub diff_layout: DiffLayout,
+    /// First key of a two-key detail binding (`]h` / `[h`).
+    pub pending_key: Option<char>,
+    /// Case-insensitive search within the detail text; empty when inactive.
+    detail_search: String,
+    /// Line to scroll to once the preview picked from a content search
+    /// arrives.
+    search_jump: Option<SearchJump>,
+    /// Cancels the running content search; set while one is in flight.
+    content_search_cancel: Option<Arc<AtomicBool>>,
     pub logs: Vec<String>,
     pub log_tail_offset: usize,
+    pub audit_history: Option<AuditHistory>,
     pub modal: ModalState,
     list_filter: String,
     staged_list_filter: Option<String>,
     staged_filter_updated_at: Option<Instant>,
     pub busy: bool,
+    /// Files changed on disk after the lists were loaded.
+    pub stale: bool,
+    /// A refresh waiting for the current task or dialog to finish.
+    pub refresh_queued: bool,
+    /// Managed targets whose status waits for the current task or dialog
+    /// to finish; covered by `refresh_queued` when both are set.
+    pub queued_status_targets: BTreeSet<PathBuf>,
+    own_writes: OwnWrites,
+    /// The managed list changed, so the watcher needs a new `WatchSet`.
+    watch_set_outdated: bool,
     pub footer_help: bool,
+    /// Split ratios and log placement, saved between sessions.
+    pub layout: PaneLayout,
+    /// The focused pane fills the screen.
+    pub zoomed: bool,
+    pub theme: Theme,
     pub pending_foreground: Option<ActionRequest>,
     pub should_quit: bool,
     pub(crate) home_dir: PathBuf,
     working_dir: PathBuf,
     expanded_dirs: BTreeSet<PathBuf>,
+    view_orders: ViewOrders,
+    /// Metadata the `Modified` and `Size` sorts read, by resolved path, so
+    /// rebuilds do not stat every entry again; cleared on refresh.
+    sort_stats: RefCell<HashMap<PathBuf, Option<SortStat>>>,
+    /// Collapsed status group headers, by label.
+    collapsed_groups: BTreeSet<String>,
     marked_entries: BTreeSet<PathBuf>,
-    batch_action: Option<Action>,
+    batch_active: bool,
     batch_total: usize,
     batch_queue: VecDeque<ActionRequest>,
+    batch_current: Option<ActionRequest>,
+    /// Whether the batch was built from one selection, so attributes picked
+    /// for its first `chattr` carry over to the rest.
+    batch_shares_chattr_attrs: bool,
+    /// Staged plan of mixed actions, run as one batch from the queue panel.
+    pub action_queue: Vec<ActionRequest>,
+    batch_results: Vec<BatchItemResult>,
     visible_entries: Vec<VisibleEntry>,
     unmanaged_filter_cache: UnmanagedFilterCache,
+    /// Noise hidden from the unmanaged view and its filter index.
+    pub(crate) unmanaged_excludes: UnmanagedExcludes,
+    audit_log: Option<AuditLog>,
 }
 
 impl App {
     pub fn new(config: AppConfig) -> Self {
         let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         let home_dir = dirs::home_dir().unwrap_or_else(|| working_dir.clone());
+        let audit_log = config.audit_log_path.clone().map(AuditLog::new);
+        let view_orders = config
+            .view_orders_path
+            .as_deref()
+            .map(load_view_orders)
+            .unwrap_or_default();
+        let layout = config
+            .layout_path
+            .as_deref()
+            .map_or(config.layout, |path| load_layout(path, config.layout));
+        let theme = config.theme.build().unwrap_or_default();
+        let unmanaged_excludes =
+            UnmanagedExcludes::new(&config.unmanaged_exclude, config.unmanaged_gitignore);
         let mut app = Self {
             config,
             focus: PaneFocus::List,
@@ -188,25 +550,51 @@ impl App {
             detail_text: String::new(),
             detail_target: None,
             detail_scroll: 0,
+            detail_metadata: None,
+            detail_hex: None,
+            screen: ScreenLayout::default(),
+            diff_layout: DiffLayout::Unified,
+            pending_key: None,
+            detail_search: String::new(),
+            search_jump: None,
+            content_search_cancel: None,
             logs: Vec::new(),
             log_tail_offset: 0,
+            audit_history: None,
             modal: ModalState::None,
             list_filter: String::new(),
             staged_list_filter: None,
             staged_filter_updated_at: None,
             busy: false,
+            stale: false,
+            refresh_queued: false,
+            queued_status_targets: BTreeSet::new(),
+            own_writes: OwnWrites::default(),
+            watch_set_outdated: false,
             footer_help: false,
+            layout,
+            zoomed: false,
+            theme,
             pending_foreground: None,
             should_quit: false,
             home_dir,
             working_dir,
             expanded_dirs: BTreeSet::new(),
+            view_orders,
+            sort_stats: RefCell::default(),
+            collapsed_groups: BTreeSet::new(),
             marked_entries: BTreeSet::new(),
-            batch_action: None,
+            batch_active: false,
             batch_total: 0,
             batch_queue: VecDeque::new(),
+            batch_current: None,
+            action_queue: Vec::new(),
+            batch_results: Vec::new(),
+            batch_shares_chattr_attrs: false,
             visible_entries: Vec::new(),
             unmanaged_filter_cache: UnmanagedFilterCache::default(),
+            unmanaged_excludes,
+            audit_log,
         };
 
         app.rebuild_visible_entries_reset();
@@ -231,9 +619,225 @@ impl App {
         self.status_entries = status;
         self.managed_entries = managed;
         self.unmanaged_entries = unmanaged;
+        self.sort_stats.get_mut().clear();
+        self.unmanaged_excludes.clear_cache();
+        self.invalidate_unmanaged_filter_index();
+        self.watch_set_outdated = true;
+    }
+
+    /// Shows or hides the unmanaged view's excluded noise. Returns whether
+    /// exclusions are now active.
+    pub fn toggle_unmanaged_excludes(&mut self) -> bool {
+        let enabled = !self.unmanaged_excludes.enabled;
+        self.unmanaged_excludes.enabled = enabled;
+        self.unmanaged_excludes.clear_cache();
+        self.invalidate_unmanaged_filter_index();
+        let selected = self.selected_path();
+        self.rebuild_visible_entries_with_selection(selected);
+        enabled
+    }
+
+    /// Replaces the status list after a status-only refresh, such as the one
+    /// run when the terminal regains focus.
+    pub fn apply_status_entries(&mut self, status: Vec<StatusEntry>) {
+        self.status_entries = status;
+        self.sort_stats.get_mut().clear();
+    }
+
+    /// Replaces the status of `targets` (absolute paths) and everything
+    /// below them with a scoped `chezmoi status`, keeping other entries.
+    pub fn merge_status_entries(&mut self, targets: &[PathBuf], status: Vec<StatusEntry>) {
+        let scopes: Vec<&Path> = targets
+            .iter()
+            .map(|target| target.strip_prefix(&self.home_dir).unwrap_or(target))
+            .collect();
+        self.status_entries
+            .retain(|entry| !scopes.iter().any(|scope| entry.path.starts_with(scope)));
+        self.status_entries.extend(status);
+        self.status_entries.sort_by(|a, b| a.path.cmp(&b.path));
+        self.sort_stats.get_mut().clear();
+    }
+
+    /// Replaces the unmanaged entries at or below `dir` with a scoped
+    /// `chezmoi unmanaged`, keeping the rest of the list.
+    pub fn merge_unmanaged_entries(&mut self, dir: &Path, unmanaged: Vec<PathBuf>) {
+        self.unmanaged_entries.retain(|path| !path.starts_with(dir));
+        self.unmanaged_entries.extend(unmanaged);
+        self.unmanaged_entries.sort();
+        self.unmanaged_entries.dedup();
+        self.sort_stats.get_mut().clear();
         self.invalidate_unmanaged_filter_index();
     }
 
+    /// The selected directory when it was just expanded in the unmanaged
+    /// view and only lists unmanaged children chezmoi reported. Directories
+    /// that are unmanaged as a whole read their children from disk instead.
+    pub fn unmanaged_dir_to_refresh(&self) -> Option<PathBuf> {
+        if self.view != ListView::Unmanaged {
+            return None;
+        }
+        let path = self.selected_path()?;
+        let partial = self.expanded_dirs.contains(&path)
+            && !self
+                .unmanaged_entries
+                .iter()
+                .any(|entry| entry == Path::new(".") || path.starts_with(entry));
+        partial.then_some(path)
+    }
+
+    /// A background index job when the unmanaged view is shown and its
+    /// index was invalidated; the previous job, if any, is cancelled.
+    pub(crate) fn take_unmanaged_index_job(&mut self) -> Option<UnmanagedIndexJob> {
+        if self.view != ListView::Unmanaged
+            || self.unmanaged_filter_cache.state != IndexState::Pending
+        {
+            return None;
+        }
+        let roots = self
+            .unmanaged_entries
+            .iter()
+            .filter(|path| self.is_visible_in_unmanaged_view(path))
+            .cloned()
+            .collect();
+        let managed = self
+            .managed_entries
+            .iter()
+            .map(|path| self.managed_absolute_path(path))
+            .filter(|path| path.starts_with(&self.working_dir))
+            .collect();
+        let (exclude, gitignore) = if self.unmanaged_excludes.enabled {
+            (
+                self.config.unmanaged_exclude.clone(),
+                self.config.unmanaged_gitignore,
+            )
+        } else {
+            (Vec::new(), false)
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cache = &mut self.unmanaged_filter_cache;
+        cache.state = IndexState::Scanning;
+        cache.cancel = Some(cancel.clone());
+        Some(UnmanagedIndexJob {
+            generation: cache.generation,
+            working_dir: self.working_dir.clone(),
+            roots,
+            managed,
+            exclude,
+            gitignore,
+            cache_path: self.config.unmanaged_index_path.clone(),
+            cancel,
+        })
+    }
+
+    /// Adds a streamed batch to the unmanaged index and re-runs an active
+    /// filter over it. Batches of a replaced job are ignored.
+    pub(crate) fn apply_unmanaged_index_batch(&mut self, generation: u64, batch: IndexBatch) {
+        let cache = &mut self.unmanaged_filter_cache;
+        if generation != cache.generation || cache.state != IndexState::Scanning {
+            return;
+        }
+        cache.entries.extend(batch.paths);
+        if batch.done {
+            cache.state = IndexState::Complete;
+            cache.cancel = None;
+        }
+        if self.view == ListView::Unmanaged && !self.list_filter.trim().is_empty() {
+            let selected = self.selected_path();
+            self.rebuild_visible_entries_with_selection(selected);
+        }
+    }
+
+    /// Paths indexed so far while the background index is still running.
+    pub fn unmanaged_index_progress(&self) -> Option<usize> {
+        (self.unmanaged_filter_cache.state == IndexState::Scanning)
+            .then_some(self.unmanaged_filter_cache.entries.len())
+    }
+
+    /// What the filesystem watcher polls: every managed target, and the
+    /// unmanaged view's working directory, whose entries are only added or
+    /// removed, two levels deep and without the configured exclusions.
+    pub(crate) fn watch_set(&self) -> WatchSet {
+        WatchSet {
+            files: self
+                .managed_entries
+                .iter()
+                .map(|path| self.home_dir.join(path))
+                .collect(),
+            trees: vec![WatchTree {
+                root: self.working_dir.clone(),
+                max_depth: Some(2),
+                files: false,
+                exclude: self.config.unmanaged_exclude.clone(),
+                gitignore: self.config.unmanaged_gitignore,
+            }],
+        }
+    }
+
+    /// Records that the app is about to write `target` (every managed file
+    /// when `None`) and the source directory.
+    pub(crate) fn note_own_write(&mut self, target: Option<&Path>) {
+        if !self.own_writes_recent() {
+            self.own_writes = OwnWrites::default();
+        }
+        match target {
+            Some(target) => {
+                let absolute = Self::resolve_with_base(target, &self.home_dir);
+                self.own_writes.targets.insert(absolute);
+            }
+            None => self.own_writes.all_targets = true,
+        }
+        self.own_writes.at = Some(Instant::now());
+    }
+
+    /// Restarts the grace period once a noted write has finished.
+    pub(crate) fn finish_own_write(&mut self) {
+        if self.own_writes.at.is_some() {
+            self.own_writes.at = Some(Instant::now());
+        }
+    }
+
+    fn own_writes_recent(&self) -> bool {
+        self.own_writes
+            .at
+            .is_some_and(|at| at.elapsed() < OWN_WRITE_GRACE)
+    }
+
+    /// Drops watcher reports caused by the app's own recent writes: their
+    /// targets and the directories holding them. Source directory reports are
+    /// kept, since a pull or an editor may change it at the same time; the
+    /// refresh after the write absorbs them.
+    pub(crate) fn drop_own_writes(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        if !self.own_writes_recent() {
+            return paths;
+        }
+        let own = &self.own_writes;
+        paths
+            .into_iter()
+            .filter(|path| {
+                let written = if own.all_targets {
+                    self.is_absolute_path_managed(path)
+                } else {
+                    own.targets.contains(path)
+                };
+                !written
+                    && !own
+                        .targets
+                        .iter()
+                        .any(|target| target.parent() == Some(path.as_path()))
+            })
+            .collect()
+    }
+
+    /// Whether a watcher-triggered refresh is still waiting to run.
+    pub(crate) fn refresh_pending(&self) -> bool {
+        self.refresh_queued || !self.queued_status_targets.is_empty()
+    }
+
+    /// A new watch set when the managed list changed since the last call.
+    pub(crate) fn take_watch_set_update(&mut self) -> Option<WatchSet> {
+        std::mem::take(&mut self.watch_set_outdated).then(|| self.watch_set())
+    }
+
     pub fn select_next(&mut self) {
         let len = self.current_len();
         if len == 0 {
@@ -284,6 +888,88 @@ impl App {
         }
     }
 
+    /// Re-clamps every scroll offset to the pane sizes of a resized
+    /// terminal, so no pane is left scrolled past its content.
+    pub fn clamp_scroll_to(&mut self, panes: &PaneAreas) {
+        let rows = |area: Option<Rect>| {
+            area.map_or(1, |area| usize::from(area.height.saturating_sub(2)).max(1))
+        };
+        self.sync_list_scroll(rows(panes.list));
+        self.detail_scroll = self.detail_scroll.min(self.detail_max_scroll());
+        let log_lines = self
+            .audit_history_lines()
+            .map_or(self.logs.len(), |lines| lines.len());
+        self.log_tail_offset = self
+            .log_tail_offset
+            .min(log_lines.saturating_sub(rows(panes.log)));
+    }
+
+    /// Selects the row at `index` (as rendered in the list); used by mouse clicks.
+    pub fn select_visible_index(&mut self, index: usize) -> bool {
+        if index >= self.current_len() || index == self.selected_index {
+            return false;
+        }
+        self.selected_index = index;
+        true
+    }
+
+    /// Scrolls the list viewport by `lines`, dragging the selection along so
+    /// it stays visible.
+    pub fn scroll_list(&mut self, down: bool, lines: usize, viewport_rows: usize) -> bool {
+        let len = self.current_len();
+        let rows = viewport_rows.max(1);
+        let max_offset = len.saturating_sub(rows);
+        let before = (self.list_scroll, self.selected_index);
+        self.list_scroll = if down {
+            (self.list_scroll + lines).min(max_offset)
+        } else {
+            self.list_scroll.saturating_sub(lines)
+        };
+        if len > 0 {
+            self.selected_index = self
+                .selected_index
+                .clamp(self.list_scroll, (self.list_scroll + rows - 1).min(len - 1));
+        }
+        (self.list_scroll, self.selected_index) != before
+    }
+
+    /// Whether `column` (chars into the row label) falls on the row's
+    /// `[+]` / `[-]` marker.
+    pub fn is_fold_marker_at(&self, index: usize, column: usize) -> bool {
+        let Some(entry) = self.visible_entries.get(index) else {
+            return false;
+        };
+        let start = if entry.group.is_some() {
+            0
+        } else if self.view_supports_tree() && entry.can_expand {
+            entry.depth * 2 + 2
+        } else {
+            return false;
+        };
+        (start..start + 3).contains(&column)
+    }
+
+    /// Expands or collapses the selected directory or group header.
+    pub fn toggle_selected_fold(&mut self) -> bool {
+        if self.toggle_selected_group() {
+            return true;
+        }
+        let Some(entry) = self.visible_entries.get(self.selected_index) else {
+            return false;
+        };
+        if !self.view_supports_tree() || !entry.can_expand {
+            return false;
+        }
+        let path = entry.path.clone();
+        if self.expanded_dirs.contains(&path) {
+            self.collapse_tree(&path);
+            self.rebuild_visible_entries_with_selection(Some(path));
+            true
+        } else {
+            self.expand_selected_directory()
+        }
+    }
+
     pub fn current_items(&self) -> Vec<String> {
         self.visible_entries
             .iter()
@@ -294,9 +980,103 @@ impl App {
     pub fn selected_path(&self) -> Option<PathBuf> {
         self.visible_entries
             .get(self.selected_index)
+            .filter(|entry| entry.group.is_none())
             .map(|entry| entry.path.clone())
     }
 
+    /// Label of the selected status group header, if one is selected.
+    pub fn selected_group(&self) -> Option<String> {
+        self.visible_entries
+            .get(self.selected_index)
+            .and_then(|entry| entry.group.as_ref())
+            .map(|header| header.label.clone())
+    }
+
+    pub fn view_order(&self) -> ViewOrder {
+        self.view_orders.get(self.view)
+    }
+
+    /// Advances the current view's sort mode and remembers it.
+    pub fn cycle_sort_mode(&mut self) -> SortMode {
+        let view = self.view;
+        let order = self.view_orders.get_mut(view);
+        order.sort = order.sort.next(view);
+        let sort = order.sort;
+        self.rebuild_visible_entries();
+        self.save_view_orders();
+        sort
+    }
+
+    /// Advances the status view's grouping; other views are not grouped.
+    pub fn cycle_group_mode(&mut self) -> Option<GroupMode> {
+        if self.view != ListView::Status {
+            return None;
+        }
+        let group = self.view_orders.status.group.next();
+        self.view_orders.status.group = group;
+        self.collapsed_groups.clear();
+        self.rebuild_visible_entries();
+        self.save_view_orders();
+        Some(group)
+    }
+
+    /// Collapses or expands the selected group header.
+    pub fn toggle_selected_group(&mut self) -> bool {
+        let Some(label) = self.selected_group() else {
+            return false;
+        };
+        if !self.collapsed_groups.remove(&label) {
+            self.collapsed_groups.insert(label);
+        }
+        self.rebuild_visible_entries();
+        true
+    }
+
+    /// Moves focus to the next visible pane.
+    pub fn cycle_focus(&mut self) {
+        self.focus = self.focus.next();
+        if self.focus == PaneFocus::Log && self.layout.log == LogPlacement::Hidden {
+            self.focus = self.focus.next();
+        }
+    }
+
+    /// Grows or shrinks the focused pane and remembers the layout.
+    pub fn resize_focused_pane(&mut self, grow: bool) -> bool {
+        let changed = self.layout.resize(self.focus, grow);
+        if changed {
+            self.save_layout();
+        }
+        changed
+    }
+
+    /// Moves the log pane to the next placement; hiding it moves focus away.
+    pub fn cycle_log_placement(&mut self) -> LogPlacement {
+        self.layout.log = self.layout.log.next();
+        if self.layout.log == LogPlacement::Hidden && self.focus == PaneFocus::Log {
+            self.focus = PaneFocus::List;
+        }
+        self.save_layout();
+        self.layout.log
+    }
+
+    fn save_layout(&mut self) {
+        let Some(path) = self.config.layout_path.clone() else {
+            return;
+        };
+        if let Err(err) = save_layout(&path, &self.layout) {
+            self.log(format!("layout save failed: {err:#}"));
+        }
+    }
+
+    fn save_view_orders(&mut self) {
+        let Some(path) = self.config.view_orders_path.clone() else {
+            return;
+        };
+        if let Err(err) = save_view_orders(&path, &self.view_orders) {
+            self.log(format!("list order save failed: {err:#}"));
+        }
+    }
+
     pub fn selected_absolute_path(&self) -> Option<PathBuf> {
         self.selected_path()
             .map(|path| self.resolve_path_for_view(&path, self.view))
@@ -326,6 +1106,11 @@ impl App {
         let Some(path) = self.selected_path() else {
             return false;
         };
+        self.toggle_mark(path)
+    }
+
+    /// Marks or unmarks a list path of the current view.
+    pub fn toggle_mark(&mut self, path: PathBuf) -> bool {
         if self.marked_entries.contains(&path) {
             self.marked_entries.remove(&path)
         } else {
@@ -333,6 +1118,10 @@ impl App {
         }
     }
 
+    pub fn is_marked(&self, path: &Path) -> bool {
+        self.marked_entries.contains(path)
+    }
+
     pub fn clear_marked_entries(&mut self) -> bool {
         if self.marked_entries.is_empty() {
             return false;
@@ -369,43 +1158,164 @@ impl App {
 
         let mut queue = VecDeque::from(requests);
         let first = queue.pop_front()?;
-        self.batch_action = Some(first.action);
+        self.batch_active = true;
         self.batch_total = queue.len() + 1;
         self.batch_queue = queue;
+        self.batch_current = Some(first.clone());
+        self.batch_results.clear();
+        self.batch_shares_chattr_attrs = true;
         Some(first)
     }
 
+    /// Starts the staged action queue. Its items were staged separately or
+    /// loaded from a plan, so each `chattr` without attributes is asked for
+    /// its own.
+    pub fn start_queue_batch(&mut self, plan: Vec<ActionRequest>) -> Option<ActionRequest> {
+        let first = self.start_batch(plan);
+        self.batch_shares_chattr_attrs = false;
+        first
+    }
+
     pub fn pop_next_batch_request(&mut self) -> Option<ActionRequest> {
-        self.batch_queue.pop_front()
+        self.batch_current = self.batch_queue.pop_front();
+        self.batch_current.clone()
+    }
+
+    /// Records the outcome of the request currently running in the batch.
+    pub fn record_batch_outcome(&mut self, outcome: Result<(), String>) {
+        let Some(request) = self.batch_current.take() else {
+            return;
+        };
+        let (succeeded, detail) = match outcome {
+            Ok(()) => (true, String::new()),
+            Err(detail) => (false, detail),
+        };
+        self.batch_results.push(BatchItemResult {
+            request,
+            succeeded,
+            detail,
+        });
+    }
+
+    pub fn batch_has_failure(&self) -> bool {
+        self.batch_results.iter().any(|result| !result.succeeded)
+    }
+
+    /// Ends the batch and returns its per-item results; queued requests are
+    /// reported as skipped.
+    pub fn finish_batch(&mut self) -> Option<BatchSummary> {
+        if !self.batch_active {
+            return None;
+        }
+        let results = std::mem::take(&mut self.batch_results);
+        let skipped: Vec<ActionRequest> = self.batch_queue.drain(..).collect();
+        let mut actions = results
+            .iter()
+            .map(|result| result.request.action)
+            .chain(skipped.iter().map(|request| request.action));
+        let label = match actions.next() {
+            Some(first) if actions.all(|action| action == first) => first.label().to_string(),
+            Some(_) => "mixed".to_string(),
+            None => "unknown".to_string(),
+        };
+        let from_queue = !self.batch_shares_chattr_attrs;
+        self.clear_batch();
+        Some(BatchSummary {
+            label,
+            results,
+            skipped,
+            from_queue,
+        })
+    }
+
+    /// Every item of the running batch in execution order with its state.
+    pub fn batch_progress_items(&self) -> Vec<(ActionRequest, BatchItemState)> {
+        let done = self.batch_results.iter().map(|result| {
+            let state = if result.succeeded {
+                BatchItemState::Passed
+            } else {
+                BatchItemState::Failed
+            };
+            (result.request.clone(), state)
+        });
+        let running = self
+            .batch_current
+            .iter()
+            .map(|request| (request.clone(), BatchItemState::Running));
+        let pending = self
+            .batch_queue
+            .iter()
+            .map(|request| (request.clone(), BatchItemState::Pending));
+        done.chain(running).chain(pending).collect()
+    }
+
+    pub fn batch_completed_count(&self) -> usize {
+        self.batch_results.len()
+    }
+
+    pub fn open_batch_results(&mut self, summary: BatchSummary) {
+        self.modal = ModalState::BatchResults {
+            summary,
+            selected: 0,
+        };
     }
 
     pub fn batch_in_progress(&self) -> bool {
-        self.batch_action.is_some()
+        self.batch_active
     }
 
     pub fn batch_total(&self) -> usize {
         self.batch_total
     }
 
-    pub fn batch_action(&self) -> Option<Action> {
-        self.batch_action
+    /// Targets of the pending chattr requests that still need attributes:
+    /// the given request plus queued batch items without attrs.
+    pub fn chattr_targets_needing_attrs(&self, request: &ActionRequest) -> Vec<PathBuf> {
+        let shared = self.batch_shares_chattr_attrs.then_some(&self.batch_queue);
+        request
+            .target
+            .iter()
+            .chain(
+                shared
+                    .into_iter()
+                    .flatten()
+                    .filter(|queued| {
+                        queued.action == Action::Chattr && queued.chattr_attrs.is_none()
+                    })
+                    .filter_map(|queued| queued.target.as_ref()),
+            )
+            .cloned()
+            .collect()
     }
 
     pub fn apply_chattr_attrs_to_batch(&mut self, attrs: &str) {
+        if !self.batch_shares_chattr_attrs {
+            return;
+        }
         for request in &mut self.batch_queue {
-            if request.action == Action::Chattr {
+            if request.action == Action::Chattr && request.chattr_attrs.is_none() {
                 request.chattr_attrs = Some(attrs.to_string());
             }
         }
     }
 
     pub fn clear_batch(&mut self) {
-        self.batch_action = None;
+        self.batch_active = false;
         self.batch_total = 0;
         self.batch_queue.clear();
+        self.batch_current = None;
+        self.batch_results.clear();
+        self.batch_shares_chattr_attrs = false;
     }
 
     pub fn expand_selected_directory(&mut self) -> bool {
+        if let Some(label) = self.selected_group() {
+            let changed = self.collapsed_groups.remove(&label);
+            if changed {
+                self.rebuild_visible_entries();
+            }
+            return changed;
+        }
         if !self.view_supports_tree() {
             return false;
         }
@@ -426,6 +1336,9 @@ impl App {
     }
 
     pub fn collapse_selected_directory_or_parent(&mut self) -> bool {
+        if self.view == ListView::Status {
+            return self.collapse_selected_group();
+        }
         if !self.view_supports_tree() {
             return false;
         }
@@ -448,6 +1361,32 @@ impl App {
         false
     }
 
+    /// Collapses the group the selection belongs to and selects its header.
+    fn collapse_selected_group(&mut self) -> bool {
+        let Some(header) = self
+            .visible_entries
+            .iter()
+            .take(self.selected_index + 1)
+            .rev()
+            .find_map(|entry| entry.group.clone())
+        else {
+            return false;
+        };
+        if !self.collapsed_groups.insert(header.label.clone()) {
+            return false;
+        }
+        self.rebuild_visible_entries();
+        if let Some(index) = self.visible_entries.iter().position(|entry| {
+            entry
+                .group
+                .as_ref()
+                .is_some_and(|group| group.label == header.label)
+        }) {
+            self.selected_index = index;
+        }
+        true
+    }
+
     pub fn open_action_menu(&mut self) {
         self.modal = ModalState::ActionMenu {
             selected: 0,
@@ -455,11 +1394,56 @@ impl App {
         };
     }
 
-    pub fn open_ignore_menu(&mut self, requests: Vec<ActionRequest>) {
+    pub fn open_ignore_menu(&mut self, requests: Vec<ActionRequest>, stage: bool) {
         self.modal = ModalState::Ignore {
             requests,
             selected: 0,
+            stage,
+        };
+    }
+
+    pub fn plan_path(&self) -> Option<PathBuf> {
+        self.config.plan_path.clone().or_else(default_plan_path)
+    }
+
+    pub fn open_queue(&mut self) {
+        self.modal = ModalState::Queue { selected: 0 };
+    }
+
+    /// Appends requests to the action queue, skipping exact duplicates.
+    /// Returns how many were added.
+    pub fn stage_requests(&mut self, requests: Vec<ActionRequest>) -> usize {
+        let before = self.action_queue.len();
+        for request in requests {
+            if !self.action_queue.contains(&request) {
+                self.action_queue.push(request);
+            }
+        }
+        self.action_queue.len() - before
+    }
+
+    /// Swaps the item at `index` with its neighbour and returns its new index.
+    pub fn move_queue_item(&mut self, index: usize, up: bool) -> usize {
+        let len = self.action_queue.len();
+        if index >= len {
+            return index;
+        }
+        let target = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|next| *next < len)
         };
+        match target {
+            Some(target) => {
+                self.action_queue.swap(index, target);
+                target
+            }
+            None => index,
+        }
+    }
+
+    pub fn remove_queue_item(&mut self, index: usize) -> Option<ActionRequest> {
+        (index < self.action_queue.len()).then(|| self.action_queue.remove(index))
     }
 
     pub fn open_list_filter(&mut self) {
@@ -470,16 +1454,180 @@ impl App {
         };
     }
 
+    /// Opens the fuzzy finder over the entries of all three lists.
+    pub fn open_fuzzy_finder(&mut self) {
+        let status = self
+            .status_entries
+            .iter()
+            .map(|entry| (ListView::Status, entry.path.clone()));
+        let managed = self
+            .managed_entries
+            .iter()
+            .map(|path| (ListView::Managed, path.clone()));
+        let mut unmanaged = self.unmanaged_tree_nodes();
+        if self
+            .unmanaged_entries
+            .iter()
+            .any(|path| path == Path::new("."))
+        {
+            unmanaged.extend(self.read_children(Path::new(".")));
+        }
+        let unmanaged = unmanaged
+            .into_iter()
+            .filter(|path| self.is_visible_in_unmanaged_view(path))
+            .map(|path| (ListView::Unmanaged, path));
+
+        let candidates = status
+            .chain(managed)
+            .chain(unmanaged)
+            .map(|(view, path)| FinderCandidate { view, path })
+            .collect();
+        self.modal = ModalState::Finder(FuzzyFinder::new(candidates));
+    }
+
+    pub fn open_content_search(&mut self) {
+        self.modal = ModalState::ContentSearch(ContentSearch::default());
+    }
+
+    /// Marks the open content search as running and returns the task that
+    /// greps every managed file for its query. The task runs beside the
+    /// worker queue, so it is sent directly rather than through `send_task`;
+    /// a search still running is cancelled.
+    pub fn content_search_task(&mut self) -> Option<BackendTask> {
+        let targets = self
+            .managed_entries
+            .iter()
+            .map(|path| {
+                (
+                    path.clone(),
+                    self.resolve_path_for_view(path, ListView::Managed),
+                )
+            })
+            .collect();
+        let ModalState::ContentSearch(search) = &mut self.modal else {
+            return None;
+        };
+        if search.query.is_empty() {
+            return None;
+        }
+        search.searching = true;
+        search.message = None;
+        let (query, scope) = (search.query.clone(), search.scope);
+        self.cancel_content_search();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.content_search_cancel = Some(cancel.clone());
+        Some(BackendTask::SearchContent {
+            query,
+            scope,
+            targets,
+            cancel,
+        })
+    }
+
+    /// Selects the file of `hit` in the managed list and returns the task
+    /// that previews it in `scope`; the preview then scrolls to the line.
+    pub fn open_search_match(
+        &mut self,
+        hit: &ContentMatch,
+        scope: SearchScope,
+        query: &str,
+    ) -> Option<BackendTask> {
+        if !self.reveal_path(ListView::Managed, &hit.path) {
+            return None;
+        }
+        self.search_jump = Some(SearchJump {
+            path: hit.path.clone(),
+            line: hit.line,
+            scope,
+            query: query.to_string(),
+        });
+        let target = hit.path.clone();
+        let absolute = self.resolve_path_for_view(&target, ListView::Managed);
+        Some(match scope {
+            SearchScope::Destination => BackendTask::LoadPreview {
+                target,
+                absolute,
+                managed: true,
+            },
+            SearchScope::Source | SearchScope::Rendered => BackendTask::LoadScopedPreview {
+                target,
+                absolute,
+                scope,
+            },
+        })
+    }
+
+    /// Scrolls a freshly loaded preview of `target` to the pending search
+    /// match and highlights the query.
+    pub fn apply_search_jump(&mut self, target: &Path) {
+        let Some(jump) = self.search_jump.take_if(|jump| jump.path == target) else {
+            return;
+        };
+        if jump.scope != SearchScope::Destination {
+            self.detail_title = format!("Preview ({}): {}", jump.scope.label(), target.display());
+        }
+        self.scroll_to_source_line(jump.line.saturating_sub(1));
+        self.set_detail_search(jump.query);
+    }
+
+    /// Switches to `view`, clears the list filter, expands the ancestors of
+    /// `path` and selects it. Returns false when the entry is not listed.
+    pub fn reveal_path(&mut self, view: ListView, path: &Path) -> bool {
+        if self.view != view {
+            self.switch_view(view);
+        } else if !self.list_filter.is_empty() {
+            self.list_filter.clear();
+            self.clear_staged_list_filter();
+        }
+        if self.view == ListView::Status {
+            self.collapsed_groups.clear();
+        }
+        if self.view_supports_tree() {
+            for ancestor in path.ancestors().skip(1) {
+                if ancestor.as_os_str().is_empty() {
+                    break;
+                }
+                self.expanded_dirs.insert(ancestor.to_path_buf());
+            }
+        }
+        self.rebuild_visible_entries_with_selection(Some(path.to_path_buf()));
+        self.focus = PaneFocus::List;
+        self.selected_path().as_deref() == Some(path)
+    }
+
     pub fn toggle_footer_help(&mut self) {
         self.footer_help = !self.footer_help;
     }
 
-    pub fn open_confirm(&mut self, request: ActionRequest) {
+    pub fn open_confirm(&mut self, request: ActionRequest, protected_by: Option<String>) {
         self.modal = ModalState::Confirm {
             request,
             step: ConfirmStep::Primary,
             typed: String::new(),
+            protected_by,
+        };
+    }
+
+    pub fn protected_pattern_for(&self, target: &Path) -> Option<String> {
+        let relative = if target.is_absolute() {
+            target.strip_prefix(&self.home_dir).ok()?
+        } else {
+            target
         };
+        protected_pattern(&self.config.protected_paths, relative).map(str::to_string)
+    }
+
+    /// Protected pattern matched by `request`'s target or, for an action
+    /// without one that acts on everything, by any managed path.
+    pub fn protected_pattern_for_request(&self, request: &ActionRequest) -> Option<String> {
+        match &request.target {
+            Some(target) => self.protected_pattern_for(target),
+            None if request.action.covers_all_targets() => self
+                .managed_entries
+                .iter()
+                .find_map(|path| self.protected_pattern_for(path)),
+            None => None,
+        }
     }
 
     pub fn open_input(&mut self, kind: InputKind, request: ActionRequest) {
@@ -491,9 +1639,19 @@ impl App {
     }
 
     pub fn close_modal(&mut self) {
+        if matches!(self.modal, ModalState::ContentSearch(_)) {
+            self.cancel_content_search();
+        }
         self.modal = ModalState::None;
     }
 
+    /// Stops the running content search, if any; its results are not sent.
+    pub fn cancel_content_search(&mut self) {
+        if let Some(cancel) = self.content_search_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
     pub fn list_filter(&self) -> &str {
         &self.list_filter
     }
@@ -525,19 +1683,112 @@ impl App {
         if self.list_filter == value {
             return;
         }
-        self.list_filter = value;
-        self.rebuild_visible_entries();
+        self.list_filter = value;
+        self.rebuild_visible_entries();
+    }
+
+    pub fn log(&mut self, line: String) {
+        self.logs.push(line);
+        if self.log_tail_offset > 0 {
+            self.log_tail_offset = self.log_tail_offset.saturating_add(1);
+        }
+        if self.logs.len() > MAX_LOG_LINES {
+            let to_trim = self.logs.len() - MAX_LOG_LINES;
+            self.logs.drain(0..to_trim);
+        }
+    }
+
+    pub fn record_audit(
+        &mut self,
+        request: &ActionRequest,
+        mode: ExecutionMode,
+        result: &CommandResult,
+    ) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+        if let Err(err) = audit_log.record(request, mode, result) {
+            self.log(format!("audit log write failed: {err:#}"));
+        }
+    }
+
+    /// Audits a file the app wrote directly; a failed write is recorded with
+    /// exit code 1 and its error.
+    pub fn record_write_audit(&mut self, action: &str, path: &Path, outcome: Result<(), &str>) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+        let result = CommandResult {
+            exit_code: i32::from(outcome.is_err()),
+            stdout: String::new(),
+            stderr: outcome.err().unwrap_or_default().to_string(),
+            duration_ms: 0,
+        };
+        if let Err(err) = audit_log.record_write(action, path, &result) {
+            self.log(format!("audit log write failed: {err:#}"));
+        }
+    }
+
+    pub fn toggle_audit_history(&mut self) {
+        if self.audit_history.take().is_some() {
+            self.log_tail_offset = 0;
+            return;
+        }
+
+        let Some(audit_log) = &self.audit_log else {
+            self.log("audit log is disabled".to_string());
+            return;
+        };
+        match load_audit_sessions(audit_log.path()) {
+            Ok(sessions) if sessions.is_empty() => {
+                self.log(format!(
+                    "audit log is empty: {}",
+                    audit_log.path().display()
+                ));
+            }
+            Ok(sessions) => {
+                let selected = sessions.len() - 1;
+                self.audit_history = Some(AuditHistory { sessions, selected });
+                self.log_tail_offset = 0;
+            }
+            Err(err) => self.log(format!("audit log read failed: {err:#}")),
+        }
+    }
+
+    pub fn select_audit_session(&mut self, forward: bool) -> bool {
+        let Some(history) = &mut self.audit_history else {
+            return false;
+        };
+        let before = history.selected;
+        history.selected = if forward {
+            (history.selected + 1).min(history.sessions.len().saturating_sub(1))
+        } else {
+            history.selected.saturating_sub(1)
+        };
+        if history.selected == before {
+            return false;
+        }
+        self.log_tail_offset = 0;
+        true
     }
 
-    pub fn log(&mut self, line: String) {
-        self.logs.push(line);
-        if self.log_tail_offset > 0 {
-            self.log_tail_offset = self.log_tail_offset.saturating_add(1);
-        }
-        if self.logs.len() > MAX_LOG_LINES {
-            let to_trim = self.logs.len() - MAX_LOG_LINES;
-            self.logs.drain(0..to_trim);
+    pub fn audit_history_lines(&self) -> Option<Vec<String>> {
+        let history = self.audit_history.as_ref()?;
+        let session = history.sessions.get(history.selected)?;
+        let mut lines = Vec::with_capacity(session.records.len());
+        for record in &session.records {
+            lines.push(record.summary_line());
+            if !record.stderr.trim().is_empty() {
+                lines.push(format!("  stderr: {}", squash_lines(&record.stderr)));
+            }
         }
+        Some(lines)
+    }
+
+    pub fn is_current_audit_session(&self, id: &str) -> bool {
+        self.audit_log
+            .as_ref()
+            .is_some_and(|audit_log| audit_log.session() == id)
     }
 
     pub fn scroll_log_up(&mut self, lines: usize) -> bool {
@@ -622,6 +1873,19 @@ impl App {
             })
     }
 
+    /// Whether every target (absolute) is a file `chezmoi status` reports as
+    /// modified in the destination; checked again when a queue is replayed.
+    pub(crate) fn readd_targets_are_eligible(&self, targets: &[&Path]) -> bool {
+        !targets.is_empty()
+            && targets.iter().all(|target| {
+                let relative = target.strip_prefix(&self.home_dir).unwrap_or(target);
+                !target.is_dir()
+                    && self.status_entries.iter().any(|entry| {
+                        entry.path == relative && entry.actual_vs_target == ChangeKind::Modified
+                    })
+            })
+    }
+
     fn selected_status_entries_for_actions(&self) -> Vec<&StatusEntry> {
         if self.view != ListView::Status {
             return Vec::new();
@@ -647,6 +1911,9 @@ impl App {
     }
 
     fn action_visible(&self, action: Action) -> bool {
+        if self.config.read_only && action.is_mutating() {
+            return false;
+        }
         if action == Action::ReAdd {
             return self.readd_selection_is_eligible();
         }
@@ -743,8 +2010,192 @@ impl App {
         true
     }
 
+    /// Whether the detail pane currently renders the side-by-side diff.
+    pub fn split_diff_active(&self) -> bool {
+        self.detail_kind == DetailKind::Diff && self.diff_layout == DiffLayout::Split
+    }
+
     fn detail_max_scroll(&self) -> usize {
-        self.detail_text.lines().count().saturating_sub(1)
+        let lines = if self.split_diff_active() {
+            split_rows(&self.detail_text).len()
+        } else {
+            self.detail_text.lines().count()
+        };
+        lines.saturating_sub(1)
+    }
+
+    /// Switches between unified and side-by-side diff, keeping the same diff
+    /// line at the top of the detail pane.
+    pub fn toggle_diff_layout(&mut self) {
+        let rows = split_rows(&self.detail_text);
+        match self.diff_layout {
+            DiffLayout::Unified => {
+                self.detail_scroll = rows
+                    .iter()
+                    .position(|row| row.source_line >= self.detail_scroll)
+                    .unwrap_or(rows.len().saturating_sub(1));
+                self.diff_layout = DiffLayout::Split;
+            }
+            DiffLayout::Split => {
+                self.detail_scroll = rows
+                    .get(self.detail_scroll)
+                    .map_or(0, |row| row.source_line);
+                self.diff_layout = DiffLayout::Unified;
+            }
+        }
+    }
+
+    /// Unified diff line shown at the top of the detail pane.
+    fn detail_source_line(&self) -> usize {
+        if self.split_diff_active() {
+            split_rows(&self.detail_text)
+                .get(self.detail_scroll)
+                .map_or(0, |row| row.source_line)
+        } else {
+            self.detail_scroll
+        }
+    }
+
+    fn scroll_to_source_line(&mut self, line: usize) {
+        self.detail_scroll = if self.split_diff_active() {
+            split_rows(&self.detail_text)
+                .iter()
+                .position(|row| row.source_line >= line)
+                .unwrap_or(0)
+        } else {
+            line
+        };
+    }
+
+    pub fn detail_search(&self) -> &str {
+        &self.detail_search
+    }
+
+    pub fn open_detail_search(&mut self) {
+        self.modal = ModalState::DetailSearch {
+            value: self.detail_search.clone(),
+            original: self.detail_search.clone(),
+        };
+    }
+
+    /// Sets the search query and moves to the first match at or below the
+    /// current position, so typing refines the search in place.
+    pub fn set_detail_search(&mut self, query: String) {
+        self.detail_search = query;
+        let top = self.detail_source_line();
+        if let Some(line) = self
+            .detail_match_lines()
+            .into_iter()
+            .find(|line| *line >= top)
+        {
+            self.scroll_to_source_line(line);
+        }
+    }
+
+    /// Detail text lines containing the search query.
+    pub fn detail_match_lines(&self) -> Vec<usize> {
+        if self.detail_search.is_empty() {
+            return Vec::new();
+        }
+        let query = self.detail_search.to_ascii_lowercase();
+        self.detail_text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| strip_ansi(line).to_ascii_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Jumps to the next or previous match, wrapping around.
+    pub fn jump_detail_match(&mut self, forward: bool) -> bool {
+        let matches = self.detail_match_lines();
+        let top = self.detail_source_line();
+        let next = if forward {
+            matches.iter().find(|line| **line > top).or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|line| **line < top)
+                .or(matches.last())
+        };
+        let Some(line) = next.copied() else {
+            return false;
+        };
+        self.scroll_to_source_line(line);
+        true
+    }
+
+    /// 1-based index of the last match at or above the top line (0 when the
+    /// view is above the first match), with the match count.
+    pub fn detail_match_position(&self) -> Option<(usize, usize)> {
+        let matches = self.detail_match_lines();
+        if matches.is_empty() {
+            return None;
+        }
+        let top = self.detail_source_line();
+        let current = matches.iter().filter(|line| **line <= top).count();
+        Some((current, matches.len()))
+    }
+
+    pub(crate) fn diff_hunks(&self) -> Vec<Hunk> {
+        if self.detail_kind == DetailKind::Diff {
+            hunks(&self.detail_text)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Hunk under the top of the detail pane (the first one when scrolled
+    /// above it), with the total hunk count.
+    pub fn current_hunk(&self) -> Option<(usize, usize)> {
+        let hunks = self.diff_hunks();
+        if hunks.is_empty() {
+            return None;
+        }
+        let top = self.detail_source_line();
+        let index = hunks.iter().rposition(|hunk| hunk.line <= top).unwrap_or(0);
+        Some((index, hunks.len()))
+    }
+
+    pub fn jump_hunk(&mut self, forward: bool) -> bool {
+        let top = self.detail_source_line();
+        let hunks = self.diff_hunks();
+        let next = if forward {
+            hunks.iter().find(|hunk| hunk.line > top)
+        } else {
+            hunks.iter().rev().find(|hunk| hunk.line < top)
+        };
+        let Some(line) = next.map(|hunk| hunk.line) else {
+            return false;
+        };
+        self.scroll_to_source_line(line);
+        true
+    }
+
+    /// Opens the confirmation for writing the current hunk. Returns an error
+    /// message when there is nothing to accept.
+    pub fn open_hunk_confirm(&mut self, direction: HunkDirection) -> Result<(), String> {
+        let Some((index, total)) = self.current_hunk() else {
+            return Err("no diff hunk to accept".to_string());
+        };
+        let hunk = self.diff_hunks().swap_remove(index);
+        let target = hunk
+            .file
+            .as_ref()
+            .map(|file| self.home_dir.join(file))
+            .or_else(|| self.detail_target.clone())
+            .ok_or_else(|| "hunk has no target file".to_string())?;
+        let protected_by = self.protected_pattern_for(&target);
+        self.modal = ModalState::HunkConfirm {
+            target,
+            hunk,
+            direction,
+            position: (index + 1, total),
+            protected_by,
+            typed: String::new(),
+        };
+        Ok(())
     }
 
     pub fn set_detail_diff(&mut self, target: Option<&Path>, text: String) {
@@ -756,6 +2207,8 @@ impl App {
         self.detail_text = text;
         self.detail_target = target.map(Path::to_path_buf);
         self.detail_scroll = 0;
+        self.detail_metadata = None;
+        self.detail_hex = None;
     }
 
     pub fn set_detail_preview(&mut self, target: &Path, content: String) {
@@ -764,6 +2217,31 @@ impl App {
         self.detail_text = content;
         self.detail_target = Some(target.to_path_buf());
         self.detail_scroll = 0;
+        self.detail_metadata = None;
+        self.detail_hex = None;
+    }
+
+    /// Swaps in another hex page of the previewed file, keeping its metadata.
+    pub fn set_detail_hex_page(&mut self, target: &Path, content: String, page: HexPage) {
+        if self.detail_kind != DetailKind::Preview || self.detail_target.as_deref() != Some(target)
+        {
+            return;
+        }
+        self.detail_text = content;
+        self.detail_scroll = 0;
+        self.detail_hex = Some(page);
+    }
+
+    /// Request for the next or previous hex page of the previewed file.
+    pub fn hex_page_task(&self, forward: bool) -> Option<BackendTask> {
+        let offset = self.detail_hex?.step(forward)?;
+        let target = self.detail_target.clone()?;
+        let absolute = self.resolve_path_for_view(&target, self.view);
+        Some(BackendTask::LoadHexPage {
+            target,
+            absolute,
+            offset,
+        })
     }
 
     pub fn clear_detail(&mut self) {
@@ -771,6 +2249,8 @@ impl App {
         self.detail_text.clear();
         self.detail_target = None;
         self.detail_scroll = 0;
+        self.detail_metadata = None;
+        self.detail_hex = None;
     }
 
     fn rebuild_visible_entries_reset(&mut self) {
@@ -779,13 +2259,21 @@ impl App {
 
     fn rebuild_visible_entries_with_selection(&mut self, preferred: Option<PathBuf>) {
         let previous = preferred.or_else(|| self.selected_path());
+        let previous_group = self.selected_group();
         let filtering = !self.list_filter.trim().is_empty();
 
-        let entries = if filtering {
+        let mut entries = if filtering {
             self.build_filtered_visible_entries()
         } else {
             self.build_unfiltered_visible_entries()
         };
+        if self.view == ListView::Status {
+            let ranks = self.change_ranks(ListView::Status);
+            entries.sort_by_cached_key(|entry| {
+                self.list_sort_key(&entry.path, ListView::Status, &ranks)
+            });
+            entries = self.group_status_entries(entries);
+        }
 
         self.visible_entries = entries;
         let visible_paths: HashSet<PathBuf> = self
@@ -802,10 +2290,71 @@ impl App {
             self.selected_index = idx;
             return;
         }
+        if let Some(label) = previous_group
+            && let Some(idx) = self
+                .visible_entries
+                .iter()
+                .position(|e| e.group.as_ref().is_some_and(|header| header.label == label))
+        {
+            self.selected_index = idx;
+            return;
+        }
 
         self.sync_selection_bounds();
     }
 
+    /// Splits sorted status entries into groups, each led by a header row.
+    /// Groups keep the entries' order; collapsed groups show only the header.
+    fn group_status_entries(&self, entries: Vec<VisibleEntry>) -> Vec<VisibleEntry> {
+        let mode = self.view_orders.status.group;
+        if mode == GroupMode::None {
+            return entries;
+        }
+
+        let mut groups: BTreeMap<(u8, String), Vec<VisibleEntry>> = BTreeMap::new();
+        for entry in entries {
+            let key = match mode {
+                GroupMode::Change => {
+                    let kind = self
+                        .status_entries
+                        .iter()
+                        .find(|status| status.path == entry.path)
+                        .map_or(ChangeKind::None, effective_change);
+                    (change_rank(kind), change_group_label(kind))
+                }
+                GroupMode::Directory | GroupMode::None => {
+                    let label = directory_group_label(&entry.path);
+                    (u8::from(label != "~"), label)
+                }
+            };
+            groups.entry(key).or_default().push(entry);
+        }
+
+        let mut out = Vec::new();
+        for ((_, label), members) in groups {
+            let collapsed = self.collapsed_groups.contains(&label);
+            out.push(VisibleEntry {
+                path: PathBuf::new(),
+                depth: 0,
+                is_dir: false,
+                can_expand: true,
+                is_symlink: false,
+                group: Some(GroupHeader {
+                    label,
+                    count: members.len(),
+                }),
+            });
+            if !collapsed {
+                out.extend(
+                    members
+                        .into_iter()
+                        .map(|entry| VisibleEntry { depth: 1, ..entry }),
+                );
+            }
+        }
+        out
+    }
+
     fn build_unfiltered_visible_entries(&self) -> Vec<VisibleEntry> {
         let base_paths = self.base_paths_for_view();
         let mut seen = HashSet::new();
@@ -843,6 +2392,7 @@ impl App {
                 is_dir,
                 can_expand: false,
                 is_symlink: false,
+                group: None,
             });
         }
         entries
@@ -853,17 +2403,12 @@ impl App {
         let view = self.view;
         match view {
             ListView::Status => self.build_filtered_status_entries(&query),
-            ListView::Managed => self.build_filtered_tree_entries(
-                self.managed_tree_nodes().into_iter().collect(),
-                &query,
-            ),
+            ListView::Managed => {
+                let nodes = self.managed_tree_nodes();
+                self.build_filtered_tree_entries(&nodes, &query)
+            }
             ListView::Unmanaged => {
-                let source_paths = if matches!(self.modal, ModalState::ListFilter { .. }) {
-                    self.unmanaged_filter_source_paths_live(&query)
-                } else {
-                    self.unmanaged_filter_source_paths(&query)
-                };
-                self.build_filtered_tree_entries(source_paths, &query)
+                self.build_filtered_tree_entries(&self.unmanaged_filter_cache.entries, &query)
             }
         }
     }
@@ -884,14 +2429,15 @@ impl App {
                     depth: 0,
                     can_expand: false,
                     is_symlink: false,
+                    group: None,
                 })
             })
             .collect()
     }
 
-    fn build_filtered_tree_entries(
+    fn build_filtered_tree_entries<'a>(
         &self,
-        source_paths: Vec<PathBuf>,
+        source_paths: impl IntoIterator<Item = &'a PathBuf>,
         query: &str,
     ) -> Vec<VisibleEntry> {
         if query.is_empty() {
@@ -901,6 +2447,7 @@ impl App {
         let matched: BTreeSet<PathBuf> = source_paths
             .into_iter()
             .filter(|path| Self::tree_entry_name_matches_query(path, query))
+            .cloned()
             .collect();
         if matched.is_empty() {
             return Vec::new();
@@ -951,9 +2498,9 @@ impl App {
         }
 
         for siblings in children.values_mut() {
-            siblings.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+            self.sort_list_paths(siblings, self.view);
         }
-        roots.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+        self.sort_list_paths(&mut roots, self.view);
 
         let mut entries = Vec::new();
         for root in &roots {
@@ -981,6 +2528,7 @@ impl App {
             is_dir: has_children || managed_has_descendants || directory.is_dir,
             can_expand: has_children || directory.can_expand,
             is_symlink: directory.is_symlink,
+            group: None,
         });
 
         if let Some(child_paths) = children.get(path) {
@@ -990,6 +2538,63 @@ impl App {
         }
     }
 
+    /// Change rank of each status path, looked up by `list_sort_key`; empty
+    /// unless `view` sorts by change.
+    fn change_ranks(&self, view: ListView) -> HashMap<&Path, u8> {
+        if self.view_orders.get(view).sort != SortMode::Change {
+            return HashMap::new();
+        }
+        self.status_entries
+            .iter()
+            .map(|status| (status.path.as_path(), change_rank(effective_change(status))))
+            .collect()
+    }
+
+    fn list_sort_key(
+        &self,
+        path: &Path,
+        view: ListView,
+        ranks: &HashMap<&Path, u8>,
+    ) -> ListSortKey {
+        let name = path.to_string_lossy().to_string();
+        match self.view_orders.get(view).sort {
+            SortMode::Name => (0, Reverse(None), Reverse(None), name),
+            SortMode::Change => {
+                let rank = ranks
+                    .get(path)
+                    .copied()
+                    .unwrap_or(change_rank(ChangeKind::None));
+                (rank, Reverse(None), Reverse(None), name)
+            }
+            SortMode::Modified => {
+                let modified = self.sort_stat(path, view).and_then(|stat| stat.modified);
+                (0, Reverse(modified), Reverse(None), name)
+            }
+            SortMode::Size => {
+                let size = self.sort_stat(path, view).map(|stat| stat.size);
+                (0, Reverse(None), Reverse(size), name)
+            }
+        }
+    }
+
+    fn sort_stat(&self, path: &Path, view: ListView) -> Option<SortStat> {
+        let absolute = self.resolve_path_for_view(path, view);
+        if let Some(stat) = self.sort_stats.borrow().get(&absolute) {
+            return *stat;
+        }
+        let stat = fs::symlink_metadata(&absolute).ok().map(|meta| SortStat {
+            modified: meta.modified().ok(),
+            size: meta.len(),
+        });
+        self.sort_stats.borrow_mut().insert(absolute, stat);
+        stat
+    }
+
+    fn sort_list_paths(&self, paths: &mut [PathBuf], view: ListView) {
+        let ranks = self.change_ranks(view);
+        paths.sort_by_cached_key(|path| self.list_sort_key(path, view, &ranks));
+    }
+
     fn path_has_managed_descendants(&self, path: &Path) -> bool {
         self.managed_entries
             .iter()
@@ -1019,7 +2624,9 @@ impl App {
                 // When infra collapses to ".", treat it as "working dir root" and
                 // show direct children instead of rendering "./" as a tree node.
                 if base_paths.iter().any(|path| path == Path::new(".")) {
-                    return self.read_children(Path::new("."));
+                    let mut children = self.read_children(Path::new("."));
+                    self.sort_list_paths(&mut children, ListView::Unmanaged);
+                    return children;
                 }
 
                 base_paths
@@ -1027,175 +2634,6 @@ impl App {
         }
     }
 
-    fn unmanaged_filter_source_paths(&mut self, query: &str) -> Vec<PathBuf> {
-        self.unmanaged_filter_source_paths_with_limits(
-            query,
-            INITIAL_UNMANAGED_FILTER_INDEX_ENTRIES,
-            UNMANAGED_FILTER_INDEX_STEP,
-            MAX_UNMANAGED_FILTER_INDEX_ENTRIES,
-        )
-    }
-
-    fn unmanaged_filter_source_paths_live(&mut self, query: &str) -> Vec<PathBuf> {
-        self.unmanaged_filter_source_paths_with_limits_and_options(
-            query,
-            LIVE_FILTER_INITIAL_UNMANAGED_INDEX_ENTRIES,
-            LIVE_FILTER_UNMANAGED_INDEX_STEP,
-            LIVE_FILTER_MAX_UNMANAGED_INDEX_ENTRIES,
-            1,
-            false,
-        )
-    }
-
-    fn unmanaged_filter_source_paths_with_limits(
-        &mut self,
-        query: &str,
-        initial_limit: usize,
-        step: usize,
-        max_limit: usize,
-    ) -> Vec<PathBuf> {
-        self.unmanaged_filter_source_paths_with_limits_and_options(
-            query,
-            initial_limit,
-            step,
-            max_limit,
-            usize::MAX,
-            true,
-        )
-    }
-
-    fn unmanaged_filter_source_paths_with_limits_and_options(
-        &mut self,
-        query: &str,
-        initial_limit: usize,
-        step: usize,
-        max_limit: usize,
-        scan_rounds: usize,
-        allow_fallback_scan: bool,
-    ) -> Vec<PathBuf> {
-        let initial = initial_limit.min(max_limit).max(1);
-        self.scan_unmanaged_filter_index_to(initial);
-
-        let normalized_query = query.trim().to_ascii_lowercase();
-        if normalized_query.is_empty() {
-            return self.unmanaged_filter_cache.entries.clone();
-        }
-
-        let mut current_limit = self.unmanaged_filter_cache.entries.len().max(initial);
-        let mut rounds = 0usize;
-        while !self.unmanaged_filter_cache.scan_complete
-            && !self.unmanaged_index_has_match(&normalized_query)
-            && current_limit < max_limit
-            && rounds < scan_rounds
-        {
-            current_limit = (current_limit + step).min(max_limit);
-            self.scan_unmanaged_filter_index_to(current_limit);
-            rounds += 1;
-        }
-
-        if self.unmanaged_filter_cache.scan_complete
-            || self.unmanaged_index_has_match(&normalized_query)
-        {
-            return self.unmanaged_filter_cache.entries.clone();
-        }
-
-        if allow_fallback_scan && current_limit >= max_limit {
-            let mut source_paths = self.unmanaged_filter_cache.entries.clone();
-            source_paths.extend(self.fallback_unmanaged_matches_outside_index(&normalized_query));
-            return source_paths;
-        }
-
-        self.unmanaged_filter_cache.entries.clone()
-    }
-
-    fn unmanaged_index_has_match(&self, query: &str) -> bool {
-        self.unmanaged_filter_cache
-            .entries
-            .iter()
-            .any(|path| path.to_string_lossy().to_ascii_lowercase().contains(query))
-    }
-
-    fn fallback_unmanaged_matches_outside_index(&self, query: &str) -> Vec<PathBuf> {
-        if query.is_empty() || self.unmanaged_filter_cache.scan_complete {
-            return Vec::new();
-        }
-
-        let mut frontier = self.unmanaged_filter_cache.frontier.clone();
-        let mut seen = self.unmanaged_filter_cache.seen.clone();
-        let mut matches = Vec::new();
-
-        while let Some(path) = frontier.pop_front() {
-            if !seen.insert(path.clone()) {
-                continue;
-            }
-
-            if Self::tree_entry_name_matches_query(path.as_path(), query) {
-                matches.push(path.clone());
-            }
-
-            let directory = self.path_directory_state_for_view(&path, ListView::Unmanaged);
-            if directory.can_expand {
-                frontier.extend(self.read_children(&path));
-            }
-        }
-
-        matches
-    }
-
-    fn scan_unmanaged_filter_index_to(&mut self, limit: usize) {
-        self.ensure_unmanaged_filter_index_seeded();
-        if self.unmanaged_filter_cache.scan_complete {
-            return;
-        }
-
-        while self.unmanaged_filter_cache.entries.len() < limit {
-            let Some(path) = self.unmanaged_filter_cache.frontier.pop_front() else {
-                self.unmanaged_filter_cache.scan_complete = true;
-                break;
-            };
-            if !self.unmanaged_filter_cache.seen.insert(path.clone()) {
-                continue;
-            }
-
-            self.unmanaged_filter_cache.entries.push(path.clone());
-            let directory = self.path_directory_state_for_view(&path, ListView::Unmanaged);
-            if directory.can_expand {
-                self.unmanaged_filter_cache
-                    .frontier
-                    .extend(self.read_children(&path));
-            }
-        }
-
-        if self.unmanaged_filter_cache.frontier.is_empty() {
-            self.unmanaged_filter_cache.scan_complete = true;
-        }
-    }
-
-    fn ensure_unmanaged_filter_index_seeded(&mut self) {
-        if self.unmanaged_filter_cache.initialized {
-            return;
-        }
-
-        let mut roots: Vec<PathBuf> = self
-            .unmanaged_entries
-            .iter()
-            .filter(|path| self.is_visible_in_unmanaged_view(path.as_path()))
-            .cloned()
-            .collect();
-
-        if roots.iter().any(|path| path == Path::new(".")) {
-            roots.retain(|path| path != Path::new("."));
-            roots.extend(self.read_children(Path::new(".")));
-        }
-        roots.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
-
-        self.unmanaged_filter_cache.frontier = roots.into_iter().collect();
-        self.unmanaged_filter_cache.initialized = true;
-        if self.unmanaged_filter_cache.frontier.is_empty() {
-            self.unmanaged_filter_cache.scan_complete = true;
-        }
-    }
-
     fn push_visible_recursive(
         &self,
         path: &Path,
@@ -1216,13 +2654,16 @@ impl App {
             is_dir,
             can_expand: directory.can_expand,
             is_symlink: directory.is_symlink,
+            group: None,
         });
 
         if !directory.can_expand || (!force_expand && !self.expanded_dirs.contains(path)) {
             return;
         }
 
-        for child in self.read_children(path) {
+        let mut children = self.read_children(path);
+        self.sort_list_paths(&mut children, self.view);
+        for child in children {
             self.push_visible_recursive(&child, depth + 1, out, seen, force_expand);
         }
     }
@@ -1250,9 +2691,9 @@ impl App {
         }
 
         for siblings in children.values_mut() {
-            siblings.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+            self.sort_list_paths(siblings, self.view);
         }
-        roots.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+        self.sort_list_paths(&mut roots, self.view);
 
         let mut seen = HashSet::new();
         for root in &roots {
@@ -1264,7 +2705,12 @@ impl App {
         let mut nodes = BTreeSet::new();
 
         for unmanaged in &self.unmanaged_entries {
-            if unmanaged.as_os_str().is_empty() || unmanaged == Path::new(".") {
+            if unmanaged.as_os_str().is_empty()
+                || unmanaged == Path::new(".")
+                || self
+                    .unmanaged_excludes
+                    .is_excluded(&self.working_dir, unmanaged)
+            {
                 continue;
             }
 
@@ -1311,6 +2757,7 @@ impl App {
             is_dir,
             can_expand,
             is_symlink: directory.is_symlink,
+            group: None,
         });
 
         if !can_expand || (!force_expand && !self.expanded_dirs.contains(path)) {
@@ -1323,6 +2770,7 @@ impl App {
         }
         child_paths.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
         child_paths.dedup();
+        self.sort_list_paths(&mut child_paths, self.view);
 
         for child in child_paths {
             self.push_unmanaged_visible_recursive(
@@ -1359,9 +2807,9 @@ impl App {
         }
 
         for siblings in children.values_mut() {
-            siblings.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+            self.sort_list_paths(siblings, self.view);
         }
-        roots.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
+        self.sort_list_paths(&mut roots, self.view);
 
         let mut seen = HashSet::new();
         for root in &roots {
@@ -1422,6 +2870,7 @@ impl App {
             is_dir,
             can_expand,
             is_symlink: directory.is_symlink,
+            group: None,
         });
 
         if !can_expand || (!force_expand && !self.expanded_dirs.contains(path)) {
@@ -1458,7 +2907,7 @@ impl App {
                     PathBuf::from(parent).join(name)
                 }
             })
-            .filter(|path| self.is_visible_in_unmanaged_view(path.as_path()))
+            .filter(|path| self.is_visible_unmanaged_child(path.as_path()))
             .collect();
 
         children.sort_by(|a, b| a.to_string_lossy().cmp(&b.to_string_lossy()));
@@ -1483,12 +2932,32 @@ impl App {
     fn is_excluded_unmanaged_path(&self, path: &Path) -> bool {
         let abs = Self::resolve_with_base(path, &self.working_dir);
         self.is_exact_managed_path_in_working_dir(&abs)
+            || self.unmanaged_excludes.is_excluded(&self.working_dir, path)
+    }
+
+    /// `is_visible_in_unmanaged_view` for an entry read from a shown
+    /// directory, whose ancestors need no exclusion check.
+    fn is_visible_unmanaged_child(&self, path: &Path) -> bool {
+        let abs = Self::resolve_with_base(path, &self.working_dir);
+        !self.is_exact_managed_path_in_working_dir(&abs)
+            && !self
+                .unmanaged_excludes
+                .is_excluded_entry(&self.working_dir, path)
     }
 
     fn format_visible_entry(&self, entry: &VisibleEntry) -> String {
+        if let Some(header) = &entry.group {
+            let marker = if self.collapsed_groups.contains(&header.label) {
+                "[+]"
+            } else {
+                "[-]"
+            };
+            return format!("{marker} {} ({})", header.label, header.count);
+        }
+
         let marked = self.marked_entries.contains(&entry.path);
         if self.view == ListView::Status {
-            let mut label = String::new();
+            let mut label = "  ".repeat(entry.depth);
             label.push_str(if marked { "* " } else { "  " });
             if let Some(status) = self.status_entries.iter().find(|s| s.path == entry.path) {
                 label.push(status.actual_vs_state.as_symbol());
@@ -1627,7 +3096,13 @@ impl App {
     }
 
     fn invalidate_unmanaged_filter_index(&mut self) {
-        self.unmanaged_filter_cache = UnmanagedFilterCache::default();
+        let cache = &mut self.unmanaged_filter_cache;
+        if let Some(cancel) = cache.cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        cache.entries.clear();
+        cache.generation += 1;
+        cache.state = IndexState::Pending;
     }
 }
 
@@ -1638,6 +3113,15 @@ mod tests {
     use std::path::Path;
     use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+    /// Runs the pending background index job in place, as the worker would.
+    fn index_unmanaged(app: &mut App) {
+        let job = app.take_unmanaged_index_job().expect("index job");
+        crate::unmanaged_index::run_index_job(&job, |batch| {
+            app.apply_unmanaged_index_batch(job.generation, batch);
+            true
+        });
+    }
+
     #[test]
     fn status_selection_returns_path() {
         let mut app = App::new(AppConfig::default());
@@ -1821,31 +3305,82 @@ mod tests {
     }
 
     #[test]
-    fn unmanaged_tree_excludes_managed_children() {
+    fn unmanaged_tree_excludes_managed_children() {
+        let temp_root = std::env::temp_dir().join(format!(
+            "chezmoi_tui_unmanaged_filter_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let dir = temp_root.join(".config");
+        fs::create_dir_all(&dir).expect("create dir");
+        fs::write(dir.join("managed.lua"), "managed").expect("write managed");
+        fs::write(dir.join("local.lua"), "local").expect("write unmanaged");
+
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = temp_root.clone();
+        app.working_dir = temp_root.clone();
+        app.managed_entries = vec![PathBuf::from(".config/managed.lua")];
+        app.unmanaged_entries = vec![PathBuf::from(".config")];
+        app.switch_view(ListView::Unmanaged);
+
+        assert!(app.expand_selected_directory());
+        let items = app.current_items();
+        assert!(items.iter().any(|line| line.contains("local.lua")));
+        assert!(!items.iter().any(|line| line.contains("managed.lua")));
+
+        let _ = fs::remove_dir_all(temp_root);
+    }
+
+    #[test]
+    fn unmanaged_exclusions_hide_noise_from_tree_and_filter_until_toggled() {
         let temp_root = std::env::temp_dir().join(format!(
-            "chezmoi_tui_unmanaged_filter_{}_{}",
+            "chezmoi_tui_unmanaged_exclude_{}_{}",
             std::process::id(),
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .expect("time")
                 .as_nanos()
         ));
-        let dir = temp_root.join(".config");
-        fs::create_dir_all(&dir).expect("create dir");
-        fs::write(dir.join("managed.lua"), "managed").expect("write managed");
-        fs::write(dir.join("local.lua"), "local").expect("write unmanaged");
-
-        let mut app = App::new(AppConfig::default());
-        app.home_dir = temp_root.clone();
+        fs::create_dir_all(temp_root.join("dev/node_modules/pkg")).expect("create dir");
+        fs::create_dir_all(temp_root.join("dev/build")).expect("create dir");
+        fs::write(temp_root.join("dev/.gitignore"), "build/\n").expect("write");
+        fs::write(temp_root.join("dev/main.rs"), "fn main() {}").expect("write");
+        fs::write(temp_root.join("dev/build/main.o"), "").expect("write");
+
+        let mut app = App::new(AppConfig {
+            unmanaged_exclude: vec![".cache".to_string(), "node_modules/".to_string()],
+            unmanaged_gitignore: true,
+            ..AppConfig::default()
+        });
         app.working_dir = temp_root.clone();
-        app.managed_entries = vec![PathBuf::from(".config/managed.lua")];
-        app.unmanaged_entries = vec![PathBuf::from(".config")];
+        app.unmanaged_entries = vec![PathBuf::from("dev"), PathBuf::from(".cache")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         assert!(app.expand_selected_directory());
         let items = app.current_items();
-        assert!(items.iter().any(|line| line.contains("local.lua")));
-        assert!(!items.iter().any(|line| line.contains("managed.lua")));
+        assert!(items.iter().any(|line| line.contains("main.rs")));
+        assert!(!items.iter().any(|line| line.contains(".cache")));
+        assert!(!items.iter().any(|line| line.contains("node_modules")));
+        assert!(!items.iter().any(|line| line.contains("build")));
+        app.apply_list_filter_immediately("main".to_string());
+        assert!(
+            !app.current_items()
+                .iter()
+                .any(|line| line.contains("main.o"))
+        );
+
+        assert!(!app.toggle_unmanaged_excludes());
+        index_unmanaged(&mut app);
+        let items = app.current_items();
+        assert!(items.iter().any(|line| line.contains("main.o")));
+        app.apply_list_filter_immediately(String::new());
+        let items = app.current_items();
+        assert!(items.iter().any(|line| line.contains(".cache")));
+        assert!(items.iter().any(|line| line.contains("node_modules")));
 
         let _ = fs::remove_dir_all(temp_root);
     }
@@ -1948,6 +3483,7 @@ mod tests {
         app.working_dir = temp_root.clone();
         app.unmanaged_entries = vec![PathBuf::from(".cache"), PathBuf::from(".codex")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         let items = app.current_items();
         assert!(items.iter().any(|line| line.contains(".cache/")));
@@ -1961,37 +3497,9 @@ mod tests {
     }
 
     #[test]
-    fn unmanaged_filter_index_uses_breadth_first_scan_order() {
-        let temp_root = std::env::temp_dir().join(format!(
-            "chezmoi_tui_unmanaged_bfs_{}_{}",
-            std::process::id(),
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("time")
-                .as_nanos()
-        ));
-        fs::create_dir_all(temp_root.join("a/sub")).expect("create a/sub");
-        fs::create_dir_all(temp_root.join("b")).expect("create b");
-        fs::write(temp_root.join("a/sub/deep.txt"), "deep").expect("write deep");
-        fs::write(temp_root.join("b/root.txt"), "root").expect("write root");
-
-        let mut app = App::new(AppConfig::default());
-        app.working_dir = temp_root.clone();
-        app.unmanaged_entries = vec![PathBuf::from(".")];
-        app.switch_view(ListView::Unmanaged);
-
-        let indexed = app.unmanaged_filter_source_paths_with_limits("", 3, 3, 9);
-        assert_eq!(indexed[0], PathBuf::from("a"));
-        assert_eq!(indexed[1], PathBuf::from("b"));
-        assert_eq!(indexed[2], PathBuf::from("a/sub"));
-
-        let _ = fs::remove_dir_all(temp_root);
-    }
-
-    #[test]
-    fn unmanaged_filter_index_expands_limit_until_query_match() {
+    fn unmanaged_filter_searches_the_streamed_index_and_drops_stale_batches() {
         let temp_root = std::env::temp_dir().join(format!(
-            "chezmoi_tui_unmanaged_expand_{}_{}",
+            "chezmoi_tui_unmanaged_index_{}_{}",
             std::process::id(),
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -1999,56 +3507,37 @@ mod tests {
                 .as_nanos()
         ));
         fs::create_dir_all(temp_root.join("a")).expect("create a");
-        fs::create_dir_all(temp_root.join("b")).expect("create b");
-        fs::create_dir_all(temp_root.join("c")).expect("create c");
+        fs::create_dir_all(temp_root.join("c/deep/er")).expect("create c");
         fs::write(temp_root.join("a/one.txt"), "one").expect("write one");
-        fs::write(temp_root.join("b/two.txt"), "two").expect("write two");
-        fs::write(temp_root.join("c/target-skill.md"), "target").expect("write target");
+        fs::write(temp_root.join("c/deep/er/target-skill.md"), "target").expect("write target");
 
         let mut app = App::new(AppConfig::default());
         app.working_dir = temp_root.clone();
         app.unmanaged_entries = vec![PathBuf::from(".")];
+        assert!(app.take_unmanaged_index_job().is_none());
         app.switch_view(ListView::Unmanaged);
 
-        let indexed = app.unmanaged_filter_source_paths_with_limits("target-skill", 2, 2, 8);
-        assert!(
-            indexed
-                .iter()
-                .any(|path| path.ends_with(Path::new("c/target-skill.md")))
-        );
-
-        let _ = fs::remove_dir_all(temp_root);
-    }
-
-    #[test]
-    fn unmanaged_filter_index_falls_back_when_max_limit_is_reached() {
-        let temp_root = std::env::temp_dir().join(format!(
-            "chezmoi_tui_unmanaged_fallback_{}_{}",
-            std::process::id(),
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("time")
-                .as_nanos()
-        ));
-        fs::create_dir_all(temp_root.join("a")).expect("create a");
-        fs::create_dir_all(temp_root.join("b")).expect("create b");
-        fs::create_dir_all(temp_root.join("c")).expect("create c");
-        fs::write(temp_root.join("a/one.txt"), "one").expect("write one");
-        fs::write(temp_root.join("b/two.txt"), "two").expect("write two");
-        fs::write(temp_root.join("c/target-skill.md"), "target").expect("write target");
-
-        let mut app = App::new(AppConfig::default());
-        app.working_dir = temp_root.clone();
-        app.unmanaged_entries = vec![PathBuf::from(".")];
+        let stale = app.take_unmanaged_index_job().expect("index job");
+        assert!(app.take_unmanaged_index_job().is_none());
+        assert_eq!(app.unmanaged_index_progress(), Some(0));
         app.switch_view(ListView::Unmanaged);
-
-        let indexed = app.unmanaged_filter_source_paths_with_limits("target-skill", 1, 1, 2);
-        assert!(
-            indexed
-                .iter()
-                .any(|path| path.ends_with(Path::new("c/target-skill.md")))
+        assert!(stale.cancel.load(Ordering::Relaxed));
+        app.apply_unmanaged_index_batch(
+            stale.generation,
+            IndexBatch {
+                paths: vec![PathBuf::from("ghost-skill.md")],
+                done: true,
+                save_error: None,
+            },
         );
 
+        index_unmanaged(&mut app);
+        assert_eq!(app.unmanaged_index_progress(), None);
+        app.apply_list_filter_immediately("skill".to_string());
+        let items = app.current_items();
+        assert!(items.iter().any(|line| line.contains("target-skill.md")));
+        assert!(!items.iter().any(|line| line.contains("ghost")));
+
         let _ = fs::remove_dir_all(temp_root);
     }
 
@@ -2069,6 +3558,7 @@ mod tests {
         app.working_dir = temp_root.clone();
         app.unmanaged_entries = vec![PathBuf::from("skills")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         app.apply_list_filter_immediately("skills".to_string());
         let items = app.current_items();
@@ -2095,6 +3585,7 @@ mod tests {
         app.working_dir = temp_root.clone();
         app.unmanaged_entries = vec![PathBuf::from("skills")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         app.apply_list_filter_immediately("skill.md".to_string());
         let items = app.current_items();
@@ -2249,6 +3740,49 @@ mod tests {
         assert_eq!(app.log_tail_offset, 6);
     }
 
+    #[test]
+    fn audit_history_loads_sessions_and_steps_between_them() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_app_audit_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        let line = |session: &str, exit_code: i32| {
+            format!(
+                r#"{{"timestamp":"2024-01-01T00:00:00.000Z","session":"{session}","mode":"background","action":"forget","argv":["forget","--",".zshrc"],"destination":"/home/u","exit_code":{exit_code},"duration_ms":5,"stdout":"","stderr":"oops"}}"#
+            )
+        };
+        fs::write(
+            &path,
+            format!("{}\n{}\n{}\n", line("a", 0), line("b", 1), line("b", 0)),
+        )
+        .expect("write audit");
+
+        let mut app = App::new(AppConfig {
+            audit_log_path: Some(path.clone()),
+            ..AppConfig::default()
+        });
+        app.toggle_audit_history();
+        let history = app.audit_history.as_ref().expect("history loaded");
+        assert_eq!(history.sessions.len(), 2);
+        assert_eq!(history.selected, 1);
+
+        let lines = app.audit_history_lines().expect("lines");
+        assert_eq!(lines.iter().filter(|l| l.contains("[bg]")).count(), 2);
+        assert!(lines.iter().any(|l| l.contains("stderr: oops")));
+
+        assert!(app.select_audit_session(false));
+        assert!(!app.select_audit_session(false));
+        assert_eq!(app.audit_history_lines().expect("lines").len(), 2);
+
+        app.toggle_audit_history();
+        assert!(app.audit_history.is_none());
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn clear_detail_resets_preview_state() {
         let mut app = App::new(AppConfig::default());
@@ -2303,6 +3837,18 @@ mod tests {
         assert!(by_description_only.is_empty());
     }
 
+    #[test]
+    fn action_menu_indices_hide_mutating_actions_in_read_only_mode() {
+        let mut app = App::new(AppConfig {
+            read_only: true,
+            ..AppConfig::default()
+        });
+        for view in [ListView::Status, ListView::Managed, ListView::Unmanaged] {
+            app.switch_view(view);
+            assert!(app.action_menu_indices("").is_empty());
+        }
+    }
+
     #[test]
     fn action_menu_indices_are_sorted_alphabetically_by_label() {
         let mut app = App::new(AppConfig::default());
@@ -2629,6 +4175,7 @@ mod tests {
         app.working_dir = temp_root.clone();
         app.unmanaged_entries = vec![PathBuf::from(".config")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         app.apply_list_filter_immediately("init.lua".to_string());
         let items = app.current_items();
@@ -2644,6 +4191,7 @@ mod tests {
         let mut app = App::new(AppConfig::default());
         app.unmanaged_entries = vec![PathBuf::from("dev/chezmoi-tui/src/main.rs")];
         app.switch_view(ListView::Unmanaged);
+        index_unmanaged(&mut app);
 
         app.apply_list_filter_immediately("main.rs".to_string());
         let items = app.current_items();
@@ -2653,6 +4201,77 @@ mod tests {
         assert!(items.iter().any(|line| line.contains("main.rs")));
     }
 
+    #[test]
+    fn merge_status_entries_replaces_only_the_refreshed_targets() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/home/user");
+        let entry = |path: &str, kind| StatusEntry {
+            path: PathBuf::from(path),
+            actual_vs_state: kind,
+            actual_vs_target: kind,
+        };
+        app.status_entries = vec![
+            entry(".bashrc", ChangeKind::Modified),
+            entry(".config/nvim/init.lua", ChangeKind::Modified),
+            entry(".zshrc", ChangeKind::Modified),
+        ];
+
+        app.merge_status_entries(
+            &[
+                PathBuf::from("/home/user/.config/nvim"),
+                PathBuf::from("/home/user/.gitconfig"),
+            ],
+            vec![entry(".gitconfig", ChangeKind::Added)],
+        );
+
+        let paths: Vec<&Path> = app
+            .status_entries
+            .iter()
+            .map(|entry| entry.path.as_path())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new(".bashrc"),
+                Path::new(".gitconfig"),
+                Path::new(".zshrc")
+            ]
+        );
+    }
+
+    #[test]
+    fn expanding_a_partially_managed_dir_merges_a_scoped_unmanaged_refresh() {
+        let mut app = App::new(AppConfig::default());
+        app.unmanaged_entries = vec![
+            PathBuf::from("dev/agent/.claude"),
+            PathBuf::from("dev/old"),
+            PathBuf::from("skills"),
+        ];
+        app.switch_view(ListView::Unmanaged);
+        assert_eq!(app.unmanaged_dir_to_refresh(), None);
+
+        app.select_visible_index(0);
+        assert!(app.expand_selected_directory());
+        assert_eq!(app.unmanaged_dir_to_refresh(), Some(PathBuf::from("dev")));
+
+        app.merge_unmanaged_entries(
+            Path::new("dev"),
+            vec![PathBuf::from("dev/agent/.claude"), PathBuf::from("dev/new")],
+        );
+        app.rebuild_visible_entries();
+        assert_eq!(
+            app.unmanaged_entries,
+            vec![
+                PathBuf::from("dev/agent/.claude"),
+                PathBuf::from("dev/new"),
+                PathBuf::from("skills"),
+            ]
+        );
+        let items = app.current_items();
+        assert!(items.iter().any(|line| line.contains("new")));
+        assert!(!items.iter().any(|line| line.contains("old")));
+    }
+
     #[test]
     fn unmanaged_view_keeps_ancestors_for_deep_entries_without_root_placeholder() {
         let mut app = App::new(AppConfig::default());
@@ -2808,4 +4427,319 @@ mod tests {
         app.sync_list_scroll(5);
         assert_eq!(app.list_scroll(), 5);
     }
+
+    #[test]
+    fn resize_clamps_list_and_log_scroll_to_new_pane_sizes() {
+        let mut app = App::new(AppConfig::default());
+        app.managed_entries = (0..20)
+            .map(|i| PathBuf::from(format!("file-{i}")))
+            .collect();
+        app.switch_view(ListView::Managed);
+        app.selected_index = 19;
+        app.sync_list_scroll(5);
+        assert_eq!(app.list_scroll(), 15);
+        app.logs = (0..10).map(|i| format!("line {i}")).collect();
+        app.log_tail_offset = 8;
+
+        let panes = PaneAreas {
+            list: Some(Rect::new(0, 0, 30, 12)),
+            detail: Some(Rect::new(30, 0, 50, 6)),
+            log: Some(Rect::new(30, 6, 50, 6)),
+        };
+        app.clamp_scroll_to(&panes);
+        assert_eq!(app.list_scroll(), 10);
+        assert_eq!(app.log_tail_offset, 6);
+    }
+
+    #[test]
+    fn toggle_diff_layout_keeps_scroll_on_same_source_line() {
+        let mut app = App::new(AppConfig::default());
+        app.set_detail_diff(
+            Some(Path::new("/home/u/.zshrc")),
+            concat!(
+                "diff --git a/.zshrc b/.zshrc\n",
+                "@@ -1,3 +1,3 @@\n",
+                " keep\n",
+                "-old\n",
+                "+new\n",
+                " tail\n",
+            )
+            .to_string(),
+        );
+        app.detail_scroll = 5;
+
+        app.toggle_diff_layout();
+        assert_eq!(app.diff_layout, DiffLayout::Split);
+        assert_eq!(app.detail_scroll, 4);
+
+        app.toggle_diff_layout();
+        assert_eq!(app.diff_layout, DiffLayout::Unified);
+        assert_eq!(app.detail_scroll, 5);
+    }
+
+    #[test]
+    fn jump_hunk_follows_the_active_diff_layout() {
+        let mut app = App::new(AppConfig::default());
+        app.set_detail_diff(
+            Some(Path::new("/home/u/.zshrc")),
+            concat!(
+                "diff --git a/.zshrc b/.zshrc\n",
+                "@@ -1,2 +1,2 @@\n",
+                "-a\n",
+                "-b\n",
+                "+A\n",
+                "@@ -9 +8 @@\n",
+                "-z\n",
+            )
+            .to_string(),
+        );
+        assert_eq!(app.current_hunk(), Some((0, 2)));
+
+        assert!(app.jump_hunk(true));
+        assert_eq!(app.detail_scroll, 1);
+        assert!(app.jump_hunk(true));
+        assert_eq!(app.detail_scroll, 5);
+        assert!(!app.jump_hunk(true));
+
+        app.toggle_diff_layout();
+        assert_eq!(app.detail_scroll, 4);
+        assert_eq!(app.current_hunk(), Some((1, 2)));
+        assert!(app.jump_hunk(false));
+        assert_eq!(app.detail_scroll, 1);
+        assert_eq!(app.current_hunk(), Some((0, 2)));
+    }
+
+    #[test]
+    fn detail_search_jumps_between_matches_and_wraps() {
+        let mut app = App::new(AppConfig::default());
+        app.set_detail_preview(
+            Path::new("/home/u/.bashrc"),
+            "alias ll=ls\nexport PATH\nALIAS gs=git\nexport EDITOR\n".to_string(),
+        );
+
+        app.set_detail_search("alias".to_string());
+        assert_eq!(app.detail_match_lines(), vec![0, 2]);
+        assert_eq!(app.detail_match_position(), Some((1, 2)));
+
+        assert!(app.jump_detail_match(true));
+        assert_eq!(app.detail_scroll, 2);
+        assert_eq!(app.detail_match_position(), Some((2, 2)));
+        assert!(app.jump_detail_match(true));
+        assert_eq!(app.detail_scroll, 0);
+        assert!(app.jump_detail_match(false));
+        assert_eq!(app.detail_scroll, 2);
+
+        app.set_detail_search("EDITOR".to_string());
+        assert_eq!(app.detail_scroll, 3);
+        app.set_detail_search(String::new());
+        assert_eq!(app.detail_match_position(), None);
+    }
+
+    #[test]
+    fn fuzzy_finder_reveals_entry_in_its_view_with_ancestors_expanded() {
+        let mut app = App::new(AppConfig::default());
+        app.status_entries = vec![StatusEntry {
+            path: PathBuf::from(".zshrc"),
+            actual_vs_state: ChangeKind::Modified,
+            actual_vs_target: ChangeKind::None,
+        }];
+        app.managed_entries = vec![
+            PathBuf::from("dev"),
+            PathBuf::from("dev/chezmoi-tui"),
+            PathBuf::from("dev/chezmoi-tui/Cargo.toml"),
+        ];
+        app.rebuild_visible_entries();
+        app.apply_list_filter_immediately("zsh".to_string());
+
+        app.open_fuzzy_finder();
+        let ModalState::Finder(mut finder) = app.modal.clone() else {
+            panic!("expected finder modal");
+        };
+        assert_eq!(finder.candidates.len(), 4);
+        finder.set_query("ctcargo".to_string());
+        let target = finder.selected_candidate().cloned().expect("result");
+        assert_eq!(target.view, ListView::Managed);
+
+        assert!(app.reveal_path(target.view, &target.path));
+        assert_eq!(app.view, ListView::Managed);
+        assert_eq!(app.list_filter(), "");
+        assert_eq!(
+            app.selected_path(),
+            Some(PathBuf::from("dev/chezmoi-tui/Cargo.toml"))
+        );
+    }
+
+    #[test]
+    fn content_search_match_selects_file_and_scrolls_its_preview_to_the_line() {
+        let mut app = App::new(AppConfig::default());
+        app.managed_entries = vec![PathBuf::from(".config"), PathBuf::from(".config/zsh.tmpl")];
+        app.rebuild_visible_entries();
+
+        app.open_content_search();
+        assert!(app.content_search_task().is_none());
+        let ModalState::ContentSearch(search) = &mut app.modal else {
+            panic!("expected content search modal");
+        };
+        search.query = "alias".to_string();
+        search.scope = SearchScope::Rendered;
+        let Some(BackendTask::SearchContent {
+            targets,
+            scope,
+            cancel: first,
+            ..
+        }) = app.content_search_task()
+        else {
+            panic!("expected search task");
+        };
+        assert_eq!(scope, SearchScope::Rendered);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].1, app.home_dir.join(".config/zsh.tmpl"));
+        let Some(BackendTask::SearchContent { cancel: second, .. }) = app.content_search_task()
+        else {
+            panic!("expected search task");
+        };
+        assert!(first.load(Ordering::Relaxed));
+        assert!(!second.load(Ordering::Relaxed));
+
+        let hit = ContentMatch {
+            path: PathBuf::from(".config/zsh.tmpl"),
+            line: 3,
+            snippet: "alias ll='ls -l'".to_string(),
+        };
+        app.close_modal();
+        assert!(second.load(Ordering::Relaxed));
+        let task = app.open_search_match(&hit, SearchScope::Rendered, "alias");
+        assert!(matches!(
+            task,
+            Some(BackendTask::LoadScopedPreview {
+                scope: SearchScope::Rendered,
+                ..
+            })
+        ));
+        assert_eq!(app.view, ListView::Managed);
+        assert_eq!(app.selected_path(), Some(hit.path.clone()));
+
+        // A preview of another file leaves the jump pending.
+        app.set_detail_preview(Path::new(".bashrc"), "alias\n".to_string());
+        app.apply_search_jump(Path::new(".bashrc"));
+        assert_eq!(app.detail_scroll, 0);
+
+        app.set_detail_preview(&hit.path, "a\nb\nalias ll='ls -l'\nc\n".to_string());
+        app.apply_search_jump(&hit.path);
+        assert_eq!(app.detail_scroll, 2);
+        assert_eq!(app.detail_search(), "alias");
+        assert_eq!(app.detail_title, "Preview (rendered): .config/zsh.tmpl");
+    }
+
+    #[test]
+    fn status_view_sorts_by_change_and_groups_with_collapsible_headers() {
+        let mut app = App::new(AppConfig::default());
+        let status = |path: &str, kind| StatusEntry {
+            path: PathBuf::from(path),
+            actual_vs_state: ChangeKind::None,
+            actual_vs_target: kind,
+        };
+        app.status_entries = vec![
+            status(".config/git/config", ChangeKind::Added),
+            status(".config/nvim/init.lua", ChangeKind::Deleted),
+            status(".zshrc", ChangeKind::Modified),
+        ];
+        app.rebuild_visible_entries();
+
+        assert_eq!(app.cycle_sort_mode(), SortMode::Change);
+        assert_eq!(
+            app.current_items(),
+            vec![
+                "   D .config/nvim/init.lua",
+                "   M .zshrc",
+                "   A .config/git/config",
+            ]
+        );
+
+        assert_eq!(app.cycle_group_mode(), Some(GroupMode::Directory));
+        assert_eq!(
+            app.current_items(),
+            vec![
+                "[-] ~ (1)",
+                "     M .zshrc",
+                "[-] .config/ (2)",
+                "     D .config/nvim/init.lua",
+                "     A .config/git/config",
+            ]
+        );
+
+        app.selected_index = 3;
+        assert!(app.collapse_selected_directory_or_parent());
+        assert_eq!(app.selected_group().as_deref(), Some(".config/"));
+        assert_eq!(app.selected_path(), None);
+        assert_eq!(app.current_len(), 3);
+        assert!(app.toggle_selected_group());
+        assert_eq!(app.current_len(), 5);
+        assert_eq!(app.selected_group().as_deref(), Some(".config/"));
+
+        app.switch_view(ListView::Managed);
+        assert_eq!(app.view_order(), ViewOrder::default());
+        app.switch_view(ListView::Status);
+        assert_eq!(app.view_order().group, GroupMode::Directory);
+    }
+
+    #[test]
+    fn size_sort_reuses_stats_until_the_next_refresh() {
+        let root = std::env::temp_dir().join(format!(
+            "chezmoi_tui_sort_stats_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("small"), "a").expect("write");
+        fs::write(root.join("large"), "aaaa").expect("write");
+
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = root.clone();
+        app.switch_view(ListView::Managed);
+        let managed = vec![PathBuf::from("large"), PathBuf::from("small")];
+        app.apply_refresh_entries(Vec::new(), managed.clone(), Vec::new());
+        app.cycle_sort_mode();
+        assert_eq!(app.cycle_sort_mode(), SortMode::Size);
+        let order = |app: &App| -> Vec<PathBuf> {
+            app.visible_entries
+                .iter()
+                .map(|entry| entry.path.clone())
+                .collect()
+        };
+        assert_eq!(
+            order(&app),
+            [PathBuf::from("large"), PathBuf::from("small")]
+        );
+
+        fs::write(root.join("small"), "aaaaaaaa").expect("write");
+        app.rebuild_visible_entries();
+        assert_eq!(
+            order(&app),
+            [PathBuf::from("large"), PathBuf::from("small")]
+        );
+
+        app.apply_refresh_entries(Vec::new(), managed, Vec::new());
+        app.rebuild_visible_entries();
+        assert_eq!(
+            order(&app),
+            [PathBuf::from("small"), PathBuf::from("large")]
+        );
+
+        // A status-only refresh, as on focus regain, reads them again too.
+        fs::write(root.join("large"), "aaaaaaaaaaaa").expect("write");
+        app.rebuild_visible_entries();
+        assert_eq!(
+            order(&app),
+            [PathBuf::from("small"), PathBuf::from("large")]
+        );
+        app.apply_status_entries(Vec::new());
+        app.rebuild_visible_entries();
+        assert_eq!(
+            order(&app),
+            [PathBuf::from("large"), PathBuf::from("small")]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
 }
@@ -0,0 +1,353 @@
+use crate::actions::infer_destination_for_target;
+use crate::domain::{ActionRequest, CommandResult};
+use crate::infra::action_to_args;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AUDIT_OUTPUT_MAX_BYTES: usize = 4096;
+const AUDIT_HISTORY_MAX_RECORDS: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExecutionMode {
+    Background,
+    Foreground,
+}
+
+impl ExecutionMode {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ExecutionMode::Background => "bg",
+            ExecutionMode::Foreground => "fg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct AuditRecord {
+    pub timestamp: String,
+    pub session: String,
+    pub mode: ExecutionMode,
+    pub action: String,
+    pub argv: Vec<String>,
+    pub destination: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl AuditRecord {
+    pub(crate) fn summary_line(&self) -> String {
+        if self.argv.is_empty() {
+            return format!(
+                "{} [{}] exit={} {} wrote {}",
+                self.timestamp,
+                self.mode.label(),
+                self.exit_code,
+                self.action,
+                self.destination
+            );
+        }
+        format!(
+            "{} [{}] exit={} duration={}ms dest={} chezmoi {}",
+            self.timestamp,
+            self.mode.label(),
+            self.exit_code,
+            self.duration_ms,
+            self.destination,
+            self.argv.join(" ")
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AuditSession {
+    pub id: String,
+    pub records: Vec<AuditRecord>,
+}
+
+/// Append-only JSONL log of every chezmoi command executed by this process.
+#[derive(Debug, Clone)]
+pub(crate) struct AuditLog {
+    path: PathBuf,
+    session: String,
+}
+
+impl AuditLog {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            path,
+            session: format!("{}-{}", now.as_millis(), std::process::id()),
+        }
+    }
+
+    pub(crate) fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("chezmoi-tui").join("audit.jsonl"))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Records one executed request. Internal actions that do not map to a
+    /// chezmoi command line (ignore, edit-ignore) are skipped.
+    pub(crate) fn record(
+        &self,
+        request: &ActionRequest,
+        mode: ExecutionMode,
+        result: &CommandResult,
+    ) -> Result<()> {
+        let Ok(args) = action_to_args(request) else {
+            return Ok(());
+        };
+        let destination = infer_destination_for_target(request.target.as_deref());
+        let record = AuditRecord {
+            timestamp: format_rfc3339_utc(SystemTime::now()),
+            session: self.session.clone(),
+            mode,
+            action: request.action.label().to_string(),
+            argv: args
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            destination: destination.display().to_string(),
+            exit_code: result.exit_code,
+            duration_ms: result.duration_ms,
+            stdout: truncate_output(&result.stdout),
+            stderr: truncate_output(&result.stderr),
+        };
+        self.append(&record)
+    }
+
+    /// Records a file the app wrote itself rather than through chezmoi, such
+    /// as a hunk applied or re-added. `argv` is empty and `destination` is the
+    /// written file.
+    pub(crate) fn record_write(
+        &self,
+        action: &str,
+        path: &Path,
+        result: &CommandResult,
+    ) -> Result<()> {
+        let record = AuditRecord {
+            timestamp: format_rfc3339_utc(SystemTime::now()),
+            session: self.session.clone(),
+            mode: ExecutionMode::Background,
+            action: action.to_string(),
+            argv: Vec::new(),
+            destination: path.display().to_string(),
+            exit_code: result.exit_code,
+            duration_ms: result.duration_ms,
+            stdout: String::new(),
+            stderr: truncate_output(&result.stderr),
+        };
+        self.append(&record)
+    }
+
+    fn append(&self, record: &AuditRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        let mut line = serde_json::to_string(record).context("failed to encode audit record")?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {} for append", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to append to {}", self.path.display()))
+    }
+}
+
+/// Reads the most recent audit records and groups them by session, oldest first.
+/// Lines that fail to parse are skipped so a partially written tail does not
+/// hide the rest of the history.
+pub(crate) fn load_audit_sessions(path: &Path) -> Result<Vec<AuditSession>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let start = lines.len().saturating_sub(AUDIT_HISTORY_MAX_RECORDS);
+
+    let mut sessions: Vec<AuditSession> = Vec::new();
+    for line in &lines[start..] {
+        let Ok(record) = serde_json::from_str::<AuditRecord>(line) else {
+            continue;
+        };
+        match sessions.last_mut() {
+            Some(session) if session.id == record.session => session.records.push(record),
+            _ => sessions.push(AuditSession {
+                id: record.session.clone(),
+                records: vec![record],
+            }),
+        }
+    }
+
+    Ok(sessions)
+}
+
+fn truncate_output(text: &str) -> String {
+    if text.len() <= AUDIT_OUTPUT_MAX_BYTES {
+        return text.to_string();
+    }
+
+    let mut end = AUDIT_OUTPUT_MAX_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…[truncated {} bytes]", &text[..end], text.len() - end)
+}
+
+pub(crate) fn format_rfc3339_utc(time: SystemTime) -> String {
+    let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = duration.as_secs();
+    let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX);
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        duration.subsec_millis()
+    )
+}
+
+// Howard Hinnant's days-to-civil conversion for the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = u32::try_from(doy - (153 * mp + 2) / 5 + 1).unwrap_or(1);
+    let month = u32::try_from(if mp < 10 { mp + 3 } else { mp - 9 }).unwrap_or(1);
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Action;
+    use std::time::Duration;
+
+    fn temp_audit_path(tag: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "chezmoi_tui_audit_{tag}_{}_{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn rfc3339_formatting_matches_known_instants() {
+        assert_eq!(format_rfc3339_utc(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let leap_day = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(format_rfc3339_utc(leap_day), "2024-02-29T12:34:56.789Z");
+    }
+
+    #[test]
+    fn truncate_output_respects_char_boundaries() {
+        let text = "é".repeat(AUDIT_OUTPUT_MAX_BYTES);
+        let got = truncate_output(&text);
+        assert!(got.contains("truncated"));
+        assert!(got.len() < text.len());
+    }
+
+    #[test]
+    fn record_appends_jsonl_and_loads_grouped_by_session() {
+        let path = temp_audit_path("roundtrip");
+        let log = AuditLog::new(path.clone());
+        let request = ActionRequest {
+            action: Action::Forget,
+            target: Some(PathBuf::from("/tmp/home/.zshrc")),
+            chattr_attrs: None,
+        };
+        let result = CommandResult {
+            exit_code: 1,
+            stdout: String::new(),
+            stderr: "boom".to_string(),
+            duration_ms: 12,
+        };
+        log.record(&request, ExecutionMode::Background, &result)
+            .expect("record");
+        log.record(&request, ExecutionMode::Foreground, &result)
+            .expect("record");
+
+        let content = fs::read_to_string(&path).expect("read audit");
+        assert_eq!(content.lines().count(), 2);
+
+        let sessions = load_audit_sessions(&path).expect("load");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, log.session());
+        let first = &sessions[0].records[0];
+        assert_eq!(
+            first.argv,
+            vec!["forget", "--force", "--no-tty", "--", "/tmp/home/.zshrc"]
+        );
+        assert_eq!(first.exit_code, 1);
+        assert_eq!(first.stderr, "boom");
+        assert_eq!(sessions[0].records[1].mode, ExecutionMode::Foreground);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn internal_actions_are_not_recorded() {
+        let path = temp_audit_path("internal");
+        let log = AuditLog::new(path.clone());
+        let request = ActionRequest {
+            action: Action::EditIgnore,
+            target: None,
+            chattr_attrs: None,
+        };
+        let result = CommandResult {
+            exit_code: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 1,
+        };
+        log.record(&request, ExecutionMode::Foreground, &result)
+            .expect("record");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn load_audit_sessions_skips_malformed_lines() {
+        let path = temp_audit_path("malformed");
+        fs::write(&path, "{not json}\n").expect("write");
+        let sessions = load_audit_sessions(&path).expect("load");
+        assert!(sessions.is_empty());
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,12 +1,22 @@
 use crate::app::{BackendEvent, BackendTask};
+use crate::binary::load_hex_page;
+use crate::chattr::source_info;
+use crate::hunk::{HunkDirection, source_write_blocker, write_hunk};
 use crate::infra::ChezmoiClient;
+use crate::metadata::{EntryMetadata, SourceDetails, read_file_metadata};
 use crate::preview::load_file_preview;
+use crate::search::{SearchScope, search_contents};
+use crate::unmanaged_index::run_index_job;
+use anyhow::Context;
+use std::io::ErrorKind;
+use std::path::Path;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 pub(crate) async fn worker_loop(
     client: std::sync::Arc<dyn ChezmoiClient>,
     mut task_rx: UnboundedReceiver<BackendTask>,
     event_tx: UnboundedSender<BackendEvent>,
+    read_only: bool,
 ) {
     while let Some(task) = task_rx.recv().await {
         match task {
@@ -52,6 +62,68 @@ pub(crate) async fn worker_loop(
                     }
                 }
             }
+            BackendTask::RefreshStatus => {
+                let c = client.clone();
+                let event = match tokio::task::spawn_blocking(move || c.status()).await {
+                    Ok(Ok(status)) => BackendEvent::StatusRefreshed { status },
+                    other => BackendEvent::Error {
+                        context: "refresh".to_string(),
+                        message: format!("status refresh failed: {:?}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::RefreshStatusPaths { targets } => {
+                let c = client.clone();
+                let targets_for_worker = targets.clone();
+                let event =
+                    match tokio::task::spawn_blocking(move || c.status_paths(&targets_for_worker))
+                        .await
+                    {
+                        Ok(Ok(status)) => BackendEvent::StatusPathsRefreshed { targets, status },
+                        other => BackendEvent::Error {
+                            context: "refresh".to_string(),
+                            message: format!("status refresh failed: {:?}", flatten_error(other)),
+                        },
+                    };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::RefreshUnmanagedDir { dir } => {
+                let c = client.clone();
+                let dir_for_worker = dir.clone();
+                let event = match tokio::task::spawn_blocking(move || {
+                    c.unmanaged_in(&dir_for_worker)
+                })
+                .await
+                {
+                    Ok(Ok(unmanaged)) => BackendEvent::UnmanagedDirRefreshed { dir, unmanaged },
+                    other => BackendEvent::Error {
+                        context: "refresh".to_string(),
+                        message: format!("unmanaged refresh failed: {:?}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::IndexUnmanaged { job } => {
+                // Runs beside the queue so refreshes are not held up by a
+                // long walk; a newer job cancels this one.
+                let tx = event_tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    run_index_job(&job, |batch| {
+                        tx.send(BackendEvent::UnmanagedIndexed {
+                            generation: job.generation,
+                            batch,
+                        })
+                        .is_ok()
+                    });
+                });
+            }
             BackendTask::LoadDiff { target } => {
                 let c = client.clone();
                 let target_for_worker = target.clone();
@@ -79,13 +151,26 @@ pub(crate) async fn worker_loop(
                     }
                 }
             }
-            BackendTask::LoadPreview { target, absolute } => {
-                let result =
-                    tokio::task::spawn_blocking(move || load_file_preview(&absolute)).await;
+            BackendTask::LoadPreview {
+                target,
+                absolute,
+                managed,
+            } => {
+                let c = client.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    load_file_preview(&absolute)
+                        .map(|preview| (preview, load_entry_metadata(&*c, &absolute, managed)))
+                })
+                .await;
                 match result {
-                    Ok(Ok(content)) => {
+                    Ok(Ok((preview, metadata))) => {
                         if event_tx
-                            .send(BackendEvent::PreviewLoaded { target, content })
+                            .send(BackendEvent::PreviewLoaded {
+                                target,
+                                content: preview.text,
+                                metadata,
+                                hex: preview.hex,
+                            })
                             .is_err()
                         {
                             break;
@@ -104,6 +189,271 @@ pub(crate) async fn worker_loop(
                     }
                 }
             }
+            BackendTask::SearchContent {
+                query,
+                scope,
+                targets,
+                cancel,
+            } => {
+                // Runs beside the queue so a long search, `chezmoi cat` per
+                // file in Rendered scope, does not hold up other tasks; a
+                // newer search or closing the modal cancels it.
+                let c = client.clone();
+                let tx = event_tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    let event = match search_contents(&*c, &query, scope, &targets, &cancel) {
+                        Ok(Some(results)) => BackendEvent::ContentSearched {
+                            query,
+                            scope,
+                            results,
+                        },
+                        Ok(None) => return,
+                        Err(err) => BackendEvent::Error {
+                            context: "search".to_string(),
+                            message: format!("content search failed: {err:#}"),
+                        },
+                    };
+                    let _ = tx.send(event);
+                });
+            }
+            BackendTask::LoadScopedPreview {
+                target,
+                absolute,
+                scope,
+            } => {
+                let c = client.clone();
+                let result = tokio::task::spawn_blocking(move || match scope {
+                    SearchScope::Destination => load_file_preview(&absolute).map(|p| p.text),
+                    SearchScope::Source => {
+                        let source = c.source_paths(std::slice::from_ref(&absolute))?.remove(0);
+                        load_file_preview(&source).map(|p| p.text)
+                    }
+                    SearchScope::Rendered => c.cat(&absolute),
+                })
+                .await;
+                let event = match result {
+                    Ok(Ok(content)) => BackendEvent::PreviewLoaded {
+                        target,
+                        content,
+                        metadata: None,
+                        hex: None,
+                    },
+                    other => BackendEvent::Error {
+                        context: "preview".to_string(),
+                        message: format!("preview failed: {}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::LoadHexPage {
+                target,
+                absolute,
+                offset,
+            } => {
+                let result =
+                    tokio::task::spawn_blocking(move || load_hex_page(&absolute, offset)).await;
+                let event = match result {
+                    Ok(Ok((content, page))) => BackendEvent::HexPageLoaded {
+                        target,
+                        content,
+                        page,
+                    },
+                    other => BackendEvent::Error {
+                        context: "preview".to_string(),
+                        message: format!("hex page failed: {:?}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::LoadSourceInfo { targets } => {
+                let c = client.clone();
+                let targets_for_worker = targets.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    c.source_paths(&targets_for_worker).map(|paths| {
+                        paths
+                            .iter()
+                            .map(|path| source_info(path, path.is_dir()))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .await;
+                let event = match result {
+                    Ok(Ok(sources)) => BackendEvent::SourceInfoLoaded { targets, sources },
+                    other => BackendEvent::Error {
+                        context: "chattr".to_string(),
+                        message: format!("source-path failed: {:?}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::WriteHunk { direction, .. } if read_only => {
+                if event_tx
+                    .send(BackendEvent::Error {
+                        context: "hunk".to_string(),
+                        message: format!("read-only mode: refused {}", direction.label()),
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            BackendTask::WriteHunk {
+                target,
+                hunk,
+                direction,
+            } => {
+                let c = client.clone();
+                let target_for_worker = target.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    // The file aimed at, kept for the audit record on failure.
+                    let mut written = target_for_worker.clone();
+                    let outcome = (|| {
+                        let source = c
+                            .source_paths(std::slice::from_ref(&target_for_worker))?
+                            .remove(0);
+                        if direction == HunkDirection::ToSource {
+                            written = source.clone();
+                        }
+                        if let Some(kind) = source_write_blocker(&source, false) {
+                            anyhow::bail!(
+                                "partial writes are not supported for {kind}: {}",
+                                source.display()
+                            );
+                        }
+                        write_hunk(&written, &hunk, direction)
+                    })();
+                    (written, outcome)
+                })
+                .await;
+                let event = match result {
+                    Ok((written, Ok(()))) => BackendEvent::HunkWritten {
+                        target,
+                        direction,
+                        written,
+                    },
+                    Ok((written, Err(err))) => BackendEvent::HunkFailed {
+                        direction,
+                        written,
+                        message: format!("{} failed: {err:#}", direction.label()),
+                    },
+                    Err(err) => BackendEvent::HunkFailed {
+                        direction,
+                        written: target,
+                        message: format!("{} failed: {err}", direction.label()),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::LoadRepoDiff => {
+                let c = client.clone();
+                let result = tokio::task::spawn_blocking(move || c.diff(None)).await;
+                let event = match result {
+                    Ok(Ok(diff)) => BackendEvent::RepoDiffLoaded { diff },
+                    other => BackendEvent::Error {
+                        context: "diff".to_string(),
+                        message: format!("diff failed: {:?}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::LoadMerge { target } => {
+                let c = client.clone();
+                let target_for_worker = target.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let source = c
+                        .source_paths(std::slice::from_ref(&target_for_worker))?
+                        .remove(0);
+                    if let Some(kind) = source_write_blocker(&source, true) {
+                        anyhow::bail!(
+                            "the built-in merge does not support {kind}: {}",
+                            source.display()
+                        );
+                    }
+                    // A missing destination merges like an empty file.
+                    let ours = match std::fs::read_to_string(&target_for_worker) {
+                        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+                        other => other.with_context(|| {
+                            format!("failed to read {}", target_for_worker.display())
+                        })?,
+                    };
+                    let theirs = std::fs::read_to_string(&source)
+                        .with_context(|| format!("failed to read {}", source.display()))?;
+                    let base = c.cat(&target_for_worker)?;
+                    Ok((source, ours, base, theirs))
+                })
+                .await;
+                let event = match result {
+                    Ok(Ok((source, ours, base, theirs))) => BackendEvent::MergeLoaded {
+                        target,
+                        source,
+                        ours,
+                        base,
+                        theirs,
+                    },
+                    other => BackendEvent::Error {
+                        context: "merge".to_string(),
+                        message: format!("merge failed to load: {}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::SaveMerge { .. } if read_only => {
+                if event_tx
+                    .send(BackendEvent::Error {
+                        context: "merge".to_string(),
+                        message: "read-only mode: refused to save merge".to_string(),
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            BackendTask::SaveMerge {
+                target,
+                source,
+                content,
+            } => {
+                let source_for_worker = source.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    std::fs::write(&source_for_worker, content)
+                        .with_context(|| format!("failed to write {}", source_for_worker.display()))
+                })
+                .await;
+                let event = match result {
+                    Ok(Ok(())) => BackendEvent::MergeSaved { target, source },
+                    other => BackendEvent::MergeSaveFailed {
+                        source,
+                        message: format!("merge save failed: {}", flatten_error(other)),
+                    },
+                };
+                if event_tx.send(event).is_err() {
+                    break;
+                }
+            }
+            BackendTask::RunAction { request } if read_only && request.action.is_mutating() => {
+                // Defence in depth: the UI already hides mutating actions.
+                if event_tx
+                    .send(BackendEvent::Error {
+                        context: "action".to_string(),
+                        message: format!("read-only mode: refused {}", request.action.label()),
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
             BackendTask::RunAction { request } => {
                 let c = client.clone();
                 let req = request.clone();
@@ -119,8 +469,8 @@ pub(crate) async fn worker_loop(
                     }
                     other => {
                         if event_tx
-                            .send(BackendEvent::Error {
-                                context: "action".to_string(),
+                            .send(BackendEvent::ActionFailed {
+                                request,
                                 message: format!("action failed: {:?}", flatten_error(other)),
                             })
                             .is_err()
@@ -142,9 +492,163 @@ fn flatten_error<T>(res: std::result::Result<anyhow::Result<T>, tokio::task::Joi
     }
 }
 
+/// Metadata panel contents; the source lookup is best effort, so an entry
+/// chezmoi cannot resolve simply has no source section.
+fn load_entry_metadata(
+    client: &dyn ChezmoiClient,
+    absolute: &Path,
+    managed: bool,
+) -> Option<EntryMetadata> {
+    let file = read_file_metadata(absolute).ok()?;
+    let source = managed
+        .then(|| client.source_paths(&[absolute.to_path_buf()]).ok())
+        .flatten()
+        .and_then(|paths| paths.into_iter().next())
+        .map(|path| SourceDetails {
+            info: source_info(&path, path.is_dir()),
+            path,
+        });
+    Some(EntryMetadata { file, source })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::{Action, ActionRequest, ChangeKind, CommandResult, DiffText, StatusEntry};
+    use anyhow::Result;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::sync::mpsc;
+
+    #[derive(Default)]
+    struct CountingClient {
+        runs: AtomicUsize,
+    }
+
+    impl ChezmoiClient for CountingClient {
+        fn status(&self) -> Result<Vec<StatusEntry>> {
+            Ok(Vec::new())
+        }
+
+        fn status_paths(&self, targets: &[PathBuf]) -> Result<Vec<StatusEntry>> {
+            Ok(targets
+                .iter()
+                .map(|target| StatusEntry {
+                    path: target.clone(),
+                    actual_vs_state: ChangeKind::None,
+                    actual_vs_target: ChangeKind::Modified,
+                })
+                .collect())
+        }
+
+        fn managed(&self) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        fn unmanaged(&self) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        fn unmanaged_in(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+            Ok(vec![dir.join("new")])
+        }
+
+        fn diff(&self, _target: Option<&Path>) -> Result<DiffText> {
+            Ok(DiffText {
+                text: String::new(),
+            })
+        }
+
+        fn run(&self, _request: &ActionRequest) -> Result<CommandResult> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(CommandResult {
+                exit_code: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+                duration_ms: 0,
+            })
+        }
+
+        fn source_paths(&self, targets: &[PathBuf]) -> Result<Vec<PathBuf>> {
+            Ok(targets.to_vec())
+        }
+
+        fn cat(&self, target: &Path) -> Result<String> {
+            Ok(std::fs::read_to_string(target)?)
+        }
+
+        fn source_dir(&self) -> Result<PathBuf> {
+            Ok(PathBuf::from("/src"))
+        }
+    }
+
+    #[tokio::test]
+    async fn worker_loop_rejects_run_action_in_read_only_mode() {
+        let client = Arc::new(CountingClient::default());
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        task_tx
+            .send(BackendTask::RunAction {
+                request: ActionRequest {
+                    action: Action::Apply,
+                    target: None,
+                    chattr_attrs: None,
+                },
+            })
+            .expect("send task");
+        drop(task_tx);
+
+        worker_loop(client.clone(), task_rx, event_tx, true).await;
+
+        match event_rx.recv().await {
+            Some(BackendEvent::Error { context, message }) => {
+                assert_eq!(context, "action");
+                assert!(message.contains("read-only"));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(client.runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn worker_loop_answers_targeted_refreshes_with_their_scope() {
+        let client = Arc::new(CountingClient::default());
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let targets = vec![PathBuf::from(".zshrc")];
+        task_tx
+            .send(BackendTask::RefreshStatusPaths {
+                targets: targets.clone(),
+            })
+            .expect("send task");
+        task_tx
+            .send(BackendTask::RefreshUnmanagedDir {
+                dir: PathBuf::from(".config"),
+            })
+            .expect("send task");
+        drop(task_tx);
+
+        worker_loop(client, task_rx, event_tx, false).await;
+
+        match event_rx.recv().await {
+            Some(BackendEvent::StatusPathsRefreshed {
+                targets: got,
+                status,
+            }) => {
+                assert_eq!(got, targets);
+                assert_eq!(status.len(), 1);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match event_rx.recv().await {
+            Some(BackendEvent::UnmanagedDirRefreshed { dir, unmanaged }) => {
+                assert_eq!(dir, PathBuf::from(".config"));
+                assert_eq!(unmanaged, vec![PathBuf::from(".config/new")]);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
 
     #[test]
     fn flatten_error_formats_all_cases() {
@@ -154,4 +658,169 @@ mod tests {
         let err = flatten_error::<()>(Ok(Err(anyhow::anyhow!("boom"))));
         assert!(err.contains("boom"));
     }
+
+    #[tokio::test]
+    async fn worker_loop_writes_a_single_hunk_into_the_destination() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_hunk_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        std::fs::write(&path, "a\nb\nc\n").expect("write");
+        let hunk = crate::diff::hunks("@@ -2 +2 @@\n-b\n+B\n").remove(0);
+
+        let client = Arc::new(CountingClient::default());
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        // The second write no longer matches the file.
+        for _ in 0..2 {
+            task_tx
+                .send(BackendTask::WriteHunk {
+                    target: path.clone(),
+                    hunk: hunk.clone(),
+                    direction: HunkDirection::ToDestination,
+                })
+                .expect("send task");
+        }
+        drop(task_tx);
+
+        worker_loop(client, task_rx, event_tx, false).await;
+
+        assert!(matches!(
+            event_rx.recv().await,
+            Some(BackendEvent::HunkWritten { .. })
+        ));
+        match event_rx.recv().await {
+            Some(BackendEvent::HunkFailed {
+                written, message, ..
+            }) => {
+                assert_eq!(written, path);
+                assert!(message.starts_with("apply-hunk failed"), "{message}");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), "a\nB\nc\n");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn worker_loop_searches_contents_and_skips_binary_files() {
+        let root = std::env::temp_dir().join(format!(
+            "chezmoi_tui_search_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("dir")).expect("create dir");
+        std::fs::write(root.join("text"), "one\nTwo needle\nthree needle\n").expect("write");
+        std::fs::write(root.join("blob"), b"needle\0needle").expect("write");
+        let targets: Vec<(PathBuf, PathBuf)> = ["dir", "text", "blob"]
+            .into_iter()
+            .map(|name| (PathBuf::from(name), root.join(name)))
+            .collect();
+
+        let client = Arc::new(CountingClient::default());
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let cancelled = Arc::new(AtomicBool::new(true));
+        for (scope, cancel) in [
+            (SearchScope::Source, Arc::new(AtomicBool::new(false))),
+            (SearchScope::Destination, cancelled),
+            (SearchScope::Rendered, Arc::new(AtomicBool::new(false))),
+        ] {
+            task_tx
+                .send(BackendTask::SearchContent {
+                    query: "NEEDLE".to_string(),
+                    scope,
+                    targets: targets.clone(),
+                    cancel,
+                })
+                .expect("send task");
+        }
+        drop(task_tx);
+
+        worker_loop(client, task_rx, event_tx, false).await;
+
+        // Searches run beside the queue, so their results may arrive in any
+        // order; the cancelled one sends nothing.
+        let mut scopes = Vec::new();
+        while let Some(event) = event_rx.recv().await {
+            let BackendEvent::ContentSearched { scope, results, .. } = event else {
+                panic!("expected search results");
+            };
+            scopes.push(scope.label());
+            let found: Vec<(&Path, usize, &str)> = results
+                .matches
+                .iter()
+                .map(|hit| (hit.path.as_path(), hit.line, hit.snippet.as_str()))
+                .collect();
+            assert_eq!(
+                found,
+                vec![
+                    (Path::new("text"), 2, "Two needle"),
+                    (Path::new("text"), 3, "three needle")
+                ]
+            );
+        }
+        scopes.sort_unstable();
+        assert_eq!(scopes, vec!["rendered", "source"]);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn worker_loop_attaches_metadata_and_source_to_previews() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_preview_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ));
+        std::fs::write(&path, "hello\n").expect("write");
+
+        let client = Arc::new(CountingClient::default());
+        let (task_tx, task_rx) = mpsc::unbounded_channel();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        for managed in [true, false] {
+            task_tx
+                .send(BackendTask::LoadPreview {
+                    target: PathBuf::from("preview"),
+                    absolute: path.clone(),
+                    managed,
+                })
+                .expect("send task");
+        }
+        drop(task_tx);
+
+        worker_loop(client, task_rx, event_tx, false).await;
+
+        let Some(BackendEvent::PreviewLoaded {
+            content,
+            metadata: Some(metadata),
+            ..
+        }) = event_rx.recv().await
+        else {
+            panic!("expected preview with metadata");
+        };
+        assert_eq!(content, "hello\n");
+        assert_eq!(metadata.file.size, 6);
+        assert_eq!(
+            metadata.source.map(|source| source.path),
+            Some(path.clone())
+        );
+
+        let Some(BackendEvent::PreviewLoaded {
+            metadata: Some(metadata),
+            ..
+        }) = event_rx.recv().await
+        else {
+            panic!("expected preview with metadata");
+        };
+        assert_eq!(metadata.source, None);
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -0,0 +1,489 @@
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes shown per hex page (256 lines of 16 bytes).
+pub(crate) const HEX_PAGE_BYTES: u64 = 4096;
+const HEX_ROW_BYTES: usize = 16;
+/// Archive listings stop after this many members.
+const MAX_LISTED_MEMBERS: usize = 40;
+/// The zip end-of-central-directory record sits within this many bytes of the end.
+const ZIP_EOCD_SEARCH_BYTES: u64 = 64 * 1024 + 22;
+const ZIP_MAX_CENTRAL_DIRECTORY_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Position of a hex page within its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HexPage {
+    pub offset: u64,
+    pub file_size: u64,
+}
+
+impl HexPage {
+    pub(crate) fn page_count(self) -> u64 {
+        self.file_size.div_ceil(HEX_PAGE_BYTES).max(1)
+    }
+
+    /// Offset of the neighbouring page, if there is one.
+    pub(crate) fn step(self, forward: bool) -> Option<u64> {
+        if forward {
+            let next = self.offset + HEX_PAGE_BYTES;
+            (next < self.file_size).then_some(next)
+        } else {
+            (self.offset > 0).then(|| self.offset.saturating_sub(HEX_PAGE_BYTES))
+        }
+    }
+}
+
+/// Reads the page starting at `offset` and renders it with a header line.
+pub(crate) fn load_hex_page(path: &Path, offset: u64) -> Result<(String, HexPage)> {
+    let mut file =
+        File::open(path).with_context(|| format!("failed to read: {}", path.display()))?;
+    let file_size = file
+        .metadata()
+        .with_context(|| format!("failed to read: {}", path.display()))?
+        .len();
+    let offset = offset.min(file_size.saturating_sub(1)) / HEX_PAGE_BYTES * HEX_PAGE_BYTES;
+    file.seek(SeekFrom::Start(offset))
+        .with_context(|| format!("failed to seek: {}", path.display()))?;
+    let mut bytes = Vec::new();
+    file.take(HEX_PAGE_BYTES)
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("failed to read: {}", path.display()))?;
+
+    let page = HexPage { offset, file_size };
+    Ok((hex_page_text(&bytes, page), page))
+}
+
+/// Header line plus `hexdump -C`-style rows for one page.
+pub(crate) fn hex_page_text(bytes: &[u8], page: HexPage) -> String {
+    let end = page.offset + bytes.len() as u64;
+    let mut text = format!(
+        "hex page {}/{}: bytes {}-{} of {}  ([ / ] previous / next page)\n",
+        page.offset / HEX_PAGE_BYTES + 1,
+        page.page_count(),
+        page.offset,
+        end.saturating_sub(1).max(page.offset),
+        page.file_size
+    );
+    for (row, chunk) in bytes.chunks(HEX_ROW_BYTES).enumerate() {
+        let _ = write!(text, "{:08x} ", page.offset + (row * HEX_ROW_BYTES) as u64);
+        for column in 0..HEX_ROW_BYTES {
+            if column % 8 == 0 {
+                text.push(' ');
+            }
+            match chunk.get(column) {
+                Some(byte) => {
+                    let _ = write!(text, "{byte:02x} ");
+                }
+                None => text.push_str("   "),
+            }
+        }
+        text.push_str(" |");
+        text.extend(chunk.iter().map(|byte| {
+            if byte.is_ascii_graphic() || *byte == b' ' {
+                char::from(*byte)
+            } else {
+                '.'
+            }
+        }));
+        text.push_str("|\n");
+    }
+    text
+}
+
+/// One-paragraph description of well-known binary formats; `head` is the
+/// start of the file. Unknown formats yield no lines.
+pub(crate) fn summarize_binary(path: &Path, head: &[u8], file_size: u64) -> Vec<String> {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return png_summary(head);
+    }
+    if head.starts_with(&[0xff, 0xd8, 0xff]) {
+        return jpeg_summary(head);
+    }
+    if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        return match (read_u16_le(head, 6), read_u16_le(head, 8)) {
+            (Some(width), Some(height)) => vec![format!("GIF image, {width} x {height}")],
+            _ => vec!["GIF image".to_string()],
+        };
+    }
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        return zip_summary(path, file_size);
+    }
+    if head.get(257..262) == Some(b"ustar") {
+        return tar_summary(path, file_size);
+    }
+    if head.starts_with(&[0x1f, 0x8b]) {
+        return vec!["gzip compressed data".to_string()];
+    }
+    if head.starts_with(b"age-encryption.org/v1\n") {
+        return age_summary(head);
+    }
+    if head.starts_with(b"SQLite format 3\0") {
+        return sqlite_summary(head);
+    }
+    if let Some(summary) = font_summary(head) {
+        return vec![summary];
+    }
+    if head.starts_with(b"kych") {
+        return vec!["macOS keychain".to_string()];
+    }
+    openpgp_summary(path, head).into_iter().collect()
+}
+
+fn read_u16_be(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u16_le(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn png_summary(head: &[u8]) -> Vec<String> {
+    let (Some(width), Some(height)) = (read_u32_be(head, 16), read_u32_be(head, 20)) else {
+        return vec!["PNG image".to_string()];
+    };
+    let color = match head.get(25) {
+        Some(0) => "grayscale",
+        Some(2) => "RGB",
+        Some(3) => "palette",
+        Some(4) => "grayscale + alpha",
+        Some(6) => "RGBA",
+        _ => "unknown color type",
+    };
+    let depth = head.get(24).copied().unwrap_or(0);
+    vec![format!(
+        "PNG image, {width} x {height}, {depth}-bit {color}"
+    )]
+}
+
+fn jpeg_summary(head: &[u8]) -> Vec<String> {
+    let mut at = 2;
+    while at + 9 < head.len() {
+        if head[at] != 0xff {
+            break;
+        }
+        let marker = head[at + 1];
+        let Some(length) = read_u16_be(head, at + 2) else {
+            break;
+        };
+        // SOF0..SOF15, excluding DHT (C4), JPG (C8) and DAC (CC).
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            if let (Some(height), Some(width)) =
+                (read_u16_be(head, at + 5), read_u16_be(head, at + 7))
+            {
+                return vec![format!("JPEG image, {width} x {height}")];
+            }
+            break;
+        }
+        at += 2 + usize::from(length);
+    }
+    vec!["JPEG image".to_string()]
+}
+
+fn zip_summary(path: &Path, file_size: u64) -> Vec<String> {
+    let listing = (|| -> Option<Vec<String>> {
+        let mut file = File::open(path).ok()?;
+        let tail_len = file_size.min(ZIP_EOCD_SEARCH_BYTES);
+        file.seek(SeekFrom::Start(file_size - tail_len)).ok()?;
+        let mut tail = Vec::new();
+        file.by_ref().take(tail_len).read_to_end(&mut tail).ok()?;
+        let eocd = tail
+            .windows(4)
+            .rposition(|window| window == b"PK\x05\x06")?;
+        let total = read_u16_le(&tail, eocd + 10)?;
+        let directory_size = u64::from(read_u32_le(&tail, eocd + 12)?);
+        let directory_offset = u64::from(read_u32_le(&tail, eocd + 16)?);
+        if directory_size > ZIP_MAX_CENTRAL_DIRECTORY_BYTES {
+            return None;
+        }
+
+        file.seek(SeekFrom::Start(directory_offset)).ok()?;
+        let mut directory = Vec::new();
+        file.take(directory_size).read_to_end(&mut directory).ok()?;
+        let mut lines = vec![format!("zip archive, {total} member(s)")];
+        let mut at = 0;
+        while directory.get(at..at + 4) == Some(b"PK\x01\x02") {
+            let size = read_u32_le(&directory, at + 24)?;
+            let name_len = usize::from(read_u16_le(&directory, at + 28)?);
+            let extra_len = usize::from(read_u16_le(&directory, at + 30)?);
+            let comment_len = usize::from(read_u16_le(&directory, at + 32)?);
+            let name = directory.get(at + 46..at + 46 + name_len)?;
+            if lines.len() <= MAX_LISTED_MEMBERS {
+                lines.push(format!("  {size:>10}  {}", String::from_utf8_lossy(name)));
+            }
+            at += 46 + name_len + extra_len + comment_len;
+        }
+        if usize::from(total) > MAX_LISTED_MEMBERS {
+            lines.push(format!(
+                "  ... {} more",
+                usize::from(total) - MAX_LISTED_MEMBERS
+            ));
+        }
+        Some(lines)
+    })();
+    listing.unwrap_or_else(|| vec!["zip archive".to_string()])
+}
+
+fn tar_summary(path: &Path, file_size: u64) -> Vec<String> {
+    let Ok(mut file) = File::open(path) else {
+        return vec!["tar archive".to_string()];
+    };
+    let mut members = Vec::new();
+    // Set once a header past `MAX_LISTED_MEMBERS` is seen; the rest of the
+    // archive is not walked.
+    let mut truncated = false;
+    let mut offset = 0u64;
+    let mut header = [0u8; 512];
+    while offset + 512 <= file_size {
+        if file.seek(SeekFrom::Start(offset)).is_err() || file.read_exact(&mut header).is_err() {
+            break;
+        }
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        if members.len() == MAX_LISTED_MEMBERS {
+            truncated = true;
+            break;
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let raw = &header[range];
+            let end = raw.iter().position(|byte| *byte == 0).unwrap_or(raw.len());
+            String::from_utf8_lossy(&raw[..end]).trim().to_string()
+        };
+        let size = u64::from_str_radix(&field(124..136), 8).unwrap_or(0);
+        let prefix = field(345..500);
+        let name = field(0..100);
+        let name = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        members.push(format!("  {size:>10}  {name}"));
+        offset += 512 + size.div_ceil(512) * 512;
+    }
+
+    let mut lines = if truncated {
+        vec![format!(
+            "tar archive, at least {} member(s)",
+            members.len() + 1
+        )]
+    } else {
+        vec![format!("tar archive, {} member(s)", members.len())]
+    };
+    lines.extend(members);
+    if truncated {
+        lines.push("  ... more not listed".to_string());
+    }
+    lines
+}
+
+fn age_summary(head: &[u8]) -> Vec<String> {
+    let header_end = head
+        .windows(4)
+        .position(|window| window == b"\n---")
+        .unwrap_or(head.len());
+    let header = String::from_utf8_lossy(&head[..header_end]);
+    let recipients: Vec<String> = header
+        .lines()
+        .filter_map(|line| line.strip_prefix("-> "))
+        .map(|stanza| stanza.split_whitespace().next().unwrap_or("?").to_string())
+        .collect();
+    vec![format!(
+        "age encrypted file, {} recipient stanza(s): {}",
+        recipients.len(),
+        recipients.join(", ")
+    )]
+}
+
+fn sqlite_summary(head: &[u8]) -> Vec<String> {
+    let page_size = match read_u16_be(head, 16) {
+        Some(1) => 65_536,
+        Some(size) => u32::from(size),
+        None => 0,
+    };
+    let pages = read_u32_be(head, 28).unwrap_or(0);
+    vec![format!(
+        "SQLite 3 database, page size {page_size}, {pages} page(s)"
+    )]
+}
+
+fn font_summary(head: &[u8]) -> Option<String> {
+    let kind = match head.get(..4)? {
+        [0, 1, 0, 0] | b"true" => "TrueType font",
+        b"OTTO" => "OpenType (CFF) font",
+        b"ttcf" => return Some("TrueType font collection".to_string()),
+        b"wOFF" => return Some("WOFF web font".to_string()),
+        b"wOF2" => return Some("WOFF2 web font".to_string()),
+        _ => return None,
+    };
+    let tables = read_u16_be(head, 4)?;
+    Some(format!("{kind}, {tables} table(s)"))
+}
+
+/// Binary OpenPGP messages start with an encrypted session key packet; only
+/// `.gpg` / `.pgp` files are probed since a lone high bit is a weak signal.
+fn openpgp_summary(path: &Path, head: &[u8]) -> Option<String> {
+    let extension = path.extension()?.to_str()?;
+    if !matches!(extension, "gpg" | "pgp") {
+        return None;
+    }
+    let first = *head.first()?;
+    if first & 0x80 == 0 {
+        return None;
+    }
+    let (tag, body) = if first & 0x40 != 0 {
+        let length_octets = match head.get(1)? {
+            0..=191 => 1,
+            192..=223 => 2,
+            255 => 5,
+            _ => return None,
+        };
+        (first & 0x3f, 1 + length_octets)
+    } else {
+        let length_octets = match first & 0x03 {
+            0 => 1,
+            1 => 2,
+            2 => 4,
+            _ => 0,
+        };
+        ((first >> 2) & 0x0f, 1 + length_octets)
+    };
+    match tag {
+        1 => {
+            let key_id: String = head
+                .get(body + 1..body + 9)?
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect();
+            Some(format!("OpenPGP encrypted data for key ID {key_id}"))
+        }
+        3 => Some("OpenPGP passphrase-encrypted data".to_string()),
+        6 | 5 => Some("OpenPGP key".to_string()),
+        _ => Some(format!("OpenPGP data (packet tag {tag})")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("chezmoi_tui_binary_{}_{name}", std::process::id()));
+        fs::write(&path, bytes).expect("write");
+        path
+    }
+
+    #[test]
+    fn hex_pages_show_offsets_ascii_and_step_through_the_file() {
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(5000).collect();
+        let path = temp_file("pages.bin", &bytes);
+
+        let (text, page) = load_hex_page(&path, 0).expect("page");
+        assert_eq!(page.page_count(), 2);
+        let mut lines = text.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("hex page 1/2: bytes 0-4095 of 5000")
+        );
+        assert_eq!(
+            lines.nth(4).unwrap(),
+            "00000040  40 41 42 43 44 45 46 47  48 49 4a 4b 4c 4d 4e 4f  |@ABCDEFGHIJKLMNO|"
+        );
+
+        let next = page.step(true).expect("next page");
+        let (text, page) = load_hex_page(&path, next).expect("page");
+        assert_eq!(page.offset, 4096);
+        assert!(text.starts_with("hex page 2/2: bytes 4096-4999"));
+        assert_eq!(page.step(true), None);
+        assert_eq!(page.step(false), Some(0));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn summaries_describe_images_databases_and_encryption_headers() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend([0, 0, 1, 0, 0, 0, 0, 64, 8, 6]);
+        assert_eq!(
+            summarize_binary(Path::new("a.png"), &png, 0),
+            vec!["PNG image, 256 x 64, 8-bit RGBA"]
+        );
+
+        let mut sqlite = b"SQLite format 3\0".to_vec();
+        sqlite.extend([0x10, 0x00]);
+        sqlite.resize(28, 0);
+        sqlite.extend([0, 0, 0, 3]);
+        assert_eq!(
+            summarize_binary(Path::new("db"), &sqlite, 0),
+            vec!["SQLite 3 database, page size 4096, 3 page(s)"]
+        );
+
+        let age = b"age-encryption.org/v1\n-> X25519 abc\nxyz\n-> scrypt salt 18\nq\n--- mac\n\0";
+        assert_eq!(
+            summarize_binary(Path::new("secret.age"), age, 0),
+            vec!["age encrypted file, 2 recipient stanza(s): X25519, scrypt"]
+        );
+
+        let gpg = [
+            0x85, 0x01, 0x0c, 0x03, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+        ];
+        assert_eq!(
+            summarize_binary(Path::new("secret.gpg"), &gpg, 0),
+            vec!["OpenPGP encrypted data for key ID 123456789ABCDEF0"]
+        );
+        assert!(summarize_binary(Path::new("blob"), &gpg, 0).is_empty());
+    }
+
+    #[test]
+    fn tar_summary_lists_members() {
+        let mut header = [0u8; 512];
+        header[..9].copy_from_slice(b"notes.txt");
+        header[124..135].copy_from_slice(b"00000000005");
+        header[257..262].copy_from_slice(b"ustar");
+        let mut archive = header.to_vec();
+        archive.extend(b"hello");
+        archive.resize(1024 + 1024, 0);
+        let path = temp_file("archive.tar", &archive);
+
+        let lines = summarize_binary(&path, &archive, archive.len() as u64);
+        assert_eq!(lines[0], "tar archive, 1 member(s)");
+        assert_eq!(lines[1], "           5  notes.txt");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn tar_summary_stops_after_the_listed_members() {
+        let mut header = [0u8; 512];
+        header[..5].copy_from_slice(b"empty");
+        header[124..135].copy_from_slice(b"00000000000");
+        header[257..262].copy_from_slice(b"ustar");
+        let mut archive = header.repeat(MAX_LISTED_MEMBERS + 5);
+        archive.resize(archive.len() + 1024, 0);
+        let path = temp_file("many.tar", &archive);
+
+        let lines = tar_summary(&path, archive.len() as u64);
+        assert_eq!(
+            lines[0],
+            format!("tar archive, at least {} member(s)", MAX_LISTED_MEMBERS + 1)
+        );
+        assert_eq!(lines.len(), MAX_LISTED_MEMBERS + 2);
+        assert_eq!(
+            lines.last().map(String::as_str),
+            Some("  ... more not listed")
+        );
+        let _ = fs::remove_file(path);
+    }
+}
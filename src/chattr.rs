@@ -0,0 +1,447 @@
+use crate::domain::ActionRequest;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ChattrAttribute {
+    Empty,
+    Encrypted,
+    Exact,
+    Executable,
+    Private,
+    Readonly,
+    Template,
+    Once,
+    Onchange,
+    Before,
+    After,
+}
+
+impl ChattrAttribute {
+    pub(crate) const ALL: [ChattrAttribute; 11] = [
+        ChattrAttribute::Empty,
+        ChattrAttribute::Encrypted,
+        ChattrAttribute::Exact,
+        ChattrAttribute::Executable,
+        ChattrAttribute::Private,
+        ChattrAttribute::Readonly,
+        ChattrAttribute::Template,
+        ChattrAttribute::Once,
+        ChattrAttribute::Onchange,
+        ChattrAttribute::Before,
+        ChattrAttribute::After,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ChattrAttribute::Empty => "empty",
+            ChattrAttribute::Encrypted => "encrypted",
+            ChattrAttribute::Exact => "exact",
+            ChattrAttribute::Executable => "executable",
+            ChattrAttribute::Private => "private",
+            ChattrAttribute::Readonly => "readonly",
+            ChattrAttribute::Template => "template",
+            ChattrAttribute::Once => "once",
+            ChattrAttribute::Onchange => "onchange",
+            ChattrAttribute::Before => "before",
+            ChattrAttribute::After => "after",
+        }
+    }
+
+    /// Position in `ALL`, which follows declaration order.
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Source name prefix; `template` is encoded as a `.tmpl` suffix instead.
+    fn source_prefix(self) -> Option<&'static str> {
+        match self {
+            ChattrAttribute::Empty => Some("empty_"),
+            ChattrAttribute::Encrypted => Some("encrypted_"),
+            ChattrAttribute::Exact => Some("exact_"),
+            ChattrAttribute::Executable => Some("executable_"),
+            ChattrAttribute::Private => Some("private_"),
+            ChattrAttribute::Readonly => Some("readonly_"),
+            ChattrAttribute::Template => None,
+            ChattrAttribute::Once => Some("once_"),
+            ChattrAttribute::Onchange => Some("onchange_"),
+            ChattrAttribute::Before => Some("before_"),
+            ChattrAttribute::After => Some("after_"),
+        }
+    }
+
+    fn file_only(self) -> bool {
+        matches!(
+            self,
+            ChattrAttribute::Empty
+                | ChattrAttribute::Encrypted
+                | ChattrAttribute::Executable
+                | ChattrAttribute::Template
+        )
+    }
+
+    fn script_only(self) -> bool {
+        matches!(
+            self,
+            ChattrAttribute::Once
+                | ChattrAttribute::Onchange
+                | ChattrAttribute::Before
+                | ChattrAttribute::After
+        )
+    }
+}
+
+/// Attributes of one target, decoded from its source state name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceInfo {
+    pub attributes: BTreeSet<ChattrAttribute>,
+    pub is_dir: bool,
+    pub is_script: bool,
+}
+
+/// Type prefixes that carry no chattr attribute but may precede attribute prefixes.
+const TYPE_PREFIXES: [&str; 6] = [
+    "run_",
+    "create_",
+    "modify_",
+    "remove_",
+    "symlink_",
+    "external_",
+];
+
+/// Decodes chezmoi source-state prefixes and suffixes, e.g.
+/// `private_executable_dot_script.sh.tmpl` or `run_once_before_install.sh`.
+pub(crate) fn source_info(source: &Path, is_dir: bool) -> SourceInfo {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut rest = name.as_str();
+    let mut attributes = BTreeSet::new();
+    let is_script = rest.starts_with("run_");
+
+    loop {
+        if let Some(stripped) = TYPE_PREFIXES
+            .iter()
+            .find_map(|prefix| rest.strip_prefix(prefix))
+        {
+            rest = stripped;
+            continue;
+        }
+        let Some((attribute, stripped)) = ChattrAttribute::ALL.iter().find_map(|attribute| {
+            let prefix = attribute.source_prefix()?;
+            rest.strip_prefix(prefix)
+                .map(|stripped| (*attribute, stripped))
+        }) else {
+            break;
+        };
+        attributes.insert(attribute);
+        rest = stripped;
+    }
+
+    let without_encryption = rest
+        .strip_suffix(".age")
+        .or_else(|| rest.strip_suffix(".asc"))
+        .unwrap_or(rest);
+    if !is_dir && without_encryption.ends_with(".tmpl") {
+        attributes.insert(ChattrAttribute::Template);
+    }
+
+    SourceInfo {
+        attributes,
+        is_dir,
+        is_script,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TriState {
+    On,
+    Off,
+    Mixed,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttrChange {
+    Keep,
+    Set,
+    Clear,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChattrPicker {
+    /// Request that opened the picker; its attrs are filled on submit.
+    pub request: ActionRequest,
+    pub targets: Vec<PathBuf>,
+    /// `None` while source names are loading or when they could not be read.
+    pub sources: Option<Vec<SourceInfo>>,
+    pub changes: [AttrChange; 11],
+    pub selected: usize,
+    pub message: Option<String>,
+}
+
+impl ChattrPicker {
+    pub(crate) fn new(request: ActionRequest, targets: Vec<PathBuf>) -> Self {
+        Self {
+            request,
+            targets,
+            sources: None,
+            changes: [AttrChange::Keep; 11],
+            selected: 0,
+            message: None,
+        }
+    }
+
+    pub(crate) fn current(&self, attribute: ChattrAttribute) -> TriState {
+        let Some(sources) = &self.sources else {
+            return TriState::Unknown;
+        };
+        let on = sources
+            .iter()
+            .filter(|source| source.attributes.contains(&attribute))
+            .count();
+        match on {
+            0 if sources.is_empty() => TriState::Unknown,
+            0 => TriState::Off,
+            n if n == sources.len() => TriState::On,
+            _ => TriState::Mixed,
+        }
+    }
+
+    /// Checkbox state after pending changes: a kept attribute shows its current state.
+    pub(crate) fn effective(&self, index: usize) -> TriState {
+        match self.changes[index] {
+            AttrChange::Keep => self.current(ChattrAttribute::ALL[index]),
+            AttrChange::Set => TriState::On,
+            AttrChange::Clear => TriState::Off,
+        }
+    }
+
+    /// Cycles the selected attribute through keep → set → clear, skipping a
+    /// state that would not change anything for every target.
+    pub(crate) fn toggle_selected(&mut self) {
+        let index = self.selected;
+        let current = self.current(ChattrAttribute::ALL[index]);
+        let mut next = self.changes[index];
+        loop {
+            next = match next {
+                AttrChange::Keep => AttrChange::Set,
+                AttrChange::Set => AttrChange::Clear,
+                AttrChange::Clear => AttrChange::Keep,
+            };
+            let noop = matches!(
+                (next, current),
+                (AttrChange::Set, TriState::On) | (AttrChange::Clear, TriState::Off)
+            );
+            if !noop {
+                break;
+            }
+        }
+        self.changes[index] = next;
+        self.message = None;
+    }
+
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let len = ChattrAttribute::ALL.len();
+        self.selected = if forward {
+            (self.selected + 1) % len
+        } else {
+            self.selected.checked_sub(1).unwrap_or(len - 1)
+        };
+    }
+
+    /// Builds the `chezmoi chattr` modifier string, e.g. `+private,-executable`.
+    pub(crate) fn attribute_string(&self) -> String {
+        ChattrAttribute::ALL
+            .iter()
+            .zip(self.changes)
+            .filter_map(|(attribute, change)| match change {
+                AttrChange::Keep => None,
+                AttrChange::Set => Some(format!("+{}", attribute.label())),
+                AttrChange::Clear => Some(format!("-{}", attribute.label())),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Checks the resulting attributes of every target for combinations
+    /// chezmoi rejects or ignores.
+    pub(crate) fn validate(&self) -> Result<String, String> {
+        let modifiers = self.attribute_string();
+        if modifiers.is_empty() {
+            return Err("no attribute changes selected".to_string());
+        }
+
+        let set = |attribute: ChattrAttribute| self.changes[attribute.index()];
+        for (first, second) in [
+            (ChattrAttribute::Once, ChattrAttribute::Onchange),
+            (ChattrAttribute::Before, ChattrAttribute::After),
+        ] {
+            let resulting: Vec<bool> = match &self.sources {
+                Some(sources) => sources
+                    .iter()
+                    .map(|source| {
+                        resulting(source, first, set(first))
+                            && resulting(source, second, set(second))
+                    })
+                    .collect(),
+                None => vec![set(first) == AttrChange::Set && set(second) == AttrChange::Set],
+            };
+            if resulting.into_iter().any(|both| both) {
+                return Err(format!(
+                    "{} and {} cannot be combined",
+                    first.label(),
+                    second.label()
+                ));
+            }
+        }
+
+        if let Some(sources) = &self.sources {
+            for (attribute, change) in ChattrAttribute::ALL.iter().zip(self.changes) {
+                if change != AttrChange::Set {
+                    continue;
+                }
+                if *attribute == ChattrAttribute::Exact
+                    && sources.iter().any(|source| !source.is_dir)
+                {
+                    return Err("exact applies only to directories".to_string());
+                }
+                if attribute.file_only() && sources.iter().any(|source| source.is_dir) {
+                    return Err(format!("{} applies only to files", attribute.label()));
+                }
+                if attribute.script_only() && sources.iter().any(|source| !source.is_script) {
+                    return Err(format!("{} applies only to scripts", attribute.label()));
+                }
+            }
+        }
+
+        Ok(modifiers)
+    }
+}
+
+fn resulting(source: &SourceInfo, attribute: ChattrAttribute, change: AttrChange) -> bool {
+    match change {
+        AttrChange::Keep => source.attributes.contains(&attribute),
+        AttrChange::Set => true,
+        AttrChange::Clear => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Action;
+
+    fn picker_with(sources: Vec<SourceInfo>) -> ChattrPicker {
+        let mut picker = ChattrPicker::new(
+            ActionRequest {
+                action: Action::Chattr,
+                target: Some(PathBuf::from("/home/me/.a")),
+                chattr_attrs: None,
+            },
+            vec![PathBuf::from("/home/me/.a"), PathBuf::from("/home/me/.b")],
+        );
+        picker.sources = Some(sources);
+        picker
+    }
+
+    fn index_of(attribute: ChattrAttribute) -> usize {
+        let index = attribute.index();
+        assert_eq!(ChattrAttribute::ALL[index], attribute);
+        index
+    }
+
+    #[test]
+    fn source_info_decodes_prefixes_and_template_suffix() {
+        let info = source_info(
+            Path::new("/src/private_readonly_executable_dot_script.sh.tmpl"),
+            false,
+        );
+        assert_eq!(
+            info.attributes,
+            BTreeSet::from([
+                ChattrAttribute::Executable,
+                ChattrAttribute::Private,
+                ChattrAttribute::Readonly,
+                ChattrAttribute::Template,
+            ])
+        );
+
+        let script = source_info(Path::new("/src/run_once_before_install.sh"), false);
+        assert!(script.is_script);
+        assert_eq!(
+            script.attributes,
+            BTreeSet::from([ChattrAttribute::Once, ChattrAttribute::Before])
+        );
+
+        let dir = source_info(Path::new("/src/exact_private_dot_config"), true);
+        assert_eq!(
+            dir.attributes,
+            BTreeSet::from([ChattrAttribute::Exact, ChattrAttribute::Private])
+        );
+
+        let encrypted = source_info(Path::new("/src/encrypted_dot_netrc.tmpl.age"), false);
+        assert!(encrypted.attributes.contains(&ChattrAttribute::Template));
+        assert!(encrypted.attributes.contains(&ChattrAttribute::Encrypted));
+    }
+
+    #[test]
+    fn mixed_selection_shows_tri_state_and_builds_modifiers() {
+        let mut picker = picker_with(vec![
+            source_info(Path::new("/src/private_dot_a"), false),
+            source_info(Path::new("/src/dot_b"), false),
+        ]);
+        let private = index_of(ChattrAttribute::Private);
+        let executable = index_of(ChattrAttribute::Executable);
+        assert_eq!(picker.effective(private), TriState::Mixed);
+        assert_eq!(picker.effective(executable), TriState::Off);
+
+        picker.selected = private;
+        picker.toggle_selected();
+        assert_eq!(picker.effective(private), TriState::On);
+        picker.selected = executable;
+        picker.toggle_selected();
+        picker.selected = index_of(ChattrAttribute::Readonly);
+        picker.toggle_selected();
+        picker.toggle_selected();
+        // Clearing readonly is a no-op for both targets, so the cycle skips it.
+        assert_eq!(
+            picker.changes[index_of(ChattrAttribute::Readonly)],
+            AttrChange::Keep
+        );
+
+        assert_eq!(picker.validate(), Ok("+executable,+private".to_string()));
+    }
+
+    #[test]
+    fn validate_rejects_incompatible_combinations() {
+        let mut picker = picker_with(vec![source_info(Path::new("/src/dot_a"), false)]);
+        picker.changes[index_of(ChattrAttribute::Exact)] = AttrChange::Set;
+        assert!(
+            picker
+                .validate()
+                .is_err_and(|err| err.contains("directories"))
+        );
+
+        let mut picker = picker_with(vec![source_info(
+            Path::new("/src/run_once_install.sh"),
+            false,
+        )]);
+        picker.changes[index_of(ChattrAttribute::Onchange)] = AttrChange::Set;
+        assert!(
+            picker
+                .validate()
+                .is_err_and(|err| err.contains("once and onchange"))
+        );
+        picker.changes[index_of(ChattrAttribute::Once)] = AttrChange::Clear;
+        assert_eq!(picker.validate(), Ok("-once,+onchange".to_string()));
+
+        let picker = picker_with(Vec::new());
+        assert!(
+            picker
+                .validate()
+                .is_err_and(|err| err.contains("no attribute"))
+        );
+    }
+}
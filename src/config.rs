@@ -1,16 +1,122 @@
-#[derive(Debug, Clone)]
+use crate::layout::PaneLayout;
+use crate::theme::ThemeConfig;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProtectedPathMode {
+    /// Require the typed confirmation phrase before running.
+    #[default]
+    Confirm,
+    /// Never run actions on protected targets.
+    Refuse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BatchErrorPolicy {
+    /// Run every queued item and report failures at the end.
+    #[default]
+    Continue,
+    /// Skip the rest of the batch after the first failed item.
+    Stop,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AppConfig {
     pub require_two_step_confirmation: bool,
+    /// Browse-only mode: mutating actions are hidden and refused.
+    pub read_only: bool,
+    pub audit_log_path: Option<PathBuf>,
+    /// Home-relative globs whose targets need extra care (see `ProtectedPathMode`).
+    pub protected_paths: Vec<String>,
+    pub protected_path_mode: ProtectedPathMode,
+    pub batch_error_policy: BatchErrorPolicy,
+    /// File used by the action queue's save/load keys.
+    pub plan_path: Option<PathBuf>,
+    /// Where each list's sort and grouping choice is remembered.
+    pub view_orders_path: Option<PathBuf>,
+    /// Starting pane layout, used until one is saved to `layout_path`.
+    pub layout: PaneLayout,
+    /// Where resized / rearranged pane layouts are remembered.
+    pub layout_path: Option<PathBuf>,
+    /// Capture the mouse for clicks and wheel scrolling; turn off to keep the
+    /// terminal's own text selection.
+    pub mouse: bool,
+    /// Poll managed targets, the working directory and the source dir, and
+    /// refresh when they change.
+    pub watch: bool,
+    /// Gitignore-style globs, relative to the working directory, hidden
+    /// from the unmanaged view and its filter index.
+    pub unmanaged_exclude: Vec<String>,
+    /// Also hide what `.gitignore` files inside the working directory ignore.
+    pub unmanaged_gitignore: bool,
+    /// Where directory listings of the unmanaged index are cached between runs.
+    pub unmanaged_index_path: Option<PathBuf>,
+    /// Colour preset and per-role overrides.
+    pub theme: ThemeConfig,
+    /// Umask chezmoi applies to what it writes, when its own config sets
+    /// one; the process umask is used otherwise.
+    pub umask: Option<u32>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             require_two_step_confirmation: true,
+            read_only: false,
+            audit_log_path: None,
+            protected_paths: vec![
+                ".ssh/**".to_string(),
+                ".gnupg/**".to_string(),
+                ".config/chezmoi/**".to_string(),
+            ],
+            protected_path_mode: ProtectedPathMode::Confirm,
+            batch_error_policy: BatchErrorPolicy::Continue,
+            plan_path: None,
+            view_orders_path: None,
+            layout: PaneLayout::default(),
+            layout_path: None,
+            mouse: true,
+            watch: true,
+            unmanaged_exclude: Vec::new(),
+            unmanaged_gitignore: false,
+            unmanaged_index_path: None,
+            theme: ThemeConfig::default(),
+            umask: None,
         }
     }
 }
 
+impl AppConfig {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("chezmoi-tui").join("config.toml"))
+    }
+
+    /// Loads the config file, falling back to defaults when it does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        Self::parse(&content).with_context(|| format!("invalid config: {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.theme.build()?;
+        Ok(config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -19,5 +125,63 @@ mod tests {
     fn default_values_are_safe() {
         let cfg = AppConfig::default();
         assert!(cfg.require_two_step_confirmation);
+        assert!(!cfg.read_only);
+        assert!(cfg.audit_log_path.is_none());
+        assert!(cfg.protected_paths.iter().any(|glob| glob == ".ssh/**"));
+        assert_eq!(cfg.protected_path_mode, ProtectedPathMode::Confirm);
+        assert_eq!(cfg.batch_error_policy, BatchErrorPolicy::Continue);
+    }
+
+    #[test]
+    fn parse_overrides_only_given_keys() {
+        let cfg = AppConfig::parse(
+            r#"
+protected_paths = [".aws/**"]
+protected_path_mode = "refuse"
+read_only = true
+batch_error_policy = "stop"
+unmanaged_exclude = ["node_modules", ".cache/"]
+umask = 0o077
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.umask, Some(0o077));
+        assert_eq!(cfg.unmanaged_exclude, vec!["node_modules", ".cache/"]);
+        assert!(!cfg.unmanaged_gitignore);
+        assert!(cfg.require_two_step_confirmation);
+        assert!(cfg.read_only);
+        assert_eq!(cfg.protected_paths, vec![".aws/**".to_string()]);
+        assert_eq!(cfg.protected_path_mode, ProtectedPathMode::Refuse);
+        assert_eq!(cfg.batch_error_policy, BatchErrorPolicy::Stop);
+    }
+
+    #[test]
+    fn parse_reads_the_layout_table() {
+        let cfg = AppConfig::parse(
+            r#"
+[layout]
+list_percent = 25
+log = "right"
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(cfg.layout.list_percent, 25);
+        assert_eq!(cfg.layout.detail_percent, 65);
+        assert_eq!(cfg.layout.log, crate::layout::LogPlacement::Right);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys() {
+        assert!(AppConfig::parse("unknown_key = true").is_err());
+    }
+
+    #[test]
+    fn load_missing_file_uses_defaults() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_missing_config_{}.toml",
+            std::process::id()
+        ));
+        let cfg = AppConfig::load(&path).expect("load");
+        assert!(cfg.require_two_step_confirmation);
     }
 }
@@ -0,0 +1,380 @@
+use std::path::PathBuf;
+
+/// Removes ANSI SGR/CSI escape sequences, e.g. from `chezmoi diff --color=true`.
+pub(crate) fn strip_ansi(input: &str) -> String {
+    if !input.contains('\u{1b}') {
+        return input.to_string();
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\u{1b}' && matches!(chars.peek(), Some('[')) {
+            chars.next();
+            for next in chars.by_ref() {
+                if ('@'..='~').contains(&next) {
+                    break;
+                }
+            }
+            continue;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// One side of a side-by-side row: line number and text without the diff marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SplitCell {
+    pub number: usize,
+    pub text: String,
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SplitRowKind {
+    /// `diff --git`, `index`, `---`/`+++` and other file-level lines.
+    FileHeader(String),
+    HunkHeader(String),
+    Lines {
+        old: Option<SplitCell>,
+        new: Option<SplitCell>,
+    },
+    Note(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SplitRow {
+    pub kind: SplitRowKind,
+    /// Index of the unified diff line this row starts at, used to keep the
+    /// scroll position when switching layouts.
+    pub source_line: usize,
+}
+
+/// Parses the `@@ -a,b +c,d @@` header into old and new start line numbers.
+pub(crate) fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (old, rest) = rest.split_once(" +")?;
+    let (new, _) = rest.split_once(' ')?;
+    let start = |range: &str| range.split(',').next()?.parse::<usize>().ok();
+    Some((start(old)?, start(new)?))
+}
+
+/// Converts a unified diff into side-by-side rows. Runs of removed lines
+/// followed by added lines are paired row by row; the shorter side is padded.
+pub(crate) fn split_rows(diff: &str) -> Vec<SplitRow> {
+    let lines: Vec<String> = diff.lines().map(strip_ansi).collect();
+    let mut rows = Vec::new();
+    let mut old_no = 0usize;
+    let mut new_no = 0usize;
+    let mut in_hunk = false;
+    let mut index = 0usize;
+
+    while index < lines.len() {
+        let line = &lines[index];
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+        }
+
+        if let Some((old_start, new_start)) = parse_hunk_header(line) {
+            in_hunk = true;
+            old_no = old_start;
+            new_no = new_start;
+            rows.push(SplitRow {
+                kind: SplitRowKind::HunkHeader(line.clone()),
+                source_line: index,
+            });
+            index += 1;
+            continue;
+        }
+
+        if !in_hunk {
+            rows.push(SplitRow {
+                kind: SplitRowKind::FileHeader(line.clone()),
+                source_line: index,
+            });
+            index += 1;
+            continue;
+        }
+
+        if line.starts_with('\\') {
+            rows.push(SplitRow {
+                kind: SplitRowKind::Note(line.clone()),
+                source_line: index,
+            });
+            index += 1;
+            continue;
+        }
+
+        if line.starts_with('-') || line.starts_with('+') {
+            let start = index;
+            let mut removed = Vec::new();
+            while let Some(text) = lines.get(index).and_then(|line| line.strip_prefix('-')) {
+                removed.push(text.to_string());
+                index += 1;
+            }
+            let mut added = Vec::new();
+            while let Some(text) = lines.get(index).and_then(|line| line.strip_prefix('+')) {
+                added.push(text.to_string());
+                index += 1;
+            }
+
+            for offset in 0..removed.len().max(added.len()) {
+                let old = removed.get(offset).map(|text| {
+                    let cell = SplitCell {
+                        number: old_no,
+                        text: text.clone(),
+                        changed: true,
+                    };
+                    old_no += 1;
+                    cell
+                });
+                let new = added.get(offset).map(|text| {
+                    let cell = SplitCell {
+                        number: new_no,
+                        text: text.clone(),
+                        changed: true,
+                    };
+                    new_no += 1;
+                    cell
+                });
+                let source_line = if offset < removed.len() {
+                    start + offset
+                } else {
+                    start + removed.len() + offset
+                };
+                rows.push(SplitRow {
+                    kind: SplitRowKind::Lines { old, new },
+                    source_line,
+                });
+            }
+            continue;
+        }
+
+        let text = line.strip_prefix(' ').unwrap_or(line).to_string();
+        rows.push(SplitRow {
+            kind: SplitRowKind::Lines {
+                old: Some(SplitCell {
+                    number: old_no,
+                    text: text.clone(),
+                    changed: false,
+                }),
+                new: Some(SplitCell {
+                    number: new_no,
+                    text,
+                    changed: false,
+                }),
+            },
+            source_line: index,
+        });
+        old_no += 1;
+        new_no += 1;
+        index += 1;
+    }
+
+    rows
+}
+
+/// One `@@` hunk of a unified diff. `old` holds the context and removed lines
+/// (the destination as it is now), `new` the context and added lines (what
+/// `chezmoi apply` would write).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hunk {
+    /// Destination-relative path from the `+++ b/...` header.
+    pub file: Option<PathBuf>,
+    /// Index of the `@@` line in the unified diff.
+    pub line: usize,
+    pub old_start: usize,
+    pub new_start: usize,
+    /// Hunk body as printed, markers included.
+    pub lines: Vec<String>,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+    pub old_missing_newline: bool,
+    pub new_missing_newline: bool,
+}
+
+/// Collects the hunks of a (possibly multi-file) unified diff.
+pub(crate) fn hunks(diff: &str) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut file: Option<PathBuf> = None;
+    let mut in_hunk = false;
+    let mut last_marker = ' ';
+
+    for (index, line) in diff.lines().map(strip_ansi).enumerate() {
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+            file = line.rsplit_once(" b/").map(|(_, path)| PathBuf::from(path));
+            continue;
+        }
+        if let Some((old_start, new_start)) = parse_hunk_header(&line) {
+            in_hunk = true;
+            hunks.push(Hunk {
+                file: file.clone(),
+                line: index,
+                old_start,
+                new_start,
+                lines: Vec::new(),
+                old: Vec::new(),
+                new: Vec::new(),
+                old_missing_newline: false,
+                new_missing_newline: false,
+            });
+            continue;
+        }
+        if !in_hunk {
+            if let Some(path) = line.strip_prefix("+++ b/") {
+                file = Some(PathBuf::from(path));
+            }
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            continue;
+        };
+        hunk.lines.push(line.clone());
+
+        if line.starts_with('\\') {
+            match last_marker {
+                '-' => hunk.old_missing_newline = true,
+                '+' => hunk.new_missing_newline = true,
+                _ => {
+                    hunk.old_missing_newline = true;
+                    hunk.new_missing_newline = true;
+                }
+            }
+        } else if let Some(text) = line.strip_prefix('-') {
+            hunk.old.push(text.to_string());
+            last_marker = '-';
+        } else if let Some(text) = line.strip_prefix('+') {
+            hunk.new.push(text.to_string());
+            last_marker = '+';
+        } else {
+            let text = line.strip_prefix(' ').unwrap_or(&line).to_string();
+            hunk.old.push(text.clone());
+            hunk.new.push(text);
+            last_marker = ' ';
+        }
+    }
+
+    hunks
+}
+
+/// Splits `old` and `new` into (prefix, changed, suffix) char ranges by
+/// trimming their common prefix and suffix, for intra-line highlighting.
+pub(crate) fn changed_span(old: &str, new: &str) -> ((usize, usize), (usize, usize)) {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    let prefix = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+    let suffix = old_chars
+        .iter()
+        .rev()
+        .zip(new_chars.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    (
+        (prefix, old_chars.len() - suffix),
+        (prefix, new_chars.len() - suffix),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_ansi_removes_color_sequences() {
+        assert_eq!(strip_ansi("\u{1b}[31m-old\u{1b}[0m"), "-old");
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn split_rows_pairs_removed_and_added_lines_with_numbers() {
+        let diff = concat!(
+            "diff --git a/.zshrc b/.zshrc\n",
+            "--- a/.zshrc\n",
+            "+++ b/.zshrc\n",
+            "@@ -3,3 +3,4 @@\n",
+            " keep\n",
+            "-old one\n",
+            "-old two\n",
+            "+new one\n",
+            "+new two\n",
+            "+new three\n",
+            " tail\n",
+        );
+        let rows = split_rows(diff);
+
+        assert!(matches!(rows[0].kind, SplitRowKind::FileHeader(_)));
+        assert!(matches!(rows[3].kind, SplitRowKind::HunkHeader(_)));
+        let SplitRowKind::Lines { old, new } = &rows[4].kind else {
+            panic!("context row");
+        };
+        assert_eq!(old.as_ref().map(|cell| cell.number), Some(3));
+        assert_eq!(new.as_ref().map(|cell| cell.number), Some(3));
+
+        let SplitRowKind::Lines { old, new } = &rows[7].kind else {
+            panic!("padded row");
+        };
+        assert!(old.is_none());
+        assert_eq!(
+            new.as_ref().map(|cell| cell.text.as_str()),
+            Some("new three")
+        );
+        assert_eq!(new.as_ref().map(|cell| cell.number), Some(6));
+
+        let SplitRowKind::Lines { old, new } = &rows[8].kind else {
+            panic!("tail row");
+        };
+        assert_eq!(old.as_ref().map(|cell| cell.number), Some(6));
+        assert_eq!(new.as_ref().map(|cell| cell.number), Some(7));
+        assert_eq!(rows[8].source_line, 10);
+    }
+
+    #[test]
+    fn changed_span_trims_common_prefix_and_suffix() {
+        assert_eq!(
+            changed_span("export EDITOR=vim", "export EDITOR=nvim"),
+            ((14, 14), (14, 15))
+        );
+        assert_eq!(changed_span("same", "same"), ((4, 4), (4, 4)));
+        assert_eq!(changed_span("abc", "xyz"), ((0, 3), (0, 3)));
+    }
+
+    #[test]
+    fn hunks_split_sides_and_track_missing_newlines() {
+        let diff = concat!(
+            "diff --git a/.zshrc b/.zshrc\n",
+            "index 1..2 100644\n",
+            "--- a/.zshrc\n",
+            "+++ b/.zshrc\n",
+            "@@ -1,2 +1,2 @@\n",
+            " keep\n",
+            "-old\n",
+            "+new\n",
+            "@@ -9 +9 @@\n",
+            "-last\n",
+            "\\ No newline at end of file\n",
+            "+last\n",
+            "diff --git a/.gitconfig b/.gitconfig\n",
+            "@@ -0,0 +1 @@\n",
+            "+[user]\n",
+        );
+        let hunks = hunks(diff);
+
+        assert_eq!(hunks.len(), 3);
+        assert_eq!(hunks[0].file, Some(PathBuf::from(".zshrc")));
+        assert_eq!(hunks[0].line, 4);
+        assert_eq!(hunks[0].old, vec!["keep", "old"]);
+        assert_eq!(hunks[0].new, vec!["keep", "new"]);
+        assert!(hunks[1].old_missing_newline);
+        assert!(!hunks[1].new_missing_newline);
+        assert_eq!(hunks[2].file, Some(PathBuf::from(".gitconfig")));
+        assert_eq!((hunks[2].old_start, hunks[2].new_start), (0, 1));
+    }
+}
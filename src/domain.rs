@@ -117,6 +117,12 @@ impl Action {
         }
     }
 
+    pub fn from_label(label: &str) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.label() == label)
+    }
+
     pub fn description(self) -> &'static str {
         match self {
             Action::Apply => "apply target state to destination",
@@ -137,10 +143,47 @@ impl Action {
         }
     }
 
+    /// Whether the action, run without a target, acts on every managed path.
+    pub fn covers_all_targets(self) -> bool {
+        matches!(self, Action::Apply | Action::Update | Action::MergeAll)
+    }
+
     pub fn is_dangerous(self) -> bool {
         matches!(self, Action::Destroy | Action::Purge)
     }
 
+    /// Whether the action can change source, destination, or chezmoi state.
+    /// Read-only mode hides and refuses every mutating action.
+    pub fn is_mutating(self) -> bool {
+        match self {
+            Action::Apply
+            | Action::Update
+            | Action::EditConfig
+            | Action::EditConfigTemplate
+            | Action::EditIgnore
+            | Action::ReAdd
+            | Action::Merge
+            | Action::MergeAll
+            | Action::Add
+            | Action::Ignore
+            | Action::Edit
+            | Action::Forget
+            | Action::Chattr
+            | Action::Destroy
+            | Action::Purge => true,
+        }
+    }
+
+    /// Whether a successful run only changes the status of its own target,
+    /// never which paths are managed or unmanaged. Apply is excluded:
+    /// `exact_` directories make it delete unmanaged files.
+    pub fn only_changes_target_status(self) -> bool {
+        matches!(
+            self,
+            Action::ReAdd | Action::Merge | Action::Edit | Action::Chattr
+        )
+    }
+
     pub fn confirm_phrase(self) -> Option<&'static str> {
         match self {
             Action::Destroy => Some("DESTROY"),
@@ -187,6 +230,28 @@ impl ActionRequest {
             _ => Some(base.to_string()),
         }
     }
+
+    /// Phrase typed to confirm an action on a protected target,
+    /// e.g. `FORGET /home/me/.ssh/config`, or `APPLY ALL` for an action
+    /// over every managed path.
+    pub fn protected_confirmation_phrase(&self) -> Option<String> {
+        let action = self.action.label().to_ascii_uppercase();
+        match &self.target {
+            Some(target) => Some(format!("{action} {}", target.display())),
+            None if self.action.covers_all_targets() => Some(format!("{action} ALL")),
+            None => None,
+        }
+    }
+
+    pub fn required_phrase(&self, protected: bool) -> Option<String> {
+        self.confirmation_phrase().or_else(|| {
+            if protected {
+                self.protected_confirmation_phrase()
+            } else {
+                None
+            }
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -197,6 +262,22 @@ pub struct CommandResult {
     pub duration_ms: u64,
 }
 
+impl CommandResult {
+    /// Exit code recorded for a command that never exited: it could not be
+    /// started, or its worker task failed.
+    pub const NOT_RUN_EXIT_CODE: i32 = -1;
+
+    /// Result of a command that never exited, with the error as `stderr`.
+    pub fn not_run(error: String) -> Self {
+        Self {
+            exit_code: Self::NOT_RUN_EXIT_CODE,
+            stdout: String::new(),
+            stderr: error,
+            duration_ms: 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ListView {
     Status,
@@ -241,6 +322,30 @@ mod tests {
         assert_eq!(req.confirmation_phrase(), Some("PURGE ALL".to_string()));
     }
 
+    #[test]
+    fn required_phrase_uses_action_and_target_only_when_protected() {
+        let req = ActionRequest {
+            action: Action::Forget,
+            target: Some(PathBuf::from("/home/me/.ssh/config")),
+            chattr_attrs: None,
+        };
+        assert_eq!(req.required_phrase(false), None);
+        assert_eq!(
+            req.required_phrase(true),
+            Some("FORGET /home/me/.ssh/config".to_string())
+        );
+
+        let apply_all = ActionRequest {
+            action: Action::Apply,
+            target: None,
+            chattr_attrs: None,
+        };
+        assert_eq!(
+            apply_all.required_phrase(true),
+            Some("APPLY ALL".to_string())
+        );
+    }
+
     #[test]
     fn readd_action_requires_target() {
         assert!(Action::ReAdd.needs_target());
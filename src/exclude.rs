@@ -0,0 +1,217 @@
+use crate::policy::glob_match;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// One gitignore-style line: `!` re-includes, a trailing `/` only matches
+/// directories, and a pattern without an inner `/` matches at any depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    glob: String,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let dir_only = line.ends_with('/');
+        let pattern = line.trim_end_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+        let glob = if pattern.trim_start_matches('/').contains('/') || pattern.starts_with('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        Some(Self {
+            glob,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+/// Ordered rules where the last matching one wins, as in a `.gitignore`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub(crate) fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            rules: lines.into_iter().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// `Some(true)` when `path` (`/`-separated, relative to the rules'
+    /// directory) is excluded, `Some(false)` when a `!` rule re-includes it,
+    /// `None` when no rule applies.
+    fn verdict(&self, path: &str, is_dir: &dyn Fn() -> bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| glob_match(&rule.glob, path) && (!rule.dir_only || is_dir()))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Noise hidden from the unmanaged view: the configured globs plus, when
+/// enabled, `.gitignore` files found between the working directory and
+/// each entry. Parsed `.gitignore` files are cached until `clear_cache`.
+#[derive(Debug, Default)]
+pub(crate) struct UnmanagedExcludes {
+    configured: RuleSet,
+    gitignore: bool,
+    /// Toggled from the view to show everything.
+    pub enabled: bool,
+    gitignores: RefCell<HashMap<PathBuf, Rc<RuleSet>>>,
+}
+
+impl UnmanagedExcludes {
+    pub(crate) fn new(patterns: &[String], gitignore: bool) -> Self {
+        Self {
+            configured: RuleSet::parse(patterns.iter().map(String::as_str)),
+            gitignore,
+            enabled: true,
+            gitignores: RefCell::default(),
+        }
+    }
+
+    pub(crate) fn clear_cache(&self) {
+        self.gitignores.borrow_mut().clear();
+    }
+
+    /// Whether `path` (relative to `root`) or any of its ancestors is
+    /// excluded.
+    pub(crate) fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        let parts = path_parts(path);
+        (1..=parts.len()).any(|len| {
+            let last = len == parts.len();
+            self.excludes_parts(root, &parts[..len], &|| !last || root.join(path).is_dir())
+        })
+    }
+
+    /// Like `is_excluded`, but only checks `path` itself; for entries read
+    /// from a directory that is already known to be shown.
+    pub(crate) fn is_excluded_entry(&self, root: &Path, path: &Path) -> bool {
+        let parts = path_parts(path);
+        !parts.is_empty() && self.excludes_parts(root, &parts, &|| root.join(path).is_dir())
+    }
+
+    fn excludes_parts(&self, root: &Path, parts: &[String], is_dir: &dyn Fn() -> bool) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.configured.verdict(&parts.join("/"), is_dir) == Some(true) {
+            return true;
+        }
+        if !self.gitignore {
+            return false;
+        }
+        // Deeper files override shallower ones, as in git.
+        let mut excluded = false;
+        let mut dir = root.to_path_buf();
+        for depth in 0..parts.len() {
+            if let Some(verdict) = self
+                .gitignore_rules(&dir)
+                .verdict(&parts[depth..].join("/"), is_dir)
+            {
+                excluded = verdict;
+            }
+            dir.push(&parts[depth]);
+        }
+        excluded
+    }
+
+    fn gitignore_rules(&self, dir: &Path) -> Rc<RuleSet> {
+        if let Some(rules) = self.gitignores.borrow().get(dir) {
+            return rules.clone();
+        }
+        let rules = Rc::new(
+            fs::read_to_string(dir.join(".gitignore"))
+                .map(|content| RuleSet::parse(content.lines()))
+                .unwrap_or_default(),
+        );
+        self.gitignores
+            .borrow_mut()
+            .insert(dir.to_path_buf(), rules.clone());
+        rules
+    }
+}
+
+fn path_parts(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_follow_gitignore_anchoring_negation_and_dir_only() {
+        let rules = RuleSet::parse([
+            "# comment",
+            "node_modules",
+            "/build",
+            ".local/share/Trash",
+            "*.log",
+            "!keep.log",
+            "cache/",
+        ]);
+        let file = || false;
+        let dir = || true;
+        assert_eq!(rules.verdict("node_modules", &dir), Some(true));
+        assert_eq!(rules.verdict("dev/app/node_modules", &dir), Some(true));
+        assert_eq!(rules.verdict("build", &dir), Some(true));
+        assert_eq!(rules.verdict("dev/build", &dir), None);
+        assert_eq!(rules.verdict(".local/share/Trash", &dir), Some(true));
+        assert_eq!(rules.verdict("a/b.log", &file), Some(true));
+        assert_eq!(rules.verdict("a/keep.log", &file), Some(false));
+        assert_eq!(rules.verdict("cache", &dir), Some(true));
+        assert_eq!(rules.verdict("cache", &file), None);
+    }
+
+    #[test]
+    fn excludes_cover_descendants_nested_gitignores_and_the_toggle() {
+        let root = std::env::temp_dir().join(format!(
+            "chezmoi_tui_exclude_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("dev/app/target")).expect("create dirs");
+        fs::write(root.join("dev/.gitignore"), "*.tmp\n").expect("write");
+        fs::write(root.join("dev/app/.gitignore"), "target/\n!keep.tmp\n").expect("write");
+
+        let mut excludes = UnmanagedExcludes::new(&[".cache".to_string()], true);
+        assert!(excludes.is_excluded(&root, Path::new(".cache/fontconfig/x")));
+        assert!(!excludes.is_excluded_entry(&root, Path::new("dev")));
+        assert!(excludes.is_excluded_entry(&root, Path::new("dev/app/target")));
+        assert!(excludes.is_excluded(&root, Path::new("dev/app/target/debug")));
+        assert!(excludes.is_excluded_entry(&root, Path::new("dev/a.tmp")));
+        assert!(!excludes.is_excluded_entry(&root, Path::new("dev/app/keep.tmp")));
+
+        excludes.enabled = false;
+        assert!(!excludes.is_excluded(&root, Path::new(".cache")));
+        let without_gitignore = UnmanagedExcludes::new(&[], false);
+        assert!(!without_gitignore.is_excluded(&root, Path::new("dev/app/target")));
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::domain::ListView;
+use std::path::PathBuf;
+
+/// Upper bound on rows kept after ranking; the modal shows only a screenful.
+const MAX_FINDER_RESULTS: usize = 200;
+
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP: i32 = -1;
+const BONUS_CONSECUTIVE: i32 = 12;
+/// Match at the start of a path segment, after `/` or a leading dot.
+const BONUS_SEGMENT: i32 = 10;
+/// Match right after `.`, `_`, `-` or a space.
+const BONUS_WORD: i32 = 8;
+const BONUS_CAMEL: i32 = 7;
+/// Per matched character inside the last path component.
+const BONUS_FILE_NAME: i32 = 2;
+const NO_MATCH: i32 = i32::MIN / 2;
+
+/// A scored fuzzy match; `positions` are char indices into the candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FuzzyMatch {
+    pub score: i32,
+    pub positions: Vec<usize>,
+}
+
+/// Case-insensitive fzf-style match of `query` as a subsequence of `text`.
+/// Matches at path segment and word starts and runs of consecutive characters
+/// score higher; characters skipped between matches cost a little.
+pub(crate) fn fuzzy_match(text: &str, query: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    let original: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = original.iter().map(char::to_ascii_lowercase).collect();
+
+    let mut rest = query.iter().peekable();
+    for ch in &lower {
+        if rest.peek() == Some(&ch) {
+            rest.next();
+        }
+    }
+    if rest.peek().is_some() {
+        return None;
+    }
+
+    let file_name_start = original
+        .iter()
+        .rposition(|ch| *ch == '/')
+        .map_or(0, |slash| slash + 1);
+    let bonus: Vec<i32> = (0..original.len())
+        .map(|index| {
+            let before_prev = index.checked_sub(2).map(|prev| original[prev]);
+            let boundary = match index.checked_sub(1).map(|prev| original[prev]) {
+                None | Some('/') => BONUS_SEGMENT,
+                Some('.') if matches!(before_prev, None | Some('/')) => BONUS_SEGMENT,
+                Some('.' | '_' | '-' | ' ') => BONUS_WORD,
+                Some(prev) if prev.is_lowercase() && original[index].is_uppercase() => BONUS_CAMEL,
+                Some(_) => 0,
+            };
+            let file_name = if index >= file_name_start {
+                BONUS_FILE_NAME
+            } else {
+                0
+            };
+            boundary + file_name
+        })
+        .collect();
+
+    // `best[i][j]`: best score for query[..=i] within text[..=j].
+    // `ending[i][j]`: same, but with query[i] matched exactly at j.
+    let (rows, cols) = (query.len(), lower.len());
+    let mut best = vec![vec![NO_MATCH; cols]; rows];
+    let mut ending = vec![vec![NO_MATCH; cols]; rows];
+    for i in 0..rows {
+        // Gaps after the last matched character are free.
+        let gap = if i + 1 == rows { 0 } else { SCORE_GAP };
+        let mut previous = NO_MATCH;
+        for j in 0..cols {
+            if lower[j] == query[i] {
+                let score = if i == 0 {
+                    SCORE_MATCH + bonus[j]
+                } else if j > 0 {
+                    let after_gap = best[i - 1][j - 1].saturating_add(SCORE_MATCH + bonus[j]);
+                    let run = ending[i - 1][j - 1].saturating_add(SCORE_MATCH + BONUS_CONSECUTIVE);
+                    after_gap.max(run)
+                } else {
+                    NO_MATCH
+                };
+                ending[i][j] = score;
+            }
+            previous = ending[i][j].max(previous.saturating_add(gap));
+            best[i][j] = previous;
+        }
+    }
+
+    let score = best[rows - 1][cols - 1];
+    if score <= NO_MATCH / 2 {
+        return None;
+    }
+
+    let mut positions = vec![0; rows];
+    let mut must_match = false;
+    let mut j = cols;
+    for i in (0..rows).rev() {
+        while j > 0 {
+            j -= 1;
+            if ending[i][j] > NO_MATCH / 2 && (must_match || ending[i][j] == best[i][j]) {
+                must_match = i > 0
+                    && j > 0
+                    && ending[i][j]
+                        == ending[i - 1][j - 1].saturating_add(SCORE_MATCH + BONUS_CONSECUTIVE);
+                positions[i] = j;
+                break;
+            }
+        }
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// A path offered by the finder together with the list it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FinderCandidate {
+    pub view: ListView,
+    /// Path as shown in that list (home- or working-dir-relative).
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FinderResult {
+    pub candidate: usize,
+    pub positions: Vec<usize>,
+}
+
+/// State of the global fuzzy finder over status, managed and unmanaged paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FuzzyFinder {
+    pub query: String,
+    pub candidates: Vec<FinderCandidate>,
+    pub results: Vec<FinderResult>,
+    pub selected: usize,
+}
+
+impl FuzzyFinder {
+    pub(crate) fn new(candidates: Vec<FinderCandidate>) -> Self {
+        let mut finder = Self {
+            query: String::new(),
+            candidates,
+            results: Vec::new(),
+            selected: 0,
+        };
+        finder.rank();
+        finder
+    }
+
+    pub(crate) fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.rank();
+    }
+
+    pub(crate) fn selected_candidate(&self) -> Option<&FinderCandidate> {
+        let result = self.results.get(self.selected)?;
+        self.candidates.get(result.candidate)
+    }
+
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let count = self.results.len();
+        if count == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            self.selected.checked_sub(1).unwrap_or(count - 1)
+        };
+    }
+
+    /// Best score first; ties go to the shorter path, then to list order.
+    fn rank(&mut self) {
+        let mut scored: Vec<(i32, usize, FinderResult)> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| {
+                let text = candidate.path.to_string_lossy();
+                let found = fuzzy_match(&text, &self.query)?;
+                Some((
+                    found.score,
+                    text.chars().count(),
+                    FinderResult {
+                        candidate: index,
+                        positions: found.positions,
+                    },
+                ))
+            })
+            .collect();
+        if !self.query.trim().is_empty() {
+            scored.sort_by(|a, b| {
+                b.0.cmp(&a.0)
+                    .then(a.1.cmp(&b.1))
+                    .then(a.2.candidate.cmp(&b.2.candidate))
+            });
+        }
+        scored.truncate(MAX_FINDER_RESULTS);
+        self.results = scored.into_iter().map(|(_, _, result)| result).collect();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_prefers_segment_starts_and_file_names() {
+        let found = fuzzy_match(".config/nvim/init.lua", "nvinit").expect("match");
+        assert_eq!(found.positions, vec![8, 9, 13, 14, 15, 16]);
+
+        let file_name = fuzzy_match(".zshrc", "zsh").expect("match").score;
+        let buried = fuzzy_match(".config/zoo/sh/rc", "zsh")
+            .expect("match")
+            .score;
+        assert!(file_name > buried);
+
+        assert_eq!(fuzzy_match(".bashrc", "zsh"), None);
+        assert!(fuzzy_match(".Bashrc", "BASH").is_some());
+    }
+
+    #[test]
+    fn finder_ranks_across_views_and_wraps_selection() {
+        let candidate = |view, path: &str| FinderCandidate {
+            view,
+            path: PathBuf::from(path),
+        };
+        let mut finder = FuzzyFinder::new(vec![
+            candidate(ListView::Managed, ".config/git/config"),
+            candidate(ListView::Unmanaged, "notes/gitconfig.bak"),
+            candidate(ListView::Status, ".gitconfig"),
+        ]);
+        assert_eq!(finder.results.len(), 3);
+
+        finder.set_query("gitconfig".to_string());
+        let ranked: Vec<&str> = finder
+            .results
+            .iter()
+            .map(|result| finder.candidates[result.candidate].path.to_str().unwrap())
+            .collect();
+        assert_eq!(ranked[0], ".gitconfig");
+        assert_eq!(ranked.len(), 3);
+
+        finder.move_selection(false);
+        assert_eq!(finder.selected, 2);
+        finder.move_selection(true);
+        assert_eq!(
+            finder.selected_candidate().map(|c| c.view),
+            Some(ListView::Status)
+        );
+    }
+}
@@ -1,13 +1,20 @@
 use crate::actions::{
     build_action_requests, dispatch_action_request, execute_action_request, maybe_continue_batch,
-    send_task, squash_lines, validate_action_requests,
+    refresh_task_after, send_task, squash_lines, validate_action_requests, validate_queue,
 };
-use crate::app::{App, BackendEvent, BackendTask, ConfirmStep, InputKind, ModalState};
-use crate::domain::{Action, ActionRequest, ListView};
+use crate::app::{App, BackendEvent, BackendTask, ConfirmStep, DetailKind, InputKind, ModalState};
+use crate::audit::ExecutionMode;
+use crate::config::ProtectedPathMode;
+use crate::domain::{Action, ActionRequest, CommandResult, ListView};
+use crate::hunk::HunkDirection;
 use crate::ignore::IgnorePatternMode;
+use crate::merge::{MergeView, Resolution};
+use crate::plan::{load_plan, save_plan};
 use crate::preview::maybe_enqueue_auto_detail;
+use crate::repo_diff::RepoDiffBrowser;
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
 use tokio::sync::mpsc::UnboundedSender;
 
 pub(crate) fn handle_backend_event(
@@ -24,18 +31,104 @@ pub(crate) fn handle_backend_event(
             app.apply_refresh_entries(status, managed, unmanaged);
             app.rebuild_visible_entries();
             app.busy = false;
+            app.stale = app.refresh_pending();
             maybe_enqueue_auto_detail(app, task_tx)?;
         }
+        BackendEvent::FilesChanged {
+            paths,
+            source_paths,
+        } => {
+            let paths = app.drop_own_writes(paths);
+            let first = paths.iter().chain(&source_paths).next().map(|path| {
+                path.strip_prefix(&app.home_dir).map_or_else(
+                    |_| path.display().to_string(),
+                    |path| format!("~/{}", path.display()),
+                )
+            });
+            if let Some(first) = first {
+                let more = match paths.len() + source_paths.len() {
+                    0 | 1 => String::new(),
+                    count => format!(" (+{} more)", count - 1),
+                };
+                app.log(format!("changed on disk: {first}{more}"));
+            }
+            if paths.is_empty() && source_paths.is_empty() {
+                return Ok(());
+            }
+            app.stale = true;
+            // Edits to managed files only need their status re-read; anything
+            // else may have changed which files are managed or unmanaged.
+            let (targets, other): (Vec<PathBuf>, Vec<PathBuf>) = paths
+                .into_iter()
+                .partition(|path| app.is_absolute_path_managed(path));
+            if source_paths.is_empty() && other.is_empty() {
+                app.queued_status_targets.extend(targets);
+            } else {
+                app.refresh_queued = true;
+            }
+            run_queued_refresh(app, task_tx)?;
+        }
+        BackendEvent::StatusRefreshed { status } => {
+            app.apply_status_entries(status);
+            app.rebuild_visible_entries();
+            app.busy = false;
+            maybe_enqueue_auto_detail(app, task_tx)?;
+        }
+        BackendEvent::StatusPathsRefreshed { targets, status } => {
+            app.merge_status_entries(&targets, status);
+            app.stale = app.refresh_pending();
+            app.rebuild_visible_entries();
+            app.busy = false;
+            maybe_enqueue_auto_detail(app, task_tx)?;
+        }
+        BackendEvent::UnmanagedDirRefreshed { dir, unmanaged } => {
+            app.merge_unmanaged_entries(&dir, unmanaged);
+            app.rebuild_visible_entries();
+            app.busy = false;
+        }
+        BackendEvent::UnmanagedIndexed { generation, batch } => {
+            if let Some(err) = &batch.save_error {
+                app.log(format!("unmanaged index not saved: {err}"));
+            }
+            app.apply_unmanaged_index_batch(generation, batch);
+        }
         BackendEvent::DiffLoaded { target, diff } => {
             app.set_detail_diff(target.as_deref(), diff.text);
             app.busy = false;
         }
-        BackendEvent::PreviewLoaded { target, content } => {
+        BackendEvent::PreviewLoaded {
+            target,
+            content,
+            metadata,
+            hex,
+        } => {
             app.set_detail_preview(&target, content);
+            app.detail_metadata = metadata;
+            app.detail_hex = hex;
+            app.apply_search_jump(&target);
+            app.busy = false;
+        }
+        BackendEvent::ContentSearched {
+            query,
+            scope,
+            results,
+        } => {
+            if let ModalState::ContentSearch(search) = &mut app.modal {
+                search.set_results(query, scope, results);
+            }
+        }
+        BackendEvent::HexPageLoaded {
+            target,
+            content,
+            page,
+        } => {
+            app.set_detail_hex_page(&target, content, page);
             app.busy = false;
         }
         BackendEvent::ActionFinished { request, result } => {
             app.busy = false;
+            app.finish_own_write();
+            app.record_audit(&request, ExecutionMode::Background, &result);
             let target = request
                 .target
                 .as_ref()
@@ -52,18 +145,147 @@ pub(crate) fn handle_backend_event(
             }
 
             if app.batch_in_progress() {
-                maybe_continue_batch(app, task_tx)?;
+                let outcome = if result.exit_code == 0 {
+                    Ok(())
+                } else if result.stderr.trim().is_empty() {
+                    Err(format!("exit={}", result.exit_code))
+                } else {
+                    Err(result.stderr.trim().to_string())
+                };
+                maybe_continue_batch(app, task_tx, outcome)?;
             } else if result.exit_code == 0 {
-                send_task(app, task_tx, BackendTask::RefreshAll)?;
+                send_task(app, task_tx, refresh_task_after([&request]))?;
             }
         }
-        BackendEvent::Error { context, message } => {
+        BackendEvent::ActionFailed { request, message } => {
+            app.busy = false;
+            app.finish_own_write();
+            app.record_audit(
+                &request,
+                ExecutionMode::Background,
+                &CommandResult::not_run(message.clone()),
+            );
+            app.log(format!("error[action]: {message}"));
+            maybe_continue_batch(app, task_tx, Err(message))?;
+        }
+        BackendEvent::HunkFailed {
+            direction,
+            written,
+            message,
+        } => {
+            app.busy = false;
+            app.finish_own_write();
+            app.record_write_audit(direction.label(), &written, Err(&message));
+            app.log(format!("error[hunk]: {message}"));
+        }
+        BackendEvent::MergeSaveFailed { source, message } => {
             app.busy = false;
+            app.finish_own_write();
+            app.record_write_audit("merge", &source, Err(&message));
+            app.log(format!("error[merge]: {message}"));
+        }
+        BackendEvent::Error { context, message } => {
+            // Content searches run beside the queue and never set `busy`.
+            if context != "search" {
+                app.busy = false;
+            }
             app.log(format!("error[{context}]: {message}"));
-            if context == "action" && app.batch_in_progress() {
-                maybe_continue_batch(app, task_tx)?;
+            if context == "action" {
+                maybe_continue_batch(app, task_tx, Err(message))?;
+            } else if context == "chattr"
+                && let ModalState::ChattrPicker(picker) = &mut app.modal
+            {
+                picker.message = Some("current attributes unavailable".to_string());
+            } else if context == "search"
+                && let ModalState::ContentSearch(search) = &mut app.modal
+            {
+                search.searching = false;
+                search.message = Some("search failed, see the log".to_string());
+            }
+        }
+        BackendEvent::SourceInfoLoaded { targets, sources } => {
+            app.busy = false;
+            if let ModalState::ChattrPicker(picker) = &mut app.modal
+                && picker.targets == targets
+            {
+                picker.sources = Some(sources);
+            }
+        }
+        BackendEvent::RepoDiffLoaded { diff } => {
+            app.busy = false;
+            let browser = RepoDiffBrowser::new(&diff.text);
+            if browser.files.is_empty() {
+                app.log("diff browser: no differences".to_string());
+            } else {
+                app.modal = ModalState::RepoDiff(browser);
+            }
+        }
+        BackendEvent::MergeLoaded {
+            target,
+            source,
+            ours,
+            base,
+            theirs,
+        } => {
+            app.busy = false;
+            match MergeView::new(target, source, &ours, &base, &theirs) {
+                Ok(mut view) => {
+                    view.protected_by = app.protected_pattern_for(&view.target);
+                    if view.change_count() == 0 {
+                        app.log(format!(
+                            "merge: {} has no differences",
+                            view.target.display()
+                        ));
+                    } else {
+                        app.modal = ModalState::Merge(view);
+                    }
+                }
+                Err(err) => app.log(format!("merge failed: {err:#}")),
             }
         }
+        BackendEvent::MergeSaved { target, source } => {
+            app.busy = false;
+            app.finish_own_write();
+            app.record_write_audit("merge", &source, Ok(()));
+            app.log(format!(
+                "merge {}: wrote {}",
+                target.display(),
+                source.display()
+            ));
+            send_task(
+                app,
+                task_tx,
+                BackendTask::RefreshStatusPaths {
+                    targets: vec![target],
+                },
+            )?;
+            let target = app.detail_target.clone();
+            send_task(app, task_tx, BackendTask::LoadDiff { target })?;
+        }
+        BackendEvent::HunkWritten {
+            target,
+            direction,
+            written,
+        } => {
+            app.busy = false;
+            app.finish_own_write();
+            app.record_write_audit(direction.label(), &written, Ok(()));
+            app.log(format!(
+                "{} {}: wrote {}",
+                direction.label(),
+                target.display(),
+                written.display()
+            ));
+            send_task(
+                app,
+                task_tx,
+                BackendTask::RefreshStatusPaths {
+                    targets: vec![target],
+                },
+            )?;
+            let target = app.detail_target.clone();
+            send_task(app, task_tx, BackendTask::LoadDiff { target })?;
+        }
     }
 
     Ok(())
@@ -86,6 +308,15 @@ pub(crate) fn handle_key_event(
         ModalState::ActionMenu { .. } => handle_action_menu_key(app, key, task_tx),
         ModalState::Confirm { .. } => handle_confirm_key(app, key, task_tx),
         ModalState::Input { .. } => handle_input_key(app, key, task_tx),
+        ModalState::BatchResults { .. } => handle_batch_results_key(app, key, task_tx),
+        ModalState::Queue { .. } => handle_queue_key(app, key, task_tx),
+        ModalState::ChattrPicker(_) => handle_chattr_picker_key(app, key, task_tx),
+        ModalState::HunkConfirm { .. } => handle_hunk_confirm_key(app, key, task_tx),
+        ModalState::Merge(_) => handle_merge_key(app, key, task_tx),
+        ModalState::RepoDiff(_) => handle_repo_diff_key(app, key),
+        ModalState::DetailSearch { .. } => handle_detail_search_key(app, key),
+        ModalState::Finder(_) => handle_finder_key(app, key, task_tx),
+        ModalState::ContentSearch(_) => handle_content_search_key(app, key, task_tx),
     }
 }
 
@@ -96,26 +327,65 @@ fn handle_key_without_modal(
 ) -> Result<()> {
     let mut selection_changed = false;
 
+    if let Some(prefix) = app.pending_key.take()
+        && key.code == KeyCode::Char('h')
+    {
+        app.jump_hunk(prefix == ']');
+        return Ok(());
+    }
+
     match key.code {
         KeyCode::Char('q') => app.should_quit = true,
         KeyCode::Char('?') => app.toggle_footer_help(),
         KeyCode::Char('/') if app.focus == crate::app::PaneFocus::List => app.open_list_filter(),
+        KeyCode::Char('/') if app.focus == crate::app::PaneFocus::Detail => {
+            app.open_detail_search();
+        }
+        KeyCode::Esc
+            if app.focus == crate::app::PaneFocus::Detail && !app.detail_search().is_empty() =>
+        {
+            app.set_detail_search(String::new());
+        }
+        KeyCode::Char(c @ ('n' | 'N'))
+            if app.focus == crate::app::PaneFocus::Detail
+                && !app.detail_search().is_empty()
+                && !app.jump_detail_match(c == 'n') =>
+        {
+            app.log(format!("no match for \"{}\"", app.detail_search()));
+        }
         KeyCode::Esc
             if app.focus == crate::app::PaneFocus::List && !app.list_filter().is_empty() =>
         {
             app.apply_list_filter_immediately(String::new());
             selection_changed = true;
         }
-        KeyCode::Tab => app.focus = app.focus.next(),
+        KeyCode::Tab => app.cycle_focus(),
+        KeyCode::Char(c @ ('+' | '=' | '-')) if !app.resize_focused_pane(c != '-') => {
+            app.log("pane is already at its size limit".to_string());
+        }
+        KeyCode::Char('z') => app.zoomed = !app.zoomed,
+        KeyCode::Char('L') => {
+            let placement = app.cycle_log_placement();
+            app.log(format!("log pane: {}", placement.label()));
+        }
         KeyCode::Char(' ') if app.focus == crate::app::PaneFocus::List => {
             let _ = app.toggle_selected_mark();
         }
         KeyCode::Char('c')
-            if key.modifiers.is_empty() && app.focus == crate::app::PaneFocus::List =>
+            if key.modifiers.is_empty()
+                && app.focus == crate::app::PaneFocus::List
+                && app.clear_marked_entries() =>
         {
-            if app.clear_marked_entries() {
-                app.log("cleared multi-selection".to_string());
-            }
+            app.log("cleared multi-selection".to_string());
+        }
+        KeyCode::Char('H') if app.focus == crate::app::PaneFocus::Log => {
+            app.toggle_audit_history();
+        }
+        KeyCode::Char('[') if app.focus == crate::app::PaneFocus::Log => {
+            app.select_audit_session(false);
+        }
+        KeyCode::Char(']') if app.focus == crate::app::PaneFocus::Log => {
+            app.select_audit_session(true);
         }
         KeyCode::Char('j') | KeyCode::Down => match app.focus {
             crate::app::PaneFocus::Detail => {
@@ -159,15 +429,14 @@ fn handle_key_without_modal(
             }
             crate::app::PaneFocus::List => {}
         },
-        KeyCode::Char('l') | KeyCode::Right => {
-            if app.expand_selected_directory() {
-                selection_changed = true;
+        KeyCode::Char('l') | KeyCode::Right if app.expand_selected_directory() => {
+            selection_changed = true;
+            if let Some(dir) = app.unmanaged_dir_to_refresh() {
+                send_task(app, task_tx, BackendTask::RefreshUnmanagedDir { dir })?;
             }
         }
-        KeyCode::Char('h') | KeyCode::Left => {
-            if app.collapse_selected_directory_or_parent() {
-                selection_changed = true;
-            }
+        KeyCode::Char('h') | KeyCode::Left if app.collapse_selected_directory_or_parent() => {
+            selection_changed = true;
         }
         KeyCode::Char('1') => {
             app.switch_view(ListView::Status);
@@ -213,6 +482,7 @@ fn handle_key_without_modal(
                 },
             )?;
         }
+        KeyCode::Enter if app.toggle_selected_group() => selection_changed = true,
         KeyCode::Enter => {
             if app.view == ListView::Unmanaged && app.selected_is_directory() {
                 app.clear_detail();
@@ -232,11 +502,89 @@ fn handle_key_without_modal(
                     app.clear_detail();
                     return Ok(());
                 }
-                send_task(app, task_tx, BackendTask::LoadPreview { target, absolute })?;
+                let managed = app.is_absolute_path_managed(&absolute);
+                send_task(
+                    app,
+                    task_tx,
+                    BackendTask::LoadPreview {
+                        target,
+                        absolute,
+                        managed,
+                    },
+                )?;
             }
             _ => app.log("No target selected for preview".to_string()),
         },
+        KeyCode::Char('f') => app.open_fuzzy_finder(),
+        KeyCode::Char('F') => app.open_content_search(),
+        KeyCode::Char('o') if app.focus == crate::app::PaneFocus::List => {
+            let sort = app.cycle_sort_mode();
+            app.log(format!("{} sorted by {}", app.view.title(), sort.label()));
+            selection_changed = true;
+        }
+        KeyCode::Char('g') if app.focus == crate::app::PaneFocus::List => {
+            match app.cycle_group_mode() {
+                Some(group) => app.log(format!("Status grouped by {}", group.label())),
+                None => app.log("grouping is available in the status view".to_string()),
+            }
+            selection_changed = true;
+        }
+        KeyCode::Char('I') if app.focus == crate::app::PaneFocus::List => {
+            if app.view == ListView::Unmanaged {
+                let message = if app.toggle_unmanaged_excludes() {
+                    "Unmanaged exclusions on: noise hidden"
+                } else {
+                    "Unmanaged exclusions off: showing everything"
+                };
+                app.log(message.to_string());
+                selection_changed = true;
+            } else {
+                app.log("exclusions apply to the unmanaged view".to_string());
+            }
+        }
         KeyCode::Char('a') => app.open_action_menu(),
+        KeyCode::Char('p') => app.open_queue(),
+        KeyCode::Char('s') if app.detail_kind == DetailKind::Diff => {
+            app.toggle_diff_layout();
+        }
+        KeyCode::Char(prefix @ ('[' | ']'))
+            if app.focus == crate::app::PaneFocus::Detail
+                && app.detail_kind == DetailKind::Diff =>
+        {
+            app.pending_key = Some(prefix);
+        }
+        KeyCode::Char(key @ ('[' | ']'))
+            if app.focus == crate::app::PaneFocus::Detail && app.detail_hex.is_some() =>
+        {
+            match app.hex_page_task(key == ']') {
+                Some(task) => send_task(app, task_tx, task)?,
+                None if key == ']' => app.log("already at the last hex page".to_string()),
+                None => app.log("already at the first hex page".to_string()),
+            }
+        }
+        KeyCode::Char('A')
+            if app.focus == crate::app::PaneFocus::Detail
+                && app.detail_kind == DetailKind::Diff =>
+        {
+            request_hunk_write(app, HunkDirection::ToDestination);
+        }
+        KeyCode::Char('R')
+            if app.focus == crate::app::PaneFocus::Detail
+                && app.detail_kind == DetailKind::Diff =>
+        {
+            request_hunk_write(app, HunkDirection::ToSource);
+        }
+        KeyCode::Char('D') => {
+            // Marks made in the browser apply to the status list.
+            if app.view != ListView::Status {
+                app.switch_view(ListView::Status);
+                selection_changed = true;
+            }
+            send_task(app, task_tx, BackendTask::LoadRepoDiff)?;
+        }
+        KeyCode::Char('m') if app.focus == crate::app::PaneFocus::List => {
+            open_builtin_merge(app, task_tx)?;
+        }
         KeyCode::Char('e') => {
             let request = ActionRequest {
                 action: Action::Edit,
@@ -248,7 +596,7 @@ fn handle_key_without_modal(
             } else if !app.selected_is_managed() {
                 app.log("edit is available only for managed files".to_string());
             } else {
-                execute_action_request(app, task_tx, request)?;
+                dispatch_action_request(app, task_tx, request)?;
             }
         }
         _ => {}
@@ -261,6 +609,207 @@ fn handle_key_without_modal(
     Ok(())
 }
 
+fn handle_detail_search_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let ModalState::DetailSearch { value, original } = &mut app.modal else {
+        return Ok(());
+    };
+
+    match key.code {
+        KeyCode::Esc => {
+            let original = original.clone();
+            app.close_modal();
+            app.set_detail_search(original);
+        }
+        KeyCode::Enter => app.close_modal(),
+        KeyCode::Backspace => {
+            value.pop();
+            let query = value.clone();
+            app.set_detail_search(query);
+        }
+        KeyCode::Char(c)
+            if !key.modifiers.contains(KeyModifiers::CONTROL)
+                && !key.modifiers.contains(KeyModifiers::ALT)
+                && !key.modifiers.contains(KeyModifiers::SUPER) =>
+        {
+            value.push(c);
+            let query = value.clone();
+            app.set_detail_search(query);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_finder_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let ModalState::Finder(finder) = &mut app.modal else {
+        return Ok(());
+    };
+
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.close_modal(),
+        KeyCode::Enter => {
+            let Some(chosen) = finder.selected_candidate().cloned() else {
+                return Ok(());
+            };
+            app.close_modal();
+            if !app.reveal_path(chosen.view, &chosen.path) {
+                app.log(format!(
+                    "{} is no longer listed in {}",
+                    chosen.path.display(),
+                    chosen.view.title()
+                ));
+            }
+            maybe_enqueue_auto_detail(app, task_tx)?;
+        }
+        KeyCode::Down => finder.move_selection(true),
+        KeyCode::Up => finder.move_selection(false),
+        KeyCode::Char('n') if ctrl => finder.move_selection(true),
+        KeyCode::Char('p') if ctrl => finder.move_selection(false),
+        KeyCode::Backspace => {
+            let mut query = finder.query.clone();
+            query.pop();
+            finder.set_query(query);
+        }
+        KeyCode::Char(c)
+            if !ctrl
+                && !key.modifiers.contains(KeyModifiers::ALT)
+                && !key.modifiers.contains(KeyModifiers::SUPER) =>
+        {
+            let query = format!("{}{c}", finder.query);
+            finder.set_query(query);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_content_search_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let ModalState::ContentSearch(search) = &mut app.modal else {
+        return Ok(());
+    };
+
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.close_modal(),
+        KeyCode::Enter if search.searching => {}
+        KeyCode::Enter if !search.is_current() => {
+            if let Some(task) = app.content_search_task() {
+                task_tx
+                    .send(task)
+                    .map_err(|err| anyhow::anyhow!("failed to dispatch content search: {err}"))?;
+            }
+        }
+        KeyCode::Enter => {
+            let Some(hit) = search.selected_match().cloned() else {
+                return Ok(());
+            };
+            let (scope, query) = (search.scope, search.query.clone());
+            app.close_modal();
+            match app.open_search_match(&hit, scope, &query) {
+                Some(task) => send_task(app, task_tx, task)?,
+                None => app.log(format!(
+                    "{} is no longer listed in {}",
+                    hit.path.display(),
+                    ListView::Managed.title()
+                )),
+            }
+        }
+        KeyCode::Tab => search.scope = search.scope.next(),
+        KeyCode::Down => search.move_selection(true),
+        KeyCode::Up => search.move_selection(false),
+        KeyCode::Char('n') if ctrl => search.move_selection(true),
+        KeyCode::Char('p') if ctrl => search.move_selection(false),
+        KeyCode::Backspace => {
+            search.query.pop();
+        }
+        KeyCode::Char(c)
+            if !ctrl
+                && !key.modifiers.contains(KeyModifiers::ALT)
+                && !key.modifiers.contains(KeyModifiers::SUPER) =>
+        {
+            search.query.push(c);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Starts a refresh queued by the filesystem watcher once no task is
+/// running and no dialog is open, so lists never change under a dialog.
+pub(crate) fn run_queued_refresh(
+    app: &mut App,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    if !app.refresh_pending() || app.busy || !matches!(app.modal, ModalState::None) {
+        return Ok(());
+    }
+    let targets = std::mem::take(&mut app.queued_status_targets);
+    if std::mem::take(&mut app.refresh_queued) {
+        return send_task(app, task_tx, BackendTask::RefreshAll);
+    }
+    send_task(
+        app,
+        task_tx,
+        BackendTask::RefreshStatusPaths {
+            targets: targets.into_iter().collect(),
+        },
+    )
+}
+
+/// Coming back from another window re-reads `chezmoi status` so the lists
+/// reflect edits made elsewhere; skipped while a dialog or task is active.
+pub(crate) fn handle_focus_gained(
+    app: &mut App,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    if app.busy || !matches!(app.modal, ModalState::None) {
+        return Ok(());
+    }
+    send_task(app, task_tx, BackendTask::RefreshStatus)
+}
+
+/// Inserts bracketed-paste text into the open filter, search or input
+/// field as one edit. Line breaks become spaces. Confirmation phrases are
+/// deliberately left out: they must be typed.
+pub(crate) fn handle_paste(app: &mut App, text: &str) {
+    let text = text
+        .trim_end_matches(['\r', '\n'])
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ");
+    match &mut app.modal {
+        ModalState::ListFilter { value, .. } => {
+            value.push_str(&text);
+            let filter = value.clone();
+            app.apply_list_filter_immediately(filter);
+        }
+        ModalState::DetailSearch { value, .. } => {
+            value.push_str(&text);
+            let query = value.clone();
+            app.set_detail_search(query);
+        }
+        ModalState::Finder(finder) => {
+            let query = format!("{}{text}", finder.query);
+            finder.set_query(query);
+        }
+        ModalState::ContentSearch(search) => search.query.push_str(&text),
+        ModalState::ActionMenu { selected, filter } => {
+            filter.push_str(&text);
+            *selected = 0;
+        }
+        ModalState::Input { value, .. } => value.push_str(&text),
+        _ => {}
+    }
+}
+
 fn handle_list_filter_key(
     app: &mut App,
     key: KeyEvent,
@@ -330,11 +879,18 @@ fn handle_ignore_key(
     task_tx: &UnboundedSender<BackendTask>,
 ) -> Result<()> {
     let mut start_requests: Option<Vec<ActionRequest>> = None;
+    let staging: bool;
 
     {
-        let ModalState::Ignore { requests, selected } = &mut app.modal else {
+        let ModalState::Ignore {
+            requests,
+            selected,
+            stage,
+        } = &mut app.modal
+        else {
             return Ok(());
         };
+        staging = *stage;
 
         match key.code {
             KeyCode::Esc => {
@@ -364,11 +920,15 @@ fn handle_ignore_key(
     }
 
     if let Some(requests) = start_requests {
+        app.close_modal();
+        if staging {
+            stage_requests(app, Action::Ignore, requests);
+            return Ok(());
+        }
         let count = requests.len();
         if count > 1 {
             app.log(format!("batch queued: action=ignore targets={count}"));
         }
-        app.close_modal();
         if let Some(first) = app.start_batch(requests) {
             dispatch_action_request(app, task_tx, first)?;
         }
@@ -383,6 +943,7 @@ fn handle_action_menu_key(
     task_tx: &UnboundedSender<BackendTask>,
 ) -> Result<()> {
     let mut selected_action: Option<Action> = None;
+    let mut stage = false;
     let mut no_action_match = false;
 
     let (selected_index, filter_text) = match &app.modal {
@@ -428,10 +989,11 @@ fn handle_action_menu_key(
                 *selected = 0;
             }
         }
-        KeyCode::Enter => {
+        KeyCode::Enter | KeyCode::Tab => {
             let indices = app.action_menu_indices(&filter_text);
             if let Some(index) = indices.get(selected_index).copied() {
                 selected_action = App::action_by_index(index);
+                stage = key.code == KeyCode::Tab;
             } else {
                 no_action_match = true;
             }
@@ -459,7 +1021,12 @@ fn handle_action_menu_key(
         }
         if action == Action::Ignore {
             app.close_modal();
-            app.open_ignore_menu(requests);
+            app.open_ignore_menu(requests, stage);
+            return Ok(());
+        }
+        if stage {
+            app.close_modal();
+            stage_requests(app, action, requests);
             return Ok(());
         }
 
@@ -496,6 +1063,7 @@ fn handle_confirm_key(
             request,
             step,
             typed,
+            protected_by,
         } = &mut app.modal
         else {
             return Ok(());
@@ -513,6 +1081,7 @@ fn handle_confirm_key(
             KeyCode::Enter => match step {
                 ConfirmStep::Primary => {
                     if request.requires_strict_confirmation()
+                        || protected_by.is_some()
                         || (request.action.is_dangerous()
                             && app.config.require_two_step_confirmation)
                     {
@@ -522,7 +1091,7 @@ fn handle_confirm_key(
                     }
                 }
                 ConfirmStep::DangerPhrase => {
-                    if let Some(phrase) = request.confirmation_phrase() {
+                    if let Some(phrase) = request.required_phrase(protected_by.is_some()) {
                         if typed.as_str() == phrase {
                             execute_request = Some(request.clone());
                         } else {
@@ -559,23 +1128,467 @@ fn handle_confirm_key(
     Ok(())
 }
 
-fn handle_input_key(
+fn stage_requests(app: &mut App, action: Action, requests: Vec<ActionRequest>) {
+    let added = app.stage_requests(requests);
+    app.log(format!(
+        "queued {added} item(s): action={} (queue: {})",
+        action.label(),
+        app.action_queue.len()
+    ));
+}
+
+fn request_hunk_write(app: &mut App, direction: HunkDirection) {
+    if app.config.read_only {
+        app.log(format!("read-only mode: {} is disabled", direction.label()));
+        return;
+    }
+    if let Err(message) = app.open_hunk_confirm(direction) {
+        app.log(message);
+        return;
+    }
+    if app.config.protected_path_mode == ProtectedPathMode::Refuse
+        && let ModalState::HunkConfirm {
+            target,
+            protected_by: Some(pattern),
+            ..
+        } = &app.modal
+    {
+        app.log(format!(
+            "refused {}: {} is protected by {pattern}",
+            direction.label(),
+            target.display()
+        ));
+        app.close_modal();
+    }
+}
+
+fn handle_hunk_confirm_key(
     app: &mut App,
     key: KeyEvent,
     task_tx: &UnboundedSender<BackendTask>,
 ) -> Result<()> {
-    let mut ready_request: Option<ActionRequest> = None;
+    let ModalState::HunkConfirm {
+        target,
+        hunk,
+        direction,
+        protected_by,
+        typed,
+        ..
+    } = &mut app.modal
+    else {
+        return Ok(());
+    };
+    let protected = protected_by.is_some();
 
-    {
-        let ModalState::Input {
-            kind,
-            request,
-            value,
-        } = &mut app.modal
-        else {
+    let confirmed = match key.code {
+        KeyCode::Esc => false,
+        KeyCode::Char('n') | KeyCode::Char('q') if !protected => false,
+        KeyCode::Enter | KeyCode::Char('y')
+            if !protected || *typed == direction.confirmation_phrase(target) =>
+        {
+            true
+        }
+        KeyCode::Backspace if protected => {
+            typed.pop();
             return Ok(());
-        };
-
+        }
+        KeyCode::Char(ch) if protected => {
+            typed.push(ch);
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+
+    let task = BackendTask::WriteHunk {
+        target: target.clone(),
+        hunk: hunk.clone(),
+        direction: *direction,
+    };
+    let label = direction.label();
+    app.close_modal();
+    if confirmed {
+        send_task(app, task_tx, task)?;
+    } else {
+        app.log(format!("{label} cancelled"));
+    }
+    Ok(())
+}
+
+fn open_builtin_merge(app: &mut App, task_tx: &UnboundedSender<BackendTask>) -> Result<()> {
+    let Some(target) = app.selected_absolute_path() else {
+        app.log("merge requires a target path".to_string());
+        return Ok(());
+    };
+    if !app.selected_is_managed() || app.selected_is_directory() {
+        app.log("merge is available only for managed files".to_string());
+        return Ok(());
+    }
+    if app.config.protected_path_mode == ProtectedPathMode::Refuse
+        && let Some(pattern) = app.protected_pattern_for(&target)
+    {
+        app.log(format!(
+            "refused merge: {} is protected by {pattern}",
+            target.display()
+        ));
+        return Ok(());
+    }
+    send_task(app, task_tx, BackendTask::LoadMerge { target })
+}
+
+fn handle_merge_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let read_only = app.config.read_only;
+    let ModalState::Merge(view) = &mut app.modal else {
+        return Ok(());
+    };
+    view.message = None;
+
+    let phrase = view.confirmation_phrase();
+    if let Some(typed) = &mut view.confirm {
+        match key.code {
+            KeyCode::Esc => view.confirm = None,
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            KeyCode::Char(ch) => typed.push(ch),
+            KeyCode::Enter if *typed == phrase => {
+                if let Some(content) = view.result() {
+                    let task = BackendTask::SaveMerge {
+                        target: view.target.clone(),
+                        source: view.source.clone(),
+                        content,
+                    };
+                    app.close_modal();
+                    send_task(app, task_tx, task)?;
+                }
+            }
+            KeyCode::Enter => {
+                view.message = Some("confirmation phrase does not match".to_string());
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            let target = view.target.clone();
+            app.close_modal();
+            app.log(format!("merge {} cancelled", target.display()));
+        }
+        KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('n') => view.move_selection(true),
+        KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('N') => view.move_selection(false),
+        KeyCode::Char('o') => view.resolve(Resolution::Ours),
+        KeyCode::Char('t') => view.resolve(Resolution::Theirs),
+        KeyCode::Char('b') => view.resolve(Resolution::Both),
+        KeyCode::Char('w') => {
+            if read_only {
+                view.message = Some("read-only mode: saving is disabled".to_string());
+                return Ok(());
+            }
+            let Some(content) = view.result() else {
+                view.message = Some(format!(
+                    "{} block(s) still unresolved",
+                    view.unresolved_count()
+                ));
+                return Ok(());
+            };
+            if view.protected_by.is_some() {
+                view.confirm = Some(String::new());
+                return Ok(());
+            }
+            let task = BackendTask::SaveMerge {
+                target: view.target.clone(),
+                source: view.source.clone(),
+                content,
+            };
+            app.close_modal();
+            send_task(app, task_tx, task)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_repo_diff_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let ModalState::RepoDiff(browser) = &mut app.modal else {
+        return Ok(());
+    };
+    let mut mark: Option<PathBuf> = None;
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('D') => {
+            app.close_modal();
+            if app.marked_count() > 0 {
+                app.log(format!(
+                    "diff browser: {} file(s) marked for batch actions",
+                    app.marked_count()
+                ));
+            }
+            return Ok(());
+        }
+        KeyCode::Down | KeyCode::Char('j') => browser.move_selection(true),
+        KeyCode::Up | KeyCode::Char('k') => browser.move_selection(false),
+        KeyCode::Home | KeyCode::Char('g') => browser.jump_to(0),
+        KeyCode::End | KeyCode::Char('G') => {
+            browser.jump_to(browser.files.len().saturating_sub(1));
+        }
+        KeyCode::Enter | KeyCode::Char('l') | KeyCode::Char('h') => browser.toggle_collapsed(),
+        KeyCode::Char('z') => browser.toggle_all_collapsed(),
+        KeyCode::PageDown => browser.scroll_by(20),
+        KeyCode::PageUp => browser.scroll_by(-20),
+        KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => browser.scroll_by(20),
+        KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => browser.scroll_by(-20),
+        KeyCode::Char('J') => browser.scroll_by(1),
+        KeyCode::Char('K') => browser.scroll_by(-1),
+        KeyCode::Char(' ') => {
+            mark = browser.selected_file().map(|file| file.path.clone());
+            browser.move_selection(true);
+        }
+        _ => {}
+    }
+
+    if let Some(path) = mark {
+        app.toggle_mark(path);
+    }
+    Ok(())
+}
+
+fn handle_queue_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let ModalState::Queue { selected } = app.modal else {
+        return Ok(());
+    };
+    if app.batch_in_progress() {
+        // While the plan runs the panel only shows progress.
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q' | 'p')) {
+            app.close_modal();
+        }
+        return Ok(());
+    }
+
+    let len = app.action_queue.len();
+    let mut next_selected = selected;
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q' | 'p') => {
+            app.close_modal();
+            return Ok(());
+        }
+        KeyCode::Down | KeyCode::Char('j') if len > 0 => next_selected = (selected + 1) % len,
+        KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+            next_selected = selected.checked_sub(1).unwrap_or(len - 1);
+        }
+        KeyCode::Char('J') => next_selected = app.move_queue_item(selected, false),
+        KeyCode::Char('K') => next_selected = app.move_queue_item(selected, true),
+        KeyCode::Char('x') | KeyCode::Delete => {
+            if let Some(removed) = app.remove_queue_item(selected) {
+                app.log(format!("dequeued: {}", request_summary(&removed)));
+            }
+            next_selected = selected.min(app.action_queue.len().saturating_sub(1));
+        }
+        KeyCode::Char('s') => save_queue(app),
+        KeyCode::Char('o') => {
+            load_queue(app);
+            next_selected = 0;
+        }
+        KeyCode::Enter => {
+            if app.action_queue.is_empty() {
+                app.log("action queue is empty".to_string());
+                return Ok(());
+            }
+            if let Some(message) = validate_queue(app, &app.action_queue) {
+                app.log(format!("queue not started: {message}"));
+                return Ok(());
+            }
+            let plan = std::mem::take(&mut app.action_queue);
+            app.log(format!("queue started: items={}", plan.len()));
+            app.close_modal();
+            if let Some(first) = app.start_queue_batch(plan) {
+                dispatch_action_request(app, task_tx, first)?;
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if let ModalState::Queue { selected } = &mut app.modal {
+        *selected = next_selected;
+    }
+    Ok(())
+}
+
+fn save_queue(app: &mut App) {
+    let Some(path) = app.plan_path() else {
+        app.log("plan save failed: no plan path available".to_string());
+        return;
+    };
+    match save_plan(&path, &app.action_queue, &app.home_dir) {
+        Ok(()) => app.log(format!(
+            "plan saved: {} ({} item(s))",
+            path.display(),
+            app.action_queue.len()
+        )),
+        Err(err) => app.log(format!("plan save failed: {err:#}")),
+    }
+}
+
+fn load_queue(app: &mut App) {
+    let Some(path) = app.plan_path() else {
+        app.log("plan load failed: no plan path available".to_string());
+        return;
+    };
+    match load_plan(&path, &app.home_dir) {
+        Ok(plan) => {
+            app.log(format!(
+                "plan loaded: {} ({} item(s))",
+                path.display(),
+                plan.len()
+            ));
+            app.action_queue = plan;
+        }
+        Err(err) => app.log(format!("plan load failed: {err:#}")),
+    }
+}
+
+fn request_summary(request: &ActionRequest) -> String {
+    let target = request
+        .target
+        .as_ref()
+        .map_or_else(|| "(none)".to_string(), |p| p.display().to_string());
+    format!("{} {target}", request.action.label())
+}
+
+fn handle_batch_results_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let mut retry: Option<(Vec<ActionRequest>, bool)> = None;
+
+    {
+        let ModalState::BatchResults { summary, selected } = &mut app.modal else {
+            return Ok(());
+        };
+        let failed = summary.failures().len();
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                app.close_modal();
+                return Ok(());
+            }
+            KeyCode::Down | KeyCode::Char('j') if failed > 0 => {
+                *selected = (*selected + 1) % failed;
+            }
+            KeyCode::Up | KeyCode::Char('k') if failed > 0 => {
+                *selected = selected.checked_sub(1).unwrap_or(failed - 1);
+            }
+            KeyCode::Char('r') => retry = Some((summary.retry_requests(), summary.from_queue)),
+            _ => {}
+        }
+    }
+
+    if let Some((requests, from_queue)) = retry {
+        app.close_modal();
+        let count = requests.len();
+        if let Some(action) = requests.first().map(|request| request.action) {
+            app.log(format!(
+                "batch retry queued: action={} targets={count}",
+                action.label()
+            ));
+        }
+        let first = if from_queue {
+            app.start_queue_batch(requests)
+        } else {
+            app.start_batch(requests)
+        };
+        if let Some(first) = first {
+            dispatch_action_request(app, task_tx, first)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_chattr_picker_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let mut ready_request: Option<ActionRequest> = None;
+
+    {
+        let ModalState::ChattrPicker(picker) = &mut app.modal else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                if app.batch_in_progress() {
+                    app.clear_batch();
+                    app.log("batch canceled".to_string());
+                }
+                app.close_modal();
+                return Ok(());
+            }
+            KeyCode::Down | KeyCode::Char('j') => picker.move_selection(true),
+            KeyCode::Up | KeyCode::Char('k') => picker.move_selection(false),
+            KeyCode::Char(' ') => picker.toggle_selected(),
+            KeyCode::Char('i') => {
+                // Fall back to free-text modifiers, prefilled with the current picks.
+                let value = picker.attribute_string();
+                let request = picker.request.clone();
+                app.open_input(InputKind::ChattrAttrs, request);
+                if let ModalState::Input { value: input, .. } = &mut app.modal {
+                    *input = value;
+                }
+                return Ok(());
+            }
+            KeyCode::Enter => match picker.validate() {
+                Ok(attrs) => {
+                    let mut request = picker.request.clone();
+                    request.chattr_attrs = Some(attrs);
+                    ready_request = Some(request);
+                }
+                Err(message) => picker.message = Some(message),
+            },
+            _ => {}
+        }
+    }
+
+    if let Some(request) = ready_request {
+        if let Some(attrs) = request.chattr_attrs.clone() {
+            app.apply_chattr_attrs_to_batch(&attrs);
+        }
+        app.close_modal();
+        dispatch_action_request(app, task_tx, request)?;
+    }
+
+    Ok(())
+}
+
+fn handle_input_key(
+    app: &mut App,
+    key: KeyEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let mut ready_request: Option<ActionRequest> = None;
+
+    {
+        let ModalState::Input {
+            kind,
+            request,
+            value,
+        } = &mut app.modal
+        else {
+            return Ok(());
+        };
+
         match key.code {
             KeyCode::Esc => {
                 if app.batch_in_progress() {
@@ -620,6 +1633,7 @@ fn handle_input_key(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::binary::HexPage;
     use crate::config::AppConfig;
     use std::path::PathBuf;
     use tokio::sync::mpsc;
@@ -746,6 +1760,7 @@ mod tests {
             },
             step: ConfirmStep::Primary,
             typed: String::new(),
+            protected_by: None,
         };
         let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
 
@@ -776,6 +1791,7 @@ mod tests {
             },
             step: ConfirmStep::DangerPhrase,
             typed: "DESTROY".to_string(),
+            protected_by: None,
         };
         let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
 
@@ -801,6 +1817,7 @@ mod tests {
             },
             step: ConfirmStep::DangerPhrase,
             typed: "DESTROY /tmp/target.txt".to_string(),
+            protected_by: None,
         };
         let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
 
@@ -823,4 +1840,1111 @@ mod tests {
             }
         ));
     }
+
+    #[test]
+    fn protected_target_requires_typed_phrase_for_forget() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/protected-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let request = ActionRequest {
+            action: Action::Forget,
+            target: Some(PathBuf::from("/tmp/protected-home/.ssh/config")),
+            chattr_attrs: None,
+        };
+
+        dispatch_action_request(&mut app, &task_tx, request).expect("dispatch");
+        assert!(matches!(
+            &app.modal,
+            ModalState::Confirm { protected_by: Some(pattern), .. } if pattern == ".ssh/**"
+        ));
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        handle_confirm_key(&mut app, enter, &task_tx).expect("confirm");
+        assert!(matches!(
+            app.modal,
+            ModalState::Confirm {
+                step: ConfirmStep::DangerPhrase,
+                ..
+            }
+        ));
+        handle_confirm_key(&mut app, enter, &task_tx).expect("confirm");
+        assert!(task_rx.try_recv().is_err());
+
+        if let ModalState::Confirm { typed, .. } = &mut app.modal {
+            *typed = "FORGET /tmp/protected-home/.ssh/config".to_string();
+        }
+        handle_confirm_key(&mut app, enter, &task_tx).expect("confirm");
+        assert!(matches!(
+            task_rx.try_recv().expect("task dispatched"),
+            BackendTask::RunAction { .. }
+        ));
+    }
+
+    #[test]
+    fn protected_target_is_refused_in_refuse_mode() {
+        let mut app = App::new(AppConfig {
+            protected_path_mode: crate::config::ProtectedPathMode::Refuse,
+            ..AppConfig::default()
+        });
+        app.home_dir = PathBuf::from("/tmp/protected-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let request = ActionRequest {
+            action: Action::Add,
+            target: Some(PathBuf::from("/tmp/protected-home/.gnupg/gpg.conf")),
+            chattr_attrs: None,
+        };
+
+        dispatch_action_request(&mut app, &task_tx, request).expect("dispatch");
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(task_rx.try_recv().is_err());
+        assert!(app.logs.iter().any(|line| line.contains("refused add")));
+    }
+
+    #[test]
+    fn untargeted_apply_is_guarded_when_any_managed_path_is_protected() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let apply_all = ActionRequest {
+            action: Action::Apply,
+            target: None,
+            chattr_attrs: None,
+        };
+        app.managed_entries = vec![PathBuf::from(".zshrc")];
+        assert_eq!(app.protected_pattern_for_request(&apply_all), None);
+
+        app.managed_entries.push(PathBuf::from(".ssh/config"));
+        dispatch_action_request(&mut app, &task_tx, apply_all.clone()).expect("dispatch");
+        assert!(matches!(
+            &app.modal,
+            ModalState::Confirm { protected_by: Some(pattern), .. } if pattern == ".ssh/**"
+        ));
+        assert!(task_rx.try_recv().is_err());
+
+        app.close_modal();
+        app.config.protected_path_mode = crate::config::ProtectedPathMode::Refuse;
+        dispatch_action_request(&mut app, &task_tx, apply_all).expect("dispatch");
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(task_rx.try_recv().is_err());
+        assert!(
+            app.logs
+                .iter()
+                .any(|line| line.contains("refused apply: managed paths are protected"))
+        );
+    }
+
+    fn forget_requests(names: &[&str]) -> Vec<ActionRequest> {
+        names
+            .iter()
+            .map(|name| ActionRequest {
+                action: Action::Forget,
+                target: Some(PathBuf::from(format!("/tmp/batch-home/{name}"))),
+                chattr_attrs: None,
+            })
+            .collect()
+    }
+
+    fn finish_running_action(
+        app: &mut App,
+        task_tx: &UnboundedSender<BackendTask>,
+        task_rx: &mut mpsc::UnboundedReceiver<BackendTask>,
+        exit_code: i32,
+    ) {
+        let Ok(BackendTask::RunAction { request }) = task_rx.try_recv() else {
+            panic!("expected a queued RunAction task");
+        };
+        let result = crate::domain::CommandResult {
+            exit_code,
+            stdout: String::new(),
+            stderr: if exit_code == 0 {
+                String::new()
+            } else {
+                "permission denied".to_string()
+            },
+            duration_ms: 1,
+        };
+        handle_backend_event(
+            app,
+            task_tx,
+            BackendEvent::ActionFinished { request, result },
+        )
+        .expect("handle event");
+    }
+
+    #[test]
+    fn batch_continue_policy_reports_failures_and_retries_them() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let first = app
+            .start_batch(forget_requests(&["a", "b", "c"]))
+            .expect("first request");
+        dispatch_action_request(&mut app, &task_tx, first).expect("dispatch");
+
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 0);
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 1);
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 0);
+
+        assert!(!app.batch_in_progress());
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::RefreshAll)));
+        let ModalState::BatchResults { summary, .. } = &app.modal else {
+            panic!("expected batch results modal");
+        };
+        assert_eq!(summary.passed(), 2);
+        assert_eq!(summary.failures().len(), 1);
+        assert_eq!(summary.failures()[0].detail, "permission denied");
+        assert!(
+            app.logs
+                .iter()
+                .any(|line| line.contains("ok=2 failed=1 skipped=0"))
+        );
+
+        let key = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        handle_key_event(&mut app, key, &task_tx).expect("retry");
+        assert!(matches!(app.modal, ModalState::None));
+        let Ok(BackendTask::RunAction { request }) = task_rx.try_recv() else {
+            panic!("expected retried action");
+        };
+        assert_eq!(request.target, Some(PathBuf::from("/tmp/batch-home/b")));
+    }
+
+    #[test]
+    fn action_queue_runs_mixed_actions_with_per_item_progress() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let mut plan = forget_requests(&["a"]);
+        plan.push(ActionRequest {
+            action: Action::Chattr,
+            target: Some(PathBuf::from("/tmp/batch-home/b")),
+            chattr_attrs: Some("+private".to_string()),
+        });
+        assert_eq!(app.stage_requests(plan.clone()), 2);
+        assert_eq!(app.stage_requests(plan), 0);
+
+        app.open_queue();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        handle_key_event(&mut app, enter, &task_tx).expect("run queue");
+        assert!(app.action_queue.is_empty());
+        let states: Vec<_> = app
+            .batch_progress_items()
+            .into_iter()
+            .map(|(_, state)| state)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                crate::app::BatchItemState::Running,
+                crate::app::BatchItemState::Pending
+            ]
+        );
+
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 0);
+        let states: Vec<_> = app
+            .batch_progress_items()
+            .into_iter()
+            .map(|(request, state)| (request.action, state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (Action::Forget, crate::app::BatchItemState::Passed),
+                (Action::Chattr, crate::app::BatchItemState::Running),
+            ]
+        );
+
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 0);
+        assert!(!app.batch_in_progress());
+        assert!(
+            app.logs
+                .iter()
+                .any(|line| line.contains("action=mixed total=2 ok=2"))
+        );
+    }
+
+    #[test]
+    fn loaded_queue_is_validated_before_it_runs() {
+        let home = std::env::temp_dir().join(format!(
+            "chezmoi_tui_queue_validate_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_dir_all(&home);
+        std::fs::create_dir_all(home.join(".config")).expect("create dir");
+        std::fs::write(home.join(".zshrc"), "alias").expect("write");
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = home.clone();
+        app.status_entries = vec![crate::domain::StatusEntry {
+            path: PathBuf::from(".zshrc"),
+            actual_vs_state: crate::domain::ChangeKind::None,
+            actual_vs_target: crate::domain::ChangeKind::Modified,
+        }];
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let request = |action, name: &str| ActionRequest {
+            action,
+            target: Some(home.join(name)),
+            chattr_attrs: None,
+        };
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        for invalid in [
+            request(Action::Add, ".config"),
+            request(Action::ReAdd, ".config"),
+        ] {
+            app.action_queue = vec![request(Action::ReAdd, ".zshrc"), invalid];
+            app.open_queue();
+            handle_key_event(&mut app, enter, &task_tx).expect("refuse queue");
+            assert_eq!(app.action_queue.len(), 2);
+            assert!(!app.batch_in_progress());
+            assert!(task_rx.try_recv().is_err());
+            assert!(
+                app.logs
+                    .last()
+                    .is_some_and(|line| line.starts_with("queue not started:"))
+            );
+        }
+
+        app.action_queue = vec![request(Action::ReAdd, ".zshrc")];
+        handle_key_event(&mut app, enter, &task_tx).expect("run queue");
+        assert!(app.action_queue.is_empty());
+        let _ = std::fs::remove_dir_all(home);
+    }
+
+    #[test]
+    fn queued_chattr_items_each_get_their_own_attributes() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let chattr = |name: &str| ActionRequest {
+            action: Action::Chattr,
+            target: Some(PathBuf::from(format!("/tmp/batch-home/{name}"))),
+            chattr_attrs: None,
+        };
+
+        // One selection shares the picked attributes...
+        let first = app
+            .start_batch(vec![chattr("a"), chattr("b")])
+            .expect("first");
+        assert_eq!(app.chattr_targets_needing_attrs(&first).len(), 2);
+        app.clear_batch();
+
+        // ...separately staged items do not.
+        app.stage_requests(vec![chattr("a")]);
+        app.stage_requests(vec![chattr("b")]);
+        app.open_queue();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        handle_key_event(&mut app, enter, &task_tx).expect("run queue");
+        let ModalState::ChattrPicker(picker) = &app.modal else {
+            panic!("expected chattr picker");
+        };
+        assert_eq!(picker.targets, vec![PathBuf::from("/tmp/batch-home/a")]);
+        app.apply_chattr_attrs_to_batch("+private");
+        assert_eq!(
+            app.batch_progress_items()
+                .iter()
+                .filter(|(request, _)| request.chattr_attrs.is_some())
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn queue_panel_reorders_and_drops_items() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        app.stage_requests(forget_requests(&["a", "b", "c"]));
+        app.open_queue();
+
+        for code in [KeyCode::Char('J'), KeyCode::Char('J'), KeyCode::Char('x')] {
+            handle_key_event(&mut app, KeyEvent::new(code, KeyModifiers::NONE), &task_tx)
+                .expect("queue key");
+        }
+
+        let targets: Vec<_> = app
+            .action_queue
+            .iter()
+            .filter_map(|request| request.target.clone())
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                PathBuf::from("/tmp/batch-home/b"),
+                PathBuf::from("/tmp/batch-home/c"),
+            ]
+        );
+        assert!(matches!(app.modal, ModalState::Queue { selected: 1 }));
+    }
+
+    #[test]
+    fn chattr_opens_attribute_picker_and_runs_with_built_modifiers() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let request = ActionRequest {
+            action: Action::Chattr,
+            target: Some(PathBuf::from("/tmp/batch-home/.zshrc")),
+            chattr_attrs: None,
+        };
+        dispatch_action_request(&mut app, &task_tx, request).expect("dispatch");
+
+        let Ok(BackendTask::LoadSourceInfo { targets }) = task_rx.try_recv() else {
+            panic!("expected source info request");
+        };
+        let sources = vec![crate::chattr::source_info(
+            std::path::Path::new("/src/private_dot_zshrc"),
+            false,
+        )];
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::SourceInfoLoaded { targets, sources },
+        )
+        .expect("loaded");
+
+        // `private` is the fifth attribute and currently set, so one toggle clears it.
+        for code in [
+            KeyCode::Char('j'),
+            KeyCode::Char('j'),
+            KeyCode::Char('j'),
+            KeyCode::Char('j'),
+            KeyCode::Char(' '),
+            KeyCode::Enter,
+        ] {
+            handle_key_event(&mut app, KeyEvent::new(code, KeyModifiers::NONE), &task_tx)
+                .expect("picker key");
+        }
+
+        let Ok(BackendTask::RunAction { request }) = task_rx.try_recv() else {
+            panic!("expected chattr run");
+        };
+        assert_eq!(request.chattr_attrs.as_deref(), Some("-private"));
+    }
+
+    #[test]
+    fn batch_stop_policy_skips_remaining_items_after_failure() {
+        let mut app = App::new(AppConfig {
+            batch_error_policy: crate::config::BatchErrorPolicy::Stop,
+            ..AppConfig::default()
+        });
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let first = app
+            .start_batch(forget_requests(&["a", "b", "c"]))
+            .expect("first request");
+        dispatch_action_request(&mut app, &task_tx, first).expect("dispatch");
+
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 1);
+
+        assert!(!app.batch_in_progress());
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::RefreshAll)));
+        let ModalState::BatchResults { summary, .. } = &app.modal else {
+            panic!("expected batch results modal");
+        };
+        assert_eq!(summary.failures().len(), 1);
+        assert_eq!(summary.skipped.len(), 2);
+        assert_eq!(summary.retry_requests().len(), 3);
+    }
+
+    #[test]
+    fn retrying_a_stopped_queue_keeps_chattr_attributes_per_item() {
+        let mut app = App::new(AppConfig {
+            batch_error_policy: crate::config::BatchErrorPolicy::Stop,
+            ..AppConfig::default()
+        });
+        app.home_dir = PathBuf::from("/tmp/batch-home");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let chattr = |name: &str| ActionRequest {
+            action: Action::Chattr,
+            target: Some(PathBuf::from(format!("/tmp/batch-home/{name}"))),
+            chattr_attrs: None,
+        };
+        let mut plan = forget_requests(&["a"]);
+        plan.extend([chattr("b"), chattr("c")]);
+        app.action_queue = plan;
+        app.open_queue();
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        handle_key_event(&mut app, enter, &task_tx).expect("run queue");
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 1);
+        while task_rx.try_recv().is_ok() {}
+        let ModalState::BatchResults { summary, .. } = &app.modal else {
+            panic!("expected batch results modal");
+        };
+        assert!(summary.from_queue);
+        assert_eq!(summary.skipped.len(), 2);
+
+        let retry = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        handle_key_event(&mut app, retry, &task_tx).expect("retry");
+        finish_running_action(&mut app, &task_tx, &mut task_rx, 0);
+        let ModalState::ChattrPicker(picker) = &app.modal else {
+            panic!("expected chattr picker");
+        };
+        assert_eq!(picker.targets, vec![PathBuf::from("/tmp/batch-home/b")]);
+        app.apply_chattr_attrs_to_batch("+private");
+        assert!(
+            app.batch_progress_items()
+                .iter()
+                .all(|(request, _)| request.chattr_attrs.is_none())
+        );
+    }
+
+    #[test]
+    fn actions_that_fail_to_run_are_audited() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_failed_action_audit_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut app = App::new(AppConfig {
+            audit_log_path: Some(path.clone()),
+            ..AppConfig::default()
+        });
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let request = forget_requests(&["a"]).remove(0);
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::ActionFailed {
+                request,
+                message: "failed to spawn chezmoi".to_string(),
+            },
+        )
+        .expect("handle failure");
+
+        let sessions = crate::audit::load_audit_sessions(&path).expect("load audit");
+        let record = &sessions[0].records[0];
+        assert_eq!(record.action, "forget");
+        assert_eq!(record.exit_code, CommandResult::NOT_RUN_EXIT_CODE);
+        assert_eq!(record.stderr, "failed to spawn chezmoi");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn hunk_writes_are_audited_with_their_outcome() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_hunk_audit_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut app = App::new(AppConfig {
+            audit_log_path: Some(path.clone()),
+            ..AppConfig::default()
+        });
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let written = PathBuf::from("/home/u/.local/share/chezmoi/private_dot_ssh/config");
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::HunkWritten {
+                target: PathBuf::from("/home/u/.ssh/config"),
+                direction: HunkDirection::ToSource,
+                written: written.clone(),
+            },
+        )
+        .expect("written");
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::HunkFailed {
+                direction: HunkDirection::ToSource,
+                written: written.clone(),
+                message: "re-add-hunk failed: hunk does not match".to_string(),
+            },
+        )
+        .expect("failed");
+
+        let sessions = crate::audit::load_audit_sessions(&path).expect("load audit");
+        let outcomes: Vec<(&str, &str, i32, &str)> = sessions[0]
+            .records
+            .iter()
+            .map(|record| {
+                (
+                    record.action.as_str(),
+                    record.destination.as_str(),
+                    record.exit_code,
+                    record.stderr.as_str(),
+                )
+            })
+            .collect();
+        let written = written.display().to_string();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("re-add-hunk", written.as_str(), 0, ""),
+                (
+                    "re-add-hunk",
+                    written.as_str(),
+                    1,
+                    "re-add-hunk failed: hunk does not match"
+                ),
+            ]
+        );
+        assert!(
+            sessions[0].records[0]
+                .summary_line()
+                .contains("re-add-hunk wrote")
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn merge_saves_are_audited_with_their_outcome() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_merge_audit_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut app = App::new(AppConfig {
+            audit_log_path: Some(path.clone()),
+            ..AppConfig::default()
+        });
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let source = PathBuf::from("/home/u/.local/share/chezmoi/dot_zshrc");
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::MergeSaved {
+                target: PathBuf::from("/home/u/.zshrc"),
+                source: source.clone(),
+            },
+        )
+        .expect("saved");
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::MergeSaveFailed {
+                source: source.clone(),
+                message: "merge save failed: permission denied".to_string(),
+            },
+        )
+        .expect("failed");
+
+        let sessions = crate::audit::load_audit_sessions(&path).expect("load audit");
+        let records = &sessions[0].records;
+        assert_eq!(records.len(), 2);
+        assert!(
+            records.iter().all(|record| record.action == "merge"
+                && record.destination == source.display().to_string())
+        );
+        assert_eq!(records[0].exit_code, 0);
+        assert_eq!(records[1].exit_code, 1);
+        assert_eq!(records[1].stderr, "merge save failed: permission denied");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn read_only_mode_refuses_actions_without_sending_tasks() {
+        let mut app = App::new(AppConfig {
+            read_only: true,
+            ..AppConfig::default()
+        });
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        for action in [Action::Apply, Action::Ignore, Action::EditIgnore] {
+            let request = ActionRequest {
+                action,
+                target: Some(PathBuf::from("/tmp/read-only/.zshrc")),
+                chattr_attrs: None,
+            };
+            execute_action_request(&mut app, &task_tx, request).expect("execute");
+        }
+
+        assert!(task_rx.try_recv().is_err());
+        assert!(app.pending_foreground.is_none());
+        assert_eq!(
+            app.logs
+                .iter()
+                .filter(|line| line.contains("read-only mode"))
+                .count(),
+            3
+        );
+    }
+
+    const TWO_HUNK_DIFF: &str = concat!(
+        "diff --git a/.zshrc b/.zshrc\n",
+        "--- a/.zshrc\n",
+        "+++ b/.zshrc\n",
+        "@@ -1,2 +1,2 @@\n",
+        " keep\n",
+        "-old\n",
+        "+new\n",
+        "@@ -10,2 +10,2 @@\n",
+        " ctx\n",
+        "-tail\n",
+        "+TAIL\n",
+    );
+
+    fn press(app: &mut App, task_tx: &UnboundedSender<BackendTask>, code: KeyCode) {
+        handle_key_event(app, KeyEvent::new(code, KeyModifiers::NONE), task_tx).expect("key");
+    }
+
+    #[test]
+    fn layout_keys_resize_zoom_and_hide_the_log_pane() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        press(&mut app, &task_tx, KeyCode::Char('+'));
+        assert_eq!(app.layout.list_percent, 40);
+        press(&mut app, &task_tx, KeyCode::Char('z'));
+        assert!(app.zoomed);
+
+        app.focus = crate::app::PaneFocus::Log;
+        press(&mut app, &task_tx, KeyCode::Char('L'));
+        press(&mut app, &task_tx, KeyCode::Char('L'));
+        assert_eq!(app.layout.log, crate::layout::LogPlacement::Hidden);
+        assert_eq!(app.focus, crate::app::PaneFocus::List);
+        press(&mut app, &task_tx, KeyCode::Tab);
+        press(&mut app, &task_tx, KeyCode::Tab);
+        assert_eq!(app.focus, crate::app::PaneFocus::List);
+    }
+
+    #[test]
+    fn paste_inserts_into_open_fields_as_one_edit() {
+        let mut app = App::new(AppConfig::default());
+        app.open_list_filter();
+        handle_paste(&mut app, "dot_config/nvim\n");
+        assert_eq!(app.list_filter(), "dot_config/nvim");
+
+        app.close_modal();
+        handle_paste(&mut app, "ignored");
+        assert!(matches!(app.modal, ModalState::None));
+
+        app.modal = ModalState::Input {
+            kind: InputKind::ChattrAttrs,
+            request: ActionRequest {
+                action: Action::Chattr,
+                target: None,
+                chattr_attrs: None,
+            },
+            value: "private,".to_string(),
+        };
+        handle_paste(&mut app, "template\r\n");
+        assert!(matches!(
+            &app.modal,
+            ModalState::Input { value, .. } if value == "private,template"
+        ));
+    }
+
+    #[test]
+    fn file_changes_mark_lists_stale_and_refresh_once_idle() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/home/me");
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        app.open_list_filter();
+
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::FilesChanged {
+                paths: vec![PathBuf::from("/home/me/.zshrc")],
+                source_paths: vec![PathBuf::from("/src/x")],
+            },
+        )
+        .expect("files changed");
+        assert!(app.stale);
+        assert!(task_rx.try_recv().is_err());
+        assert!(
+            app.logs
+                .last()
+                .is_some_and(|line| line.ends_with("~/.zshrc (+1 more)"))
+        );
+
+        app.close_modal();
+        run_queued_refresh(&mut app, &task_tx).expect("queued refresh");
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::RefreshAll)));
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::Refreshed {
+                status: Vec::new(),
+                managed: vec![PathBuf::from(".zshrc")],
+                unmanaged: Vec::new(),
+            },
+        )
+        .expect("refreshed");
+        assert!(!app.stale);
+        let set = app.take_watch_set_update().expect("watch set");
+        assert_eq!(set.files, vec![PathBuf::from("/home/me/.zshrc")]);
+        assert!(app.take_watch_set_update().is_none());
+    }
+
+    #[test]
+    fn file_changes_skip_own_writes_and_refresh_managed_files_by_path() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/home/me");
+        app.managed_entries = vec![PathBuf::from(".zshrc"), PathBuf::from(".vimrc")];
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let changed = |paths: &[&str], source: &[&str]| BackendEvent::FilesChanged {
+            paths: paths.iter().map(PathBuf::from).collect(),
+            source_paths: source.iter().map(PathBuf::from).collect(),
+        };
+
+        // The app's own re-add rewrites the source and touches the target.
+        send_task(
+            &mut app,
+            &task_tx,
+            BackendTask::RunAction {
+                request: ActionRequest {
+                    action: Action::ReAdd,
+                    target: Some(PathBuf::from("/home/me/.zshrc")),
+                    chattr_attrs: None,
+                },
+            },
+        )
+        .expect("send");
+        let Ok(BackendTask::RunAction { request }) = task_rx.try_recv() else {
+            panic!("expected the re-add to run");
+        };
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            changed(&["/home/me/.zshrc", "/home/me"], &[]),
+        )
+        .expect("files changed");
+        assert!(!app.stale);
+
+        // Source directory reports are kept, and the refresh after the
+        // re-add is widened to cover them.
+        handle_backend_event(&mut app, &task_tx, changed(&[], &["/src/dot_zshrc"]))
+            .expect("files changed");
+        assert!(app.stale);
+        assert!(task_rx.try_recv().is_err());
+        let result = crate::domain::CommandResult {
+            exit_code: 0,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 1,
+        };
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::ActionFinished { request, result },
+        )
+        .expect("finished");
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::RefreshAll)));
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::Refreshed {
+                status: Vec::new(),
+                managed: vec![PathBuf::from(".zshrc"), PathBuf::from(".vimrc")],
+                unmanaged: Vec::new(),
+            },
+        )
+        .expect("refreshed");
+        assert!(!app.stale);
+        run_queued_refresh(&mut app, &task_tx).expect("queued refresh");
+        assert!(task_rx.try_recv().is_err());
+
+        // An outside edit of another managed file only re-reads its status.
+        handle_backend_event(&mut app, &task_tx, changed(&["/home/me/.vimrc"], &[]))
+            .expect("files changed");
+        assert!(app.stale);
+        let Ok(BackendTask::RefreshStatusPaths { targets }) = task_rx.try_recv() else {
+            panic!("expected a targeted status refresh");
+        };
+        assert_eq!(targets, vec![PathBuf::from("/home/me/.vimrc")]);
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::StatusPathsRefreshed {
+                targets,
+                status: Vec::new(),
+            },
+        )
+        .expect("status refreshed");
+        assert!(!app.stale);
+    }
+
+    #[test]
+    fn focus_gained_refreshes_status_only_when_idle() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        handle_focus_gained(&mut app, &task_tx).expect("focus");
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::RefreshStatus)));
+        assert!(app.busy);
+
+        handle_focus_gained(&mut app, &task_tx).expect("focus");
+        assert!(task_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn brackets_page_through_hex_previews() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/hex-home");
+        app.view = ListView::Managed;
+        app.focus = crate::app::PaneFocus::Detail;
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let page = HexPage {
+            offset: 0,
+            file_size: 5000,
+        };
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::PreviewLoaded {
+                target: PathBuf::from("blob.bin"),
+                content: "hex page 1/2".to_string(),
+                metadata: None,
+                hex: Some(page),
+            },
+        )
+        .expect("preview");
+
+        press(&mut app, &task_tx, KeyCode::Char('['));
+        assert!(task_rx.try_recv().is_err());
+        press(&mut app, &task_tx, KeyCode::Char(']'));
+        let Ok(BackendTask::LoadHexPage {
+            absolute, offset, ..
+        }) = task_rx.try_recv()
+        else {
+            panic!("expected hex page task");
+        };
+        assert_eq!(absolute, PathBuf::from("/tmp/hex-home/blob.bin"));
+        assert_eq!(offset, 4096);
+
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::HexPageLoaded {
+                target: PathBuf::from("blob.bin"),
+                content: "hex page 2/2".to_string(),
+                page: HexPage {
+                    offset: 4096,
+                    ..page
+                },
+            },
+        )
+        .expect("hex page");
+        assert_eq!(app.detail_text, "hex page 2/2");
+        assert_eq!(app.detail_hex.map(|page| page.offset), Some(4096));
+        press(&mut app, &task_tx, KeyCode::Char(']'));
+        assert!(task_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn bracket_h_jumps_between_hunks_and_accepts_current_one() {
+        let mut app = App::new(AppConfig::default());
+        app.home_dir = PathBuf::from("/tmp/hunk-home");
+        app.focus = crate::app::PaneFocus::Detail;
+        app.set_detail_diff(None, TWO_HUNK_DIFF.to_string());
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        press(&mut app, &task_tx, KeyCode::Char(']'));
+        press(&mut app, &task_tx, KeyCode::Char('h'));
+        assert_eq!(app.detail_scroll, 3);
+        press(&mut app, &task_tx, KeyCode::Char(']'));
+        press(&mut app, &task_tx, KeyCode::Char('h'));
+        assert_eq!(app.detail_scroll, 7);
+        assert_eq!(app.current_hunk(), Some((1, 2)));
+        press(&mut app, &task_tx, KeyCode::Char('['));
+        press(&mut app, &task_tx, KeyCode::Char('h'));
+        assert_eq!(app.detail_scroll, 3);
+
+        press(&mut app, &task_tx, KeyCode::Char('R'));
+        assert!(matches!(
+            &app.modal,
+            ModalState::HunkConfirm { target, position: (1, 2), direction: HunkDirection::ToSource, .. }
+                if target == &PathBuf::from("/tmp/hunk-home/.zshrc")
+        ));
+        press(&mut app, &task_tx, KeyCode::Enter);
+        let Ok(BackendTask::WriteHunk {
+            hunk, direction, ..
+        }) = task_rx.try_recv()
+        else {
+            panic!("expected a WriteHunk task");
+        };
+        assert_eq!(direction, HunkDirection::ToSource);
+        assert_eq!(hunk.new, vec!["keep", "new"]);
+    }
+
+    #[test]
+    fn hunk_writes_respect_read_only_and_protected_paths() {
+        let mut app = App::new(AppConfig {
+            read_only: true,
+            ..AppConfig::default()
+        });
+        app.focus = crate::app::PaneFocus::Detail;
+        app.set_detail_diff(None, TWO_HUNK_DIFF.replace(".zshrc", ".ssh/config"));
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        press(&mut app, &task_tx, KeyCode::Char('A'));
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(app.logs.iter().any(|line| line.contains("read-only mode")));
+
+        app.config.read_only = false;
+        press(&mut app, &task_tx, KeyCode::Char('A'));
+        press(&mut app, &task_tx, KeyCode::Enter);
+        assert!(task_rx.try_recv().is_err());
+        let phrase = format!("APPLY-HUNK {}", app.home_dir.join(".ssh/config").display());
+        for ch in phrase.chars() {
+            press(&mut app, &task_tx, KeyCode::Char(ch));
+        }
+        press(&mut app, &task_tx, KeyCode::Enter);
+        assert!(matches!(
+            task_rx.try_recv(),
+            Ok(BackendTask::WriteHunk {
+                direction: HunkDirection::ToDestination,
+                ..
+            })
+        ));
+
+        app.config.protected_path_mode = ProtectedPathMode::Refuse;
+        press(&mut app, &task_tx, KeyCode::Char('A'));
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(
+            app.logs
+                .iter()
+                .any(|line| line.contains("refused apply-hunk"))
+        );
+    }
+
+    #[test]
+    fn builtin_merge_resolves_conflicts_before_writing_the_source() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::MergeLoaded {
+                target: PathBuf::from("/tmp/merge-home/.zshrc"),
+                source: PathBuf::from("/tmp/merge-src/dot_zshrc"),
+                ours: "a\nmine\nc\n".to_string(),
+                base: "a\nb\nc\n".to_string(),
+                theirs: "a\nyours\nc\n".to_string(),
+            },
+        )
+        .expect("event");
+        assert!(matches!(app.modal, ModalState::Merge(_)));
+
+        press(&mut app, &task_tx, KeyCode::Char('w'));
+        assert!(task_rx.try_recv().is_err());
+        assert!(matches!(
+            &app.modal,
+            ModalState::Merge(view) if view.message.as_deref() == Some("1 block(s) still unresolved")
+        ));
+
+        press(&mut app, &task_tx, KeyCode::Char('b'));
+        press(&mut app, &task_tx, KeyCode::Char('w'));
+        assert!(matches!(app.modal, ModalState::None));
+        let Ok(BackendTask::SaveMerge {
+            source, content, ..
+        }) = task_rx.try_recv()
+        else {
+            panic!("expected a SaveMerge task");
+        };
+        assert_eq!(source, PathBuf::from("/tmp/merge-src/dot_zshrc"));
+        assert_eq!(content, "a\nmine\nyours\nc\n");
+    }
+
+    #[test]
+    fn builtin_merge_on_a_protected_target_requires_the_typed_phrase() {
+        let mut app = App::new(AppConfig::default());
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        let target = app.home_dir.join(".ssh/config");
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::MergeLoaded {
+                target: target.clone(),
+                source: PathBuf::from("/tmp/merge-src/private_dot_ssh/config"),
+                ours: "a\nmine\n".to_string(),
+                base: "a\n".to_string(),
+                theirs: "a\n".to_string(),
+            },
+        )
+        .expect("event");
+
+        press(&mut app, &task_tx, KeyCode::Char('w'));
+        press(&mut app, &task_tx, KeyCode::Char('w'));
+        assert!(task_rx.try_recv().is_err());
+        press(&mut app, &task_tx, KeyCode::Enter);
+        assert!(matches!(
+            &app.modal,
+            ModalState::Merge(view) if view.message.as_deref() == Some("confirmation phrase does not match")
+        ));
+
+        press(&mut app, &task_tx, KeyCode::Esc);
+        assert!(matches!(&app.modal, ModalState::Merge(view) if view.confirm.is_none()));
+        press(&mut app, &task_tx, KeyCode::Char('w'));
+        for ch in format!("MERGE {}", target.display()).chars() {
+            press(&mut app, &task_tx, KeyCode::Char(ch));
+        }
+        press(&mut app, &task_tx, KeyCode::Enter);
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(matches!(
+            task_rx.try_recv(),
+            Ok(BackendTask::SaveMerge { .. })
+        ));
+    }
+
+    #[test]
+    fn repo_diff_browser_marks_flow_into_the_status_list() {
+        let mut app = App::new(AppConfig::default());
+        app.switch_view(ListView::Managed);
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        press(&mut app, &task_tx, KeyCode::Char('D'));
+        assert_eq!(app.view, ListView::Status);
+        assert!(matches!(task_rx.try_recv(), Ok(BackendTask::LoadRepoDiff)));
+
+        let diff = concat!(
+            "diff --git a/.zshrc b/.zshrc\n",
+            "@@ -1 +1 @@\n",
+            "-old\n",
+            "+new\n",
+            "diff --git a/.vimrc b/.vimrc\n",
+            "@@ -1 +1 @@\n",
+            "-a\n",
+            "+b\n",
+        );
+        handle_backend_event(
+            &mut app,
+            &task_tx,
+            BackendEvent::RepoDiffLoaded {
+                diff: crate::domain::DiffText {
+                    text: diff.to_string(),
+                },
+            },
+        )
+        .expect("event");
+        assert!(matches!(app.modal, ModalState::RepoDiff(_)));
+
+        press(&mut app, &task_tx, KeyCode::Char('j'));
+        press(&mut app, &task_tx, KeyCode::Char(' '));
+        press(&mut app, &task_tx, KeyCode::Esc);
+        assert!(matches!(app.modal, ModalState::None));
+        assert!(app.is_marked(std::path::Path::new(".vimrc")));
+        assert_eq!(app.marked_count(), 1);
+    }
+
+    #[test]
+    fn slash_in_detail_focus_searches_and_esc_restores_previous_query() {
+        let mut app = App::new(AppConfig::default());
+        app.focus = crate::app::PaneFocus::Detail;
+        app.set_detail_diff(None, TWO_HUNK_DIFF.to_string());
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        press(&mut app, &task_tx, KeyCode::Char('/'));
+        for ch in "tail".chars() {
+            press(&mut app, &task_tx, KeyCode::Char(ch));
+        }
+        assert_eq!(app.detail_scroll, 9);
+        press(&mut app, &task_tx, KeyCode::Enter);
+        assert!(matches!(app.modal, ModalState::None));
+        assert_eq!(app.detail_search(), "tail");
+
+        press(&mut app, &task_tx, KeyCode::Char('N'));
+        assert_eq!(app.detail_scroll, 10);
+        press(&mut app, &task_tx, KeyCode::Char('n'));
+        assert_eq!(app.detail_scroll, 9);
+
+        press(&mut app, &task_tx, KeyCode::Char('/'));
+        press(&mut app, &task_tx, KeyCode::Char('x'));
+        press(&mut app, &task_tx, KeyCode::Esc);
+        assert_eq!(app.detail_search(), "tail");
+        press(&mut app, &task_tx, KeyCode::Esc);
+        assert_eq!(app.detail_search(), "");
+    }
 }
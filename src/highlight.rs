@@ -0,0 +1,1140 @@
+use std::path::Path;
+
+/// Lines scanned at each end of a file for a vim / emacs modeline.
+const MODELINE_LINES: usize = 5;
+
+/// Highlight class of a run of text; the renderer maps each to a theme role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Token {
+    Plain,
+    Keyword,
+    Key,
+    String,
+    Number,
+    Punctuation,
+    Comment,
+    /// INI / TOML section headers and Markdown headings.
+    Section,
+    Variable,
+    /// Go template `{{` / `}}` delimiters.
+    Template,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Syntax {
+    Rust,
+    Shell,
+    Fish,
+    Nushell,
+    Lua,
+    Python,
+    JsTs,
+    Json,
+    Toml,
+    Yaml,
+    /// gitconfig, editorconfig and other INI-style files.
+    Ini,
+    SshConfig,
+    Tmux,
+    Vim,
+    Kdl,
+    Markdown,
+    #[default]
+    Plain,
+}
+
+/// How the first word of a line is recognised as a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyStyle {
+    None,
+    /// `key = value`
+    Equals,
+    /// `key: value`, optionally after a `- ` list marker.
+    Colon,
+    /// `Key value` (ssh_config, KDL node names).
+    FirstWord,
+    /// `"key": value`
+    QuotedColon,
+}
+
+/// Lexical rules for one line-oriented format.
+struct Grammar {
+    /// Line comment markers; single-character ones must start a word.
+    comments: &'static [&'static str],
+    /// Markers that comment a line only as its first non-blank text.
+    line_start_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// Characters that introduce a variable (`$HOME`, `@plugin`, `&anchor`).
+    sigils: &'static [char],
+    keywords: &'static [&'static str],
+    constants: &'static [&'static str],
+    keys: KeyStyle,
+    /// `[section]` lines.
+    sections: bool,
+    /// Words may contain `-` (`set-option`, KDL node names).
+    dashed_words: bool,
+    /// vim's `g:name` scopes and `<Leader>` key notation.
+    vim_scopes: bool,
+}
+
+const PLAIN: Grammar = Grammar {
+    comments: &[],
+    line_start_comments: &[],
+    block_comment: None,
+    quotes: &[],
+    sigils: &[],
+    keywords: &[],
+    constants: &[],
+    keys: KeyStyle::None,
+    sections: false,
+    dashed_words: false,
+    vim_scopes: false,
+};
+
+const RUST: Grammar = Grammar {
+    comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    keywords: &[
+        "fn", "let", "mut", "pub", "struct", "enum", "impl", "use", "mod", "match", "if", "else",
+        "for", "while", "loop", "return", "async", "await", "trait", "where", "self", "Self",
+    ],
+    constants: &["true", "false"],
+    ..PLAIN
+};
+
+const SHELL: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    sigils: &['$'],
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "for", "in", "do", "done", "case", "esac", "while",
+        "until", "function", "export", "local", "return", "source", "alias", "unset",
+    ],
+    ..PLAIN
+};
+
+const FISH: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    sigils: &['$'],
+    keywords: &[
+        "function", "end", "if", "else", "switch", "case", "for", "in", "while", "begin", "set",
+        "and", "or", "not", "return", "alias", "abbr", "source", "status", "test",
+    ],
+    dashed_words: true,
+    ..PLAIN
+};
+
+const NUSHELL: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\'', '`'],
+    sigils: &['$'],
+    keywords: &[
+        "def", "let", "mut", "const", "if", "else", "for", "in", "loop", "while", "match", "use",
+        "export", "module", "return", "alias", "extern", "source", "try", "catch", "do",
+    ],
+    constants: &["true", "false", "null"],
+    dashed_words: true,
+    ..PLAIN
+};
+
+const LUA: Grammar = Grammar {
+    comments: &["--"],
+    quotes: &['"', '\''],
+    keywords: &[
+        "local", "function", "if", "then", "else", "elseif", "end", "for", "in", "do", "while",
+        "repeat", "until", "return", "and", "or", "not",
+    ],
+    constants: &["true", "false", "nil"],
+    ..PLAIN
+};
+
+const PYTHON: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    keywords: &[
+        "def", "class", "if", "elif", "else", "for", "while", "try", "except", "finally", "return",
+        "import", "from", "as", "with", "lambda", "in", "not", "and", "or",
+    ],
+    constants: &["True", "False", "None"],
+    ..PLAIN
+};
+
+const JS_TS: Grammar = Grammar {
+    comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    keywords: &[
+        "function",
+        "const",
+        "let",
+        "var",
+        "if",
+        "else",
+        "for",
+        "while",
+        "return",
+        "import",
+        "from",
+        "export",
+        "class",
+        "extends",
+        "async",
+        "await",
+        "type",
+        "interface",
+    ],
+    constants: &["true", "false", "null", "undefined"],
+    ..PLAIN
+};
+
+const JSON: Grammar = Grammar {
+    quotes: &['"'],
+    constants: &["true", "false", "null"],
+    keys: KeyStyle::QuotedColon,
+    ..PLAIN
+};
+
+const TOML: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    constants: &["true", "false"],
+    keys: KeyStyle::Equals,
+    sections: true,
+    ..PLAIN
+};
+
+const YAML: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    sigils: &['&', '*'],
+    constants: &["true", "false", "null", "yes", "no", "on", "off"],
+    keys: KeyStyle::Colon,
+    ..PLAIN
+};
+
+const INI: Grammar = Grammar {
+    comments: &["#", ";"],
+    quotes: &['"'],
+    constants: &["true", "false", "yes", "no", "on", "off"],
+    keys: KeyStyle::Equals,
+    sections: true,
+    dashed_words: true,
+    ..PLAIN
+};
+
+const SSH_CONFIG: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"'],
+    keywords: &["Host", "Match", "Include"],
+    constants: &["yes", "no", "ask", "auto", "none", "any", "all"],
+    keys: KeyStyle::FirstWord,
+    ..PLAIN
+};
+
+const TMUX: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    sigils: &['@', '$'],
+    keywords: &[
+        "set",
+        "set-option",
+        "setw",
+        "set-window-option",
+        "bind",
+        "bind-key",
+        "unbind",
+        "unbind-key",
+        "run",
+        "run-shell",
+        "source",
+        "source-file",
+        "if-shell",
+        "new-session",
+        "display-message",
+        "send-keys",
+    ],
+    constants: &["on", "off"],
+    dashed_words: true,
+    ..PLAIN
+};
+
+const VIM: Grammar = Grammar {
+    line_start_comments: &["\""],
+    quotes: &['\'', '"'],
+    keywords: &[
+        "set",
+        "setlocal",
+        "let",
+        "unlet",
+        "if",
+        "elseif",
+        "else",
+        "endif",
+        "for",
+        "endfor",
+        "while",
+        "endwhile",
+        "function",
+        "endfunction",
+        "call",
+        "return",
+        "try",
+        "catch",
+        "endtry",
+        "augroup",
+        "autocmd",
+        "command",
+        "execute",
+        "source",
+        "runtime",
+        "filetype",
+        "syntax",
+        "highlight",
+        "colorscheme",
+        "map",
+        "noremap",
+        "nnoremap",
+        "inoremap",
+        "vnoremap",
+        "xnoremap",
+        "nmap",
+        "imap",
+        "vmap",
+        "lua",
+        "Plug",
+    ],
+    constants: &["true", "false"],
+    vim_scopes: true,
+    ..PLAIN
+};
+
+const KDL: Grammar = Grammar {
+    comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    constants: &["true", "false", "null"],
+    keys: KeyStyle::FirstWord,
+    dashed_words: true,
+    ..PLAIN
+};
+
+/// Go template actions, as used by chezmoi `.tmpl` sources.
+const TEMPLATE_KEYWORDS: &[&str] = &[
+    "if",
+    "else",
+    "end",
+    "range",
+    "with",
+    "define",
+    "template",
+    "block",
+    "break",
+    "continue",
+    "and",
+    "or",
+    "not",
+    "eq",
+    "ne",
+    "lt",
+    "le",
+    "gt",
+    "ge",
+    "len",
+    "index",
+    "print",
+    "printf",
+    "println",
+    "include",
+    "includeTemplate",
+    "true",
+    "false",
+    "nil",
+];
+
+impl Syntax {
+    fn grammar(self) -> &'static Grammar {
+        match self {
+            Syntax::Rust => &RUST,
+            Syntax::Shell => &SHELL,
+            Syntax::Fish => &FISH,
+            Syntax::Nushell => &NUSHELL,
+            Syntax::Lua => &LUA,
+            Syntax::Python => &PYTHON,
+            Syntax::JsTs => &JS_TS,
+            Syntax::Json => &JSON,
+            Syntax::Toml => &TOML,
+            Syntax::Yaml => &YAML,
+            Syntax::Ini => &INI,
+            Syntax::SshConfig => &SSH_CONFIG,
+            Syntax::Tmux => &TMUX,
+            Syntax::Vim => &VIM,
+            Syntax::Kdl => &KDL,
+            Syntax::Markdown | Syntax::Plain => &PLAIN,
+        }
+    }
+
+    /// Filetype names as written in modelines.
+    fn from_name(name: &str) -> Option<Self> {
+        let syntax = match name.to_ascii_lowercase().as_str() {
+            "rust" => Syntax::Rust,
+            "sh" | "bash" | "zsh" | "shell" => Syntax::Shell,
+            "fish" => Syntax::Fish,
+            "nu" | "nushell" => Syntax::Nushell,
+            "lua" => Syntax::Lua,
+            "python" => Syntax::Python,
+            "javascript" | "typescript" | "js" | "ts" => Syntax::JsTs,
+            "json" | "jsonc" => Syntax::Json,
+            "toml" => Syntax::Toml,
+            "yaml" => Syntax::Yaml,
+            "gitconfig" | "dosini" | "ini" | "conf" | "editorconfig" => Syntax::Ini,
+            "sshconfig" | "ssh-config" => Syntax::SshConfig,
+            "tmux" => Syntax::Tmux,
+            "vim" => Syntax::Vim,
+            "kdl" => Syntax::Kdl,
+            "markdown" | "md" => Syntax::Markdown,
+            _ => return None,
+        };
+        Some(syntax)
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        let syntax = match extension {
+            "rs" => Syntax::Rust,
+            "sh" | "bash" | "zsh" => Syntax::Shell,
+            "fish" => Syntax::Fish,
+            "nu" => Syntax::Nushell,
+            "lua" => Syntax::Lua,
+            "py" => Syntax::Python,
+            "js" | "mjs" | "cjs" | "ts" | "tsx" | "jsx" => Syntax::JsTs,
+            "json" | "jsonc" => Syntax::Json,
+            "toml" => Syntax::Toml,
+            "yaml" | "yml" => Syntax::Yaml,
+            "ini" | "cfg" | "conf" | "gitconfig" => Syntax::Ini,
+            "tmux" => Syntax::Tmux,
+            "vim" => Syntax::Vim,
+            "kdl" => Syntax::Kdl,
+            "md" | "markdown" => Syntax::Markdown,
+            _ => return None,
+        };
+        Some(syntax)
+    }
+
+    /// Well-known dotfiles whose name carries no (or a misleading) extension.
+    fn from_file_name(name: &str, parent: &str) -> Option<Self> {
+        let bare = name.trim_start_matches('.');
+        let syntax = match bare {
+            "gitconfig" | "gitmodules" | "editorconfig" => Syntax::Ini,
+            "tmux.conf" => Syntax::Tmux,
+            "vimrc" | "gvimrc" | "exrc" => Syntax::Vim,
+            "ssh_config" | "sshd_config" => Syntax::SshConfig,
+            "config" if parent == "git" => Syntax::Ini,
+            _ if parent == ".ssh" && bare.starts_with("config") => Syntax::SshConfig,
+            _ if [
+                "zsh", "bash", "profile", "zprofile", "zlogin", "zlogout", "aliases", "envrc",
+            ]
+            .iter()
+            .any(|prefix| bare.starts_with(prefix)) =>
+            {
+                Syntax::Shell
+            }
+            _ => return None,
+        };
+        Some(syntax)
+    }
+
+    /// `vim: set ft=sh:` or `-*- mode: sh -*-` near either end of the file.
+    fn from_modeline(content: &str) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let tail = lines
+            .len()
+            .saturating_sub(MODELINE_LINES)
+            .max(MODELINE_LINES);
+        lines
+            .iter()
+            .take(MODELINE_LINES)
+            .chain(lines.iter().skip(tail))
+            .find_map(|line| {
+                if let Some(start) = line.find("-*-") {
+                    let rest = &line[start + 3..];
+                    let body = &rest[..rest.find("-*-")?];
+                    let name = body
+                        .split(';')
+                        .find_map(|part| {
+                            let (key, value) = part.split_once(':')?;
+                            (key.trim() == "mode").then_some(value)
+                        })
+                        .unwrap_or(body);
+                    return Syntax::from_name(name.trim());
+                }
+                let start = ["vim:", "vi:", "ex:"]
+                    .iter()
+                    .find_map(|marker| line.find(marker).map(|index| index + marker.len()))?;
+                line[start..]
+                    .split([' ', ':', '\t'])
+                    .find_map(|option| {
+                        let (key, value) = option.split_once('=')?;
+                        matches!(key, "ft" | "filetype" | "syntax").then_some(value)
+                    })
+                    .and_then(Syntax::from_name)
+            })
+    }
+
+    /// `#!/bin/sh`, `#!/usr/bin/env -S fish`, ...
+    fn from_shebang(content: &str) -> Option<Self> {
+        let line = content.lines().next()?.strip_prefix("#!")?;
+        let mut words = line.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = words.find(|word| !word.starts_with('-'))?;
+        }
+        match program {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Syntax::Shell),
+            "fish" => Some(Syntax::Fish),
+            "nu" => Some(Syntax::Nushell),
+            "node" | "deno" | "bun" => Some(Syntax::JsTs),
+            _ if program.starts_with("python") => Some(Syntax::Python),
+            _ if program.starts_with("lua") => Some(Syntax::Lua),
+            _ => None,
+        }
+    }
+}
+
+/// Chooses the grammar for a preview. Names are decoded from chezmoi source
+/// form first (`private_dot_gitconfig.tmpl` is a templated `.gitconfig`);
+/// an explicit modeline wins, then the file name and extension, then the
+/// shebang. Returns whether Go template actions should be highlighted too.
+pub(crate) fn detect_syntax(path: Option<&Path>, content: &str) -> (Syntax, bool) {
+    let name = path
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let parent = path
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .map(|name| target_name(&name.to_string_lossy()))
+        .unwrap_or_default();
+    let in_templates_dir = path.is_some_and(|path| {
+        path.components()
+            .any(|component| component.as_os_str() == ".chezmoitemplates")
+    });
+    let (name, template) = match name.strip_suffix(".tmpl") {
+        Some(stripped) => (stripped.to_string(), true),
+        None => (name, in_templates_dir),
+    };
+    let name = target_name(&name).to_ascii_lowercase();
+    let extension = Path::new(&name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string());
+
+    let syntax = Syntax::from_modeline(content)
+        .or_else(|| Syntax::from_file_name(&name, &parent))
+        .or_else(|| extension.as_deref().and_then(Syntax::from_extension))
+        .or_else(|| Syntax::from_shebang(content))
+        .unwrap_or(Syntax::Plain);
+    (syntax, template)
+}
+
+/// Target name of a chezmoi source entry: attribute prefixes dropped and
+/// `dot_` turned back into a leading dot.
+fn target_name(name: &str) -> String {
+    const PREFIXES: [&str; 12] = [
+        "create_",
+        "modify_",
+        "run_",
+        "once_",
+        "onchange_",
+        "symlink_",
+        "encrypted_",
+        "private_",
+        "readonly_",
+        "empty_",
+        "executable_",
+        "exact_",
+    ];
+    let mut rest = name;
+    while let Some(stripped) = PREFIXES.iter().find_map(|prefix| rest.strip_prefix(prefix)) {
+        rest = stripped;
+    }
+    match rest.strip_prefix("dot_") {
+        Some(stripped) => format!(".{stripped}"),
+        None => rest.to_string(),
+    }
+}
+
+/// Splits `content` into highlighted runs, one vector per line.
+pub(crate) fn highlight(path: Option<&Path>, content: &str) -> Vec<Vec<(Token, String)>> {
+    let (syntax, template) = detect_syntax(path, content);
+    let mut highlighter = Highlighter::new(syntax, template);
+    content.lines().map(|line| highlighter.line(line)).collect()
+}
+
+/// Runs of one line, with adjacent runs of the same class merged.
+#[derive(Default)]
+struct Runs(Vec<(Token, String)>);
+
+impl Runs {
+    fn push(&mut self, token: Token, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some((last, run)) if *last == token => run.push_str(text),
+            _ => self.0.push((token, text.to_string())),
+        }
+    }
+
+    fn push_chars(&mut self, token: Token, chars: &[char]) {
+        self.push(token, &chars.iter().collect::<String>());
+    }
+}
+
+/// State carried from one line to the next.
+#[derive(Debug, Default)]
+struct Highlighter {
+    syntax: Syntax,
+    template: bool,
+    block_comment: bool,
+    /// Inside a fenced Markdown code block.
+    fence: bool,
+    /// Inside a `{{ ... }}` action that spans lines.
+    action: bool,
+    action_comment: bool,
+    /// A line comment started earlier on the current line.
+    line_comment: bool,
+}
+
+impl Highlighter {
+    fn new(syntax: Syntax, template: bool) -> Self {
+        Self {
+            syntax,
+            template,
+            ..Self::default()
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Vec<(Token, String)> {
+        let mut runs = Runs::default();
+        self.line_comment = false;
+        if !self.template {
+            self.text(line, true, &mut runs);
+            return runs.0;
+        }
+
+        let mut rest = line;
+        let mut line_start = true;
+        loop {
+            if self.action {
+                let consumed = self.action(rest, &mut runs);
+                rest = &rest[consumed..];
+                line_start = false;
+                if self.action {
+                    break;
+                }
+                continue;
+            }
+            let Some(open) = rest.find("{{") else {
+                self.text(rest, line_start, &mut runs);
+                break;
+            };
+            self.text(&rest[..open], line_start, &mut runs);
+            let delimiter = if rest[open..].starts_with("{{-") {
+                "{{-"
+            } else {
+                "{{"
+            };
+            runs.push(Token::Template, delimiter);
+            rest = &rest[open + delimiter.len()..];
+            self.action = true;
+            line_start = false;
+        }
+        runs.0
+    }
+
+    /// Highlights a Go template action up to and including its closing
+    /// delimiter; returns the bytes consumed.
+    fn action(&mut self, text: &str, runs: &mut Runs) -> usize {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if self.action_comment {
+                let end = find(&chars, i, "*/").map_or(chars.len(), |end| end + 2);
+                runs.push_chars(Token::Comment, &chars[i..end]);
+                self.action_comment = end == chars.len() && !ends_with(&chars, "*/");
+                i = end;
+                continue;
+            }
+            if starts_at(&chars, i, "/*") {
+                self.action_comment = true;
+                continue;
+            }
+            for close in ["-}}", "}}"] {
+                if starts_at(&chars, i, close) {
+                    runs.push(Token::Template, close);
+                    self.action = false;
+                    return byte_offset(&chars, i + close.chars().count());
+                }
+            }
+            let ch = chars[i];
+            if ch == '"' || ch == '`' {
+                let end = string_end(&chars, i, ch);
+                runs.push_chars(Token::String, &chars[i..end]);
+                i = end;
+            } else if (ch == '.' || ch == '$')
+                && chars
+                    .get(i + 1)
+                    .is_none_or(|next| is_word_start(*next) || *next == '.')
+            {
+                let end = scan(&chars, i + 1, |ch| is_word(ch) || ch == '.');
+                runs.push_chars(Token::Variable, &chars[i..end]);
+                i = end;
+            } else if ch.is_ascii_digit() {
+                let end = scan(&chars, i, |ch| ch.is_ascii_alphanumeric() || ch == '.');
+                runs.push_chars(Token::Number, &chars[i..end]);
+                i = end;
+            } else if is_word_start(ch) {
+                let end = scan(&chars, i, is_word);
+                let word: String = chars[i..end].iter().collect();
+                let token = if TEMPLATE_KEYWORDS.contains(&word.as_str()) {
+                    Token::Keyword
+                } else {
+                    Token::Plain
+                };
+                runs.push(token, &word);
+                i = end;
+            } else if ch.is_whitespace() {
+                runs.push(Token::Plain, &ch.to_string());
+                i += 1;
+            } else {
+                runs.push(Token::Punctuation, &ch.to_string());
+                i += 1;
+            }
+        }
+        text.len()
+    }
+
+    /// Highlights text outside template actions with the file's grammar.
+    fn text(&mut self, text: &str, line_start: bool, runs: &mut Runs) {
+        if self.syntax == Syntax::Markdown {
+            self.markdown(text, line_start, runs);
+            return;
+        }
+        if self.line_comment {
+            runs.push(Token::Comment, text);
+            return;
+        }
+        let grammar = self.syntax.grammar();
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        if line_start && !self.block_comment {
+            let indent = scan(&chars, 0, char::is_whitespace);
+            let trimmed = &text[byte_offset(&chars, indent)..];
+            if grammar
+                .line_start_comments
+                .iter()
+                .any(|marker| trimmed.starts_with(marker))
+            {
+                runs.push(Token::Comment, text);
+                self.line_comment = true;
+                return;
+            }
+            if grammar.sections && trimmed.starts_with('[') {
+                let end = chars[indent..]
+                    .iter()
+                    .position(|ch| *ch == ']')
+                    .map_or(chars.len(), |close| indent + close + 1);
+                runs.push_chars(Token::Plain, &chars[..indent]);
+                runs.push_chars(Token::Section, &chars[indent..end]);
+                i = end;
+            } else {
+                i = leading_key(grammar, &chars, runs);
+            }
+        }
+
+        while i < chars.len() {
+            if self.block_comment {
+                let (_, close) = grammar.block_comment.unwrap_or(("", ""));
+                let end = find(&chars, i, close).map_or(chars.len(), |end| end + close.len());
+                self.block_comment = end == chars.len() && !ends_with(&chars, close);
+                runs.push_chars(Token::Comment, &chars[i..end]);
+                i = end;
+                continue;
+            }
+            if let Some((open, _)) = grammar.block_comment
+                && starts_at(&chars, i, open)
+            {
+                self.block_comment = true;
+                runs.push(Token::Comment, open);
+                i += open.len();
+                continue;
+            }
+            if is_comment_start(grammar, &chars, i) {
+                runs.push_chars(Token::Comment, &chars[i..]);
+                self.line_comment = true;
+                return;
+            }
+
+            let ch = chars[i];
+            let next = chars.get(i + 1).copied();
+            if grammar.quotes.contains(&ch) {
+                let end = string_end(&chars, i, ch);
+                let token = if grammar.keys == KeyStyle::QuotedColon
+                    && chars[end..].iter().find(|ch| !ch.is_whitespace()) == Some(&':')
+                {
+                    Token::Key
+                } else {
+                    Token::String
+                };
+                runs.push_chars(token, &chars[i..end]);
+                i = end;
+            } else if grammar.sigils.contains(&ch)
+                && next.is_some_and(|next| is_word_start(next) || next == '{')
+            {
+                let end = if next == Some('{') {
+                    find(&chars, i, "}").map_or(chars.len(), |end| end + 1)
+                } else {
+                    scan(&chars, i + 1, is_word)
+                };
+                runs.push_chars(Token::Variable, &chars[i..end]);
+                i = end;
+            } else if grammar.vim_scopes && ch == '<' && next.is_some_and(is_word_start) {
+                let end = find(&chars, i, ">").map_or(chars.len(), |end| end + 1);
+                runs.push_chars(Token::Variable, &chars[i..end]);
+                i = end;
+            } else if ch.is_ascii_digit() && (i == 0 || !is_word(chars[i - 1])) {
+                let end = scan(&chars, i, |ch| {
+                    ch.is_ascii_hexdigit() || matches!(ch, '_' | '.' | 'x' | 'X')
+                });
+                runs.push_chars(Token::Number, &chars[i..end]);
+                i = end;
+            } else if is_word_start(ch) {
+                let end = scan(&chars, i, |ch| {
+                    is_word(ch) || (grammar.dashed_words && ch == '-')
+                });
+                let word: String = chars[i..end].iter().collect();
+                let scoped = grammar.vim_scopes
+                    && end - i == 1
+                    && chars.get(end) == Some(&':')
+                    && "gsbwltav".contains(ch);
+                if scoped {
+                    let end = scan(&chars, end + 1, |ch| is_word(ch) || ch == '#');
+                    runs.push_chars(Token::Variable, &chars[i..end]);
+                    i = end;
+                    continue;
+                }
+                let token = if grammar.keywords.contains(&word.as_str())
+                    || grammar.constants.contains(&word.as_str())
+                {
+                    Token::Keyword
+                } else {
+                    Token::Plain
+                };
+                runs.push(token, &word);
+                i = end;
+            } else if ch.is_whitespace() {
+                runs.push(Token::Plain, &ch.to_string());
+                i += 1;
+            } else {
+                runs.push(Token::Punctuation, &ch.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    fn markdown(&mut self, text: &str, line_start: bool, runs: &mut Runs) {
+        let trimmed = text.trim_start();
+        if line_start && (trimmed.starts_with("```") || trimmed.starts_with("~~~")) {
+            self.fence = !self.fence;
+            runs.push(Token::String, text);
+            return;
+        }
+        if self.fence {
+            runs.push(Token::String, text);
+            return;
+        }
+        if line_start && trimmed.starts_with('#') {
+            runs.push(Token::Section, text);
+            return;
+        }
+        if line_start && trimmed.starts_with('>') {
+            runs.push(Token::Comment, text);
+            return;
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        if line_start {
+            let indent = scan(&chars, 0, char::is_whitespace);
+            let digits = scan(&chars, indent, |ch| ch.is_ascii_digit());
+            let marker = match (chars.get(indent), chars.get(digits)) {
+                (Some('-' | '*' | '+'), _) => Some(indent + 1),
+                (_, Some('.' | ')')) if digits > indent => Some(digits + 1),
+                _ => None,
+            };
+            if let Some(end) = marker.filter(|end| chars.get(*end).is_none_or(|ch| *ch == ' ')) {
+                runs.push_chars(Token::Plain, &chars[..indent]);
+                runs.push_chars(Token::Punctuation, &chars[indent..end]);
+                i = end;
+            }
+        }
+        while i < chars.len() {
+            let ch = chars[i];
+            if ch == '`' {
+                let end = find(&chars, i + 1, "`").map_or(chars.len(), |end| end + 1);
+                runs.push_chars(Token::String, &chars[i..end]);
+                i = end;
+            } else if ch == '['
+                && let Some(close) = find(&chars, i, "]")
+            {
+                runs.push_chars(Token::Key, &chars[i..=close]);
+                i = close + 1;
+                if chars.get(i) == Some(&'(') {
+                    let end = find(&chars, i, ")").map_or(chars.len(), |end| end + 1);
+                    runs.push_chars(Token::String, &chars[i..end]);
+                    i = end;
+                }
+            } else if (starts_at(&chars, i, "**") || starts_at(&chars, i, "__"))
+                && let Some(close) =
+                    find(&chars, i + 2, &chars[i..i + 2].iter().collect::<String>())
+            {
+                runs.push_chars(Token::Keyword, &chars[i..close + 2]);
+                i = close + 2;
+            } else {
+                runs.push(Token::Plain, &ch.to_string());
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Highlights the key at the start of a line (after indentation and, for
+/// YAML, a list marker); returns where the rest of the line starts.
+fn leading_key(grammar: &Grammar, chars: &[char], runs: &mut Runs) -> usize {
+    let indent = scan(chars, 0, char::is_whitespace);
+    let mut start = indent;
+    if grammar.keys == KeyStyle::Colon && starts_at(chars, start, "- ") {
+        start += 2;
+    }
+    let valid_key = |key: &[char]| {
+        !key.is_empty()
+            && is_word_start(key[0])
+            && !key
+                .iter()
+                .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\'' | '{'))
+    };
+    let end = match grammar.keys {
+        KeyStyle::None | KeyStyle::QuotedColon => None,
+        KeyStyle::Equals => chars[start..]
+            .iter()
+            .position(|ch| *ch == '=')
+            .and_then(|eq| {
+                let end = start
+                    + chars[start..start + eq]
+                        .iter()
+                        .rposition(|ch| !ch.is_whitespace())?
+                    + 1;
+                valid_key(&chars[start..end]).then_some(end)
+            }),
+        KeyStyle::Colon => chars[start..]
+            .iter()
+            .enumerate()
+            .position(|(offset, ch)| {
+                *ch == ':'
+                    && chars
+                        .get(start + offset + 1)
+                        .is_none_or(|next| next.is_whitespace())
+            })
+            .map(|colon| start + colon)
+            .filter(|end| valid_key(&chars[start..*end])),
+        KeyStyle::FirstWord => {
+            let end = scan(chars, start, |ch| !ch.is_whitespace() && ch != '=');
+            valid_key(&chars[start..end]).then_some(end)
+        }
+    };
+    let Some(end) = end else {
+        return 0;
+    };
+    let word: String = chars[start..end].iter().collect();
+    let token = if grammar.keywords.contains(&word.as_str()) {
+        Token::Keyword
+    } else {
+        Token::Key
+    };
+    runs.push_chars(Token::Plain, &chars[..indent]);
+    runs.push_chars(Token::Punctuation, &chars[indent..start]);
+    runs.push(token, &word);
+    end
+}
+
+/// Single-character markers (`#`, `;`) only start a comment at the start of
+/// a word, so `a#b` and tmux's `#{pane_id}` formats stay code.
+fn is_comment_start(grammar: &Grammar, chars: &[char], i: usize) -> bool {
+    grammar.comments.iter().any(|marker| {
+        if !starts_at(chars, i, marker) {
+            return false;
+        }
+        if marker.chars().count() > 1 {
+            return true;
+        }
+        let after_space = i == 0 || chars[i - 1].is_whitespace();
+        after_space && !(*marker == "#" && chars.get(i + 1) == Some(&'{'))
+    })
+}
+
+/// End of the string literal opening at `start` (exclusive); unterminated
+/// strings run to the end of the line.
+fn string_end(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == '\\' && quote != '`' {
+            i += 2;
+            continue;
+        }
+        if chars[i] == quote {
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+fn scan(chars: &[char], from: usize, accept: impl Fn(char) -> bool) -> usize {
+    chars[from.min(chars.len())..]
+        .iter()
+        .position(|ch| !accept(*ch))
+        .map_or(chars.len(), |offset| from + offset)
+}
+
+fn starts_at(chars: &[char], i: usize, pattern: &str) -> bool {
+    pattern
+        .chars()
+        .enumerate()
+        .all(|(offset, expected)| chars.get(i + offset) == Some(&expected))
+}
+
+fn ends_with(chars: &[char], pattern: &str) -> bool {
+    let len = pattern.chars().count();
+    chars.len() >= len && starts_at(chars, chars.len() - len, pattern)
+}
+
+fn find(chars: &[char], from: usize, pattern: &str) -> Option<usize> {
+    (from..chars.len()).find(|index| starts_at(chars, *index, pattern))
+}
+
+fn byte_offset(chars: &[char], index: usize) -> usize {
+    chars[..index].iter().map(|ch| ch.len_utf8()).sum()
+}
+
+fn is_word_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+fn is_word(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs_of(path: &str, content: &str) -> Vec<Vec<(Token, String)>> {
+        highlight(Some(Path::new(path)), content)
+    }
+
+    fn run(token: Token, text: &str) -> (Token, String) {
+        (token, text.to_string())
+    }
+
+    #[test]
+    fn detects_syntax_from_names_modelines_and_shebangs() {
+        let detect = |path: &str, content: &str| detect_syntax(Some(Path::new(path)), content);
+        assert_eq!(detect(".gitconfig", ""), (Syntax::Ini, false));
+        assert_eq!(
+            detect("/src/private_dot_gitconfig.tmpl", ""),
+            (Syntax::Ini, true)
+        );
+        assert_eq!(detect(".ssh/config", "").0, Syntax::SshConfig);
+        assert_eq!(detect("private_dot_ssh/config.d", "").0, Syntax::SshConfig);
+        assert_eq!(detect(".tmux.conf", "").0, Syntax::Tmux);
+        assert_eq!(detect(".zshrc.local", "").0, Syntax::Shell);
+        assert_eq!(detect("config.nu", "").0, Syntax::Nushell);
+        assert!(detect(".chezmoitemplates/aliases", "").1);
+        assert_eq!(detect("bin/setup", "#!/usr/bin/env fish\n").0, Syntax::Fish);
+        assert_eq!(
+            detect("notes", "text\n# vim: set ft=markdown:\n").0,
+            Syntax::Markdown
+        );
+        assert_eq!(detect("misc", "# -*- mode: yaml -*-\n").0, Syntax::Yaml);
+        assert_eq!(detect("misc", "plain").0, Syntax::Plain);
+    }
+
+    #[test]
+    fn ini_and_ssh_lines_split_into_sections_keys_and_comments() {
+        let lines = runs_of(
+            ".gitconfig",
+            "[user]\n\tname = Jane # me\nHost github.com\n",
+        );
+        assert_eq!(lines[0], vec![run(Token::Section, "[user]")]);
+        assert_eq!(
+            lines[1],
+            vec![
+                run(Token::Plain, "\t"),
+                run(Token::Key, "name"),
+                run(Token::Plain, " "),
+                run(Token::Punctuation, "="),
+                run(Token::Plain, " Jane "),
+                run(Token::Comment, "# me"),
+            ]
+        );
+
+        let ssh = runs_of(".ssh/config", "Host work\n  ForwardAgent yes\n");
+        assert_eq!(ssh[0][0], run(Token::Keyword, "Host"));
+        assert_eq!(ssh[1][1], run(Token::Key, "ForwardAgent"));
+        assert_eq!(ssh[1][3], run(Token::Keyword, "yes"));
+    }
+
+    #[test]
+    fn template_actions_are_highlighted_inside_the_base_grammar() {
+        let lines = runs_of(
+            "dot_zshrc.tmpl",
+            "export EDITOR={{ if eq .chezmoi.os \"darwin\" }}vim{{ end }} # x\n{{/* multi\nline */}}\n",
+        );
+        let first = &lines[0];
+        assert_eq!(first[0], run(Token::Keyword, "export"));
+        assert!(first.contains(&run(Token::Template, "{{")));
+        assert!(first.contains(&run(Token::Keyword, "eq")));
+        assert!(first.contains(&run(Token::Variable, ".chezmoi.os")));
+        assert!(first.contains(&run(Token::String, "\"darwin\"")));
+        assert_eq!(first.last(), Some(&run(Token::Comment, "# x")));
+        assert_eq!(lines[1][1], run(Token::Comment, "/* multi"));
+        assert_eq!(
+            lines[2],
+            vec![run(Token::Comment, "line */"), run(Token::Template, "}}")]
+        );
+    }
+
+    #[test]
+    fn markdown_fences_and_vim_comments_carry_state_across_lines() {
+        let lines = runs_of(
+            "README.md",
+            "# Title\n```sh\n# not a heading\n```\n- `code`\n",
+        );
+        assert_eq!(lines[0], vec![run(Token::Section, "# Title")]);
+        assert_eq!(lines[2], vec![run(Token::String, "# not a heading")]);
+        assert_eq!(
+            lines[4],
+            vec![
+                run(Token::Punctuation, "-"),
+                run(Token::Plain, " "),
+                run(Token::String, "`code`"),
+            ]
+        );
+
+        let vim = runs_of(".vimrc", "\" comment\nlet g:mapleader = \",\"\n");
+        assert_eq!(vim[0], vec![run(Token::Comment, "\" comment")]);
+        assert_eq!(vim[1][2], run(Token::Variable, "g:mapleader"));
+        assert_eq!(vim[1].last(), Some(&run(Token::String, "\",\"")));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::chattr::{ChattrAttribute, source_info};
+use crate::diff::Hunk;
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+/// Where a single accepted hunk is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkDirection {
+    /// Partial apply: write the target-state side into the destination file.
+    ToDestination,
+    /// Partial re-add: write the destination side back into the source file.
+    ToSource,
+}
+
+impl HunkDirection {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            HunkDirection::ToDestination => "apply-hunk",
+            HunkDirection::ToSource => "re-add-hunk",
+        }
+    }
+
+    /// Phrase typed to confirm a hunk write to a protected target.
+    pub(crate) fn confirmation_phrase(self, target: &Path) -> String {
+        format!("{} {}", self.label().to_ascii_uppercase(), target.display())
+    }
+}
+
+/// Reason the source entry's content cannot be rewritten from target content,
+/// if any: for encrypted files, scripts, `modify_` and symlinks the source is
+/// not the file's text. Templates are only accepted by callers that merge
+/// against the template itself rather than its rendered output.
+pub(crate) fn source_write_blocker(source: &Path, allow_templates: bool) -> Option<&'static str> {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let info = source_info(source, false);
+    if info.is_script {
+        Some("scripts")
+    } else if name.starts_with("modify_") {
+        Some("modify_ scripts")
+    } else if name.starts_with("symlink_") {
+        Some("symlinks")
+    } else if !allow_templates && info.attributes.contains(&ChattrAttribute::Template) {
+        Some("templates")
+    } else if info.attributes.contains(&ChattrAttribute::Encrypted) {
+        Some("encrypted files")
+    } else {
+        None
+    }
+}
+
+/// Rewrites `path` with `hunk` applied in `direction`.
+pub(crate) fn write_hunk(path: &Path, hunk: &Hunk, direction: HunkDirection) -> Result<()> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let updated = apply_hunk(&content, hunk, direction)
+        .with_context(|| format!("cannot apply hunk to {}", path.display()))?;
+    fs::write(path, updated).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Replaces the hunk's "from" side with its "to" side in `content`. The hunk is
+/// looked up at its recorded line first, then at the nearest offset where all
+/// of its lines match, so unrelated edits elsewhere in the file are tolerated.
+pub(crate) fn apply_hunk(content: &str, hunk: &Hunk, direction: HunkDirection) -> Result<String> {
+    let (from, to, start, to_missing_newline) = match direction {
+        HunkDirection::ToDestination => (
+            &hunk.old,
+            &hunk.new,
+            hunk.old_start,
+            hunk.new_missing_newline,
+        ),
+        HunkDirection::ToSource => (
+            &hunk.new,
+            &hunk.old,
+            hunk.new_start,
+            hunk.old_missing_newline,
+        ),
+    };
+
+    let had_newline = content.ends_with('\n');
+    let body = content.strip_suffix('\n').unwrap_or(content);
+    let mut lines: Vec<&str> = if content.is_empty() {
+        Vec::new()
+    } else {
+        body.split('\n').collect()
+    };
+
+    if from.len() > lines.len() {
+        bail!("hunk does not match the current content");
+    }
+    // An empty side's start number refers to the line before the insertion.
+    let expected = if from.is_empty() {
+        start
+    } else {
+        start.saturating_sub(1)
+    };
+    let last = lines.len() - from.len();
+    let matches_at = |pos: usize| {
+        lines[pos..pos + from.len()]
+            .iter()
+            .zip(from.iter())
+            .all(|(line, want)| line == want)
+    };
+    let position = (0..=last.max(expected))
+        .flat_map(|distance| {
+            let before = expected.checked_sub(distance);
+            let after = (distance > 0).then_some(expected + distance);
+            before.into_iter().chain(after)
+        })
+        .filter(|pos| *pos <= last)
+        .find(|pos| matches_at(*pos))
+        .context("hunk does not match the current content")?;
+
+    let reaches_end = position + from.len() == lines.len();
+    lines.splice(
+        position..position + from.len(),
+        to.iter().map(String::as_str),
+    );
+    let mut updated = lines.join("\n");
+    let trailing_newline = if reaches_end {
+        !to_missing_newline
+    } else {
+        had_newline
+    };
+    if trailing_newline && !lines.is_empty() {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::hunks;
+    use std::path::PathBuf;
+
+    fn single_hunk(diff: &str) -> Hunk {
+        hunks(diff).into_iter().next().expect("hunk")
+    }
+
+    #[test]
+    fn apply_hunk_writes_either_side_and_tolerates_offsets() {
+        let hunk = single_hunk(concat!("@@ -2,3 +2,3 @@\n", " b\n", "-c\n", "+C\n", " d\n",));
+
+        let destination = "a\nb\nc\nd\ne\n";
+        assert_eq!(
+            apply_hunk(destination, &hunk, HunkDirection::ToDestination).expect("apply"),
+            "a\nb\nC\nd\ne\n"
+        );
+
+        let shifted_source = "new\na\nb\nC\nd\ne\n";
+        assert_eq!(
+            apply_hunk(shifted_source, &hunk, HunkDirection::ToSource).expect("re-add"),
+            "new\na\nb\nc\nd\ne\n"
+        );
+
+        let err = apply_hunk("x\ny\n", &hunk, HunkDirection::ToDestination).expect_err("mismatch");
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn apply_hunk_respects_missing_trailing_newline() {
+        let hunk = single_hunk(concat!(
+            "@@ -1 +1 @@\n",
+            "-last\n",
+            "\\ No newline at end of file\n",
+            "+last\n",
+        ));
+        assert_eq!(
+            apply_hunk("last", &hunk, HunkDirection::ToDestination).expect("apply"),
+            "last\n"
+        );
+        assert_eq!(
+            apply_hunk("last\n", &hunk, HunkDirection::ToSource).expect("re-add"),
+            "last"
+        );
+    }
+
+    #[test]
+    fn partial_writes_are_limited_to_plain_files() {
+        assert_eq!(
+            source_write_blocker(&PathBuf::from("/src/private_dot_zshrc"), false),
+            None
+        );
+        assert_eq!(
+            source_write_blocker(&PathBuf::from("/src/dot_gitconfig.tmpl"), false),
+            Some("templates")
+        );
+        assert_eq!(
+            source_write_blocker(&PathBuf::from("/src/encrypted_dot_netrc.age"), false),
+            Some("encrypted files")
+        );
+        assert_eq!(
+            source_write_blocker(&PathBuf::from("/src/modify_dot_settings.json"), false),
+            Some("modify_ scripts")
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::app::App;
 use crate::domain::ActionRequest;
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
 use std::path::Path;
@@ -55,6 +55,9 @@ pub(crate) fn run_internal_ignore_action(app: &mut App, request: &ActionRequest)
         .target
         .as_deref()
         .context("ignore requires a target file or directory")?;
+    if app.config.read_only {
+        bail!("ignore is disabled in read-only mode");
+    }
 
     let is_dir = fs::symlink_metadata(target)
         .with_context(|| format!("failed to stat ignore target: {}", target.display()))?
@@ -9,10 +9,22 @@ use std::time::Instant;
 
 pub trait ChezmoiClient: Send + Sync {
     fn status(&self) -> Result<Vec<StatusEntry>>;
+    /// `chezmoi status` limited to `targets` (absolute paths); entries are
+    /// relative to the home directory like `status`.
+    fn status_paths(&self, targets: &[PathBuf]) -> Result<Vec<StatusEntry>>;
     fn managed(&self) -> Result<Vec<PathBuf>>;
     fn unmanaged(&self) -> Result<Vec<PathBuf>>;
+    /// Unmanaged paths at or below `dir`, which like every result of
+    /// `unmanaged` is relative to the working directory.
+    fn unmanaged_in(&self, dir: &Path) -> Result<Vec<PathBuf>>;
     fn diff(&self, target: Option<&Path>) -> Result<DiffText>;
     fn run(&self, request: &ActionRequest) -> Result<CommandResult>;
+    /// Source state paths for `targets`, in the same order.
+    fn source_paths(&self, targets: &[PathBuf]) -> Result<Vec<PathBuf>>;
+    /// Rendered target state of `target` (`chezmoi cat`).
+    fn cat(&self, target: &Path) -> Result<String>;
+    /// Root of the source state (`chezmoi source-path`).
+    fn source_dir(&self) -> Result<PathBuf>;
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +104,20 @@ impl ChezmoiClient for ShellChezmoiClient {
         parse_status_output(&result.stdout)
     }
 
+    fn status_paths(&self, targets: &[PathBuf]) -> Result<Vec<StatusEntry>> {
+        let mut args = vec![os("status"), os("--")];
+        args.extend(
+            targets
+                .iter()
+                .map(|target| target.as_os_str().to_os_string()),
+        );
+        let result = self.run_raw(&args, &self.home_dir)?;
+        if result.exit_code != 0 {
+            bail!("chezmoi status failed: {}", result.stderr.trim());
+        }
+        parse_status_output(&result.stdout)
+    }
+
     fn managed(&self) -> Result<Vec<PathBuf>> {
         let result = self.run_raw(["managed", "--format", "json"], &self.home_dir)?;
         if result.exit_code != 0 {
@@ -101,19 +127,14 @@ impl ChezmoiClient for ShellChezmoiClient {
     }
 
     fn unmanaged(&self) -> Result<Vec<PathBuf>> {
-        let use_home_destination = self.working_dir.starts_with(&self.home_dir);
+        let use_home_destination = self.uses_home_destination_for_unmanaged();
         let destination = if use_home_destination {
             &self.home_dir
         } else {
             &self.working_dir
         };
 
-        let result = self.run_raw(["unmanaged"], destination)?;
-        if result.exit_code != 0 {
-            bail!("chezmoi unmanaged failed: {}", result.stderr.trim());
-        }
-
-        let paths = parse_unmanaged_output(&result.stdout);
+        let paths = self.run_unmanaged(&[], destination)?;
         if use_home_destination {
             let mut scoped =
                 filter_unmanaged_to_working_dir(paths, &self.home_dir, &self.working_dir);
@@ -128,6 +149,20 @@ impl ChezmoiClient for ShellChezmoiClient {
         }
     }
 
+    fn unmanaged_in(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let target = self.working_dir.join(dir);
+        if self.uses_home_destination_for_unmanaged() {
+            let paths = self.run_unmanaged(&[target], &self.home_dir)?;
+            Ok(filter_unmanaged_to_working_dir(
+                paths,
+                &self.home_dir,
+                &self.working_dir,
+            ))
+        } else {
+            self.run_unmanaged(&[target], &self.working_dir)
+        }
+    }
+
     fn diff(&self, target: Option<&Path>) -> Result<DiffText> {
         let args = diff_args(target);
         let destination = self.destination_for_target(target);
@@ -148,29 +183,106 @@ impl ChezmoiClient for ShellChezmoiClient {
         let destination = self.destination_for_target(request.target.as_deref());
         self.run_raw(&args, destination)
     }
+
+    fn source_paths(&self, targets: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut args = vec![os("source-path"), os("--")];
+        args.extend(
+            targets
+                .iter()
+                .map(|target| target.as_os_str().to_os_string()),
+        );
+        let destination = self.destination_for_target(targets.first().map(PathBuf::as_path));
+
+        let result = self.run_raw(&args, destination)?;
+        if result.exit_code != 0 {
+            bail!("chezmoi source-path failed: {}", result.stderr.trim());
+        }
+        let paths: Vec<PathBuf> = result
+            .stdout
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if paths.len() != targets.len() {
+            bail!(
+                "chezmoi source-path returned {} paths for {} targets",
+                paths.len(),
+                targets.len()
+            );
+        }
+        Ok(paths)
+    }
+
+    fn cat(&self, target: &Path) -> Result<String> {
+        let args = vec![os("cat"), os("--"), target.as_os_str().to_os_string()];
+        let destination = self.destination_for_target(Some(target));
+
+        let result = self.run_raw(&args, destination)?;
+        if result.exit_code != 0 {
+            bail!("chezmoi cat failed: {}", result.stderr.trim());
+        }
+        Ok(result.stdout)
+    }
+
+    fn source_dir(&self) -> Result<PathBuf> {
+        let result = self.run_raw([os("source-path")], &self.home_dir)?;
+        if result.exit_code != 0 {
+            bail!("chezmoi source-path failed: {}", result.stderr.trim());
+        }
+        Ok(PathBuf::from(result.stdout.trim()))
+    }
 }
 
 impl ShellChezmoiClient {
+    /// `chezmoi unmanaged` runs against the home directory whenever the
+    /// working directory is inside it; results are then scoped back to the
+    /// working directory.
+    fn uses_home_destination_for_unmanaged(&self) -> bool {
+        self.working_dir.starts_with(&self.home_dir)
+    }
+
+    /// Runs `chezmoi unmanaged`, limited to `targets` unless empty.
+    fn run_unmanaged(&self, targets: &[PathBuf], destination: &Path) -> Result<Vec<PathBuf>> {
+        let mut args = vec![os("unmanaged")];
+        if !targets.is_empty() {
+            args.push(os("--"));
+            args.extend(
+                targets
+                    .iter()
+                    .map(|target| target.as_os_str().to_os_string()),
+            );
+        }
+        let result = self.run_raw(&args, destination)?;
+        if result.exit_code != 0 {
+            bail!("chezmoi unmanaged failed: {}", result.stderr.trim());
+        }
+        Ok(parse_unmanaged_output(&result.stdout))
+    }
+
     fn expand_working_root_entries_from_home(&self, scoped: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
         let mut merged: BTreeSet<PathBuf> = scoped
             .into_iter()
             .filter(|path| path != Path::new("."))
             .collect();
 
-        let mut home_results = Vec::new();
+        // One process for every child: the working root itself is
+        // unmanaged, so each child reports at most itself.
+        let mut children = Vec::new();
         let read_dir = std::fs::read_dir(&self.working_dir)
             .with_context(|| format!("failed to read {}", self.working_dir.display()))?;
         for entry in read_dir {
-            let child = entry
-                .with_context(|| format!("failed to read child in {}", self.working_dir.display()))?
-                .path();
-            let args = vec![os("unmanaged"), os("--"), child.into_os_string()];
-            let result = self.run_raw(&args, &self.home_dir)?;
-            if result.exit_code != 0 {
-                bail!("chezmoi unmanaged failed: {}", result.stderr.trim());
-            }
-            home_results.extend(parse_unmanaged_output(&result.stdout));
+            children.push(
+                entry
+                    .with_context(|| {
+                        format!("failed to read child in {}", self.working_dir.display())
+                    })?
+                    .path(),
+            );
         }
+        if children.is_empty() {
+            return Ok(merged.into_iter().collect());
+        }
+        let home_results = self.run_unmanaged(&children, &self.home_dir)?;
 
         let expanded =
             filter_unmanaged_to_working_dir(home_results, &self.home_dir, &self.working_dir);
@@ -346,12 +458,16 @@ fn required_target(target: Option<OsString>, action: Action) -> Result<OsString>
     target.with_context(|| format!("{} requires target", action.label()))
 }
 
+/// Hunks from this diff are written back (partial apply / re-add), which
+/// assumes `-` is the destination and `+` the target state, so a configured
+/// `diff.reverse` is overridden.
 fn diff_args(target: Option<&Path>) -> Vec<OsString> {
     let mut args = vec![
         os("diff"),
         os("--no-pager"),
         os("--use-builtin-diff"),
         os("--color=true"),
+        os("--reverse=false"),
     ];
     if let Some(path) = target {
         args.push(os("--"));
@@ -552,6 +668,7 @@ mod tests {
                 os("--no-pager"),
                 os("--use-builtin-diff"),
                 os("--color=true"),
+                os("--reverse=false"),
                 os("--"),
                 os("-n")
             ]
@@ -567,11 +684,23 @@ mod tests {
                 os("diff"),
                 os("--no-pager"),
                 os("--use-builtin-diff"),
-                os("--color=true")
+                os("--color=true"),
+                os("--reverse=false")
             ]
         );
     }
 
+    #[test]
+    fn diff_args_pin_the_direction_hunk_writes_expect() {
+        // A user's `diff.reverse = true` would swap the sides partial apply
+        // and re-add rely on.
+        for target in [None, Some(Path::new("/home/me/.zshrc"))] {
+            let got = diff_args(target);
+            assert!(got.contains(&os("--reverse=false")));
+            assert!(!got.contains(&os("--reverse")));
+        }
+    }
+
     #[test]
     fn default_client_uses_current_dir_for_working_destination() {
         let client = ShellChezmoiClient::default();
@@ -0,0 +1,266 @@
+use crate::app::PaneFocus;
+use anyhow::{Context, Result};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Percentage points moved per grow / shrink key press.
+pub(crate) const RESIZE_STEP: u16 = 5;
+const LIST_PERCENT_RANGE: (u16, u16) = (15, 80);
+const DETAIL_PERCENT_RANGE: (u16, u16) = (20, 85);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogPlacement {
+    /// Below the detail pane.
+    #[default]
+    Bottom,
+    /// In its own column right of the detail pane.
+    Right,
+    Hidden,
+}
+
+impl LogPlacement {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogPlacement::Bottom => "bottom",
+            LogPlacement::Right => "right",
+            LogPlacement::Hidden => "hidden",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            LogPlacement::Bottom => LogPlacement::Right,
+            LogPlacement::Right => LogPlacement::Hidden,
+            LogPlacement::Hidden => LogPlacement::Bottom,
+        }
+    }
+}
+
+/// Pane split ratios and log placement, saved between sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaneLayout {
+    /// Width of the list column, in percent of the screen.
+    pub list_percent: u16,
+    /// Share of the detail pane next to the log, in percent of the space
+    /// they split (height when the log is at the bottom, width when right).
+    pub detail_percent: u16,
+    pub log: LogPlacement,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            list_percent: 35,
+            detail_percent: 65,
+            log: LogPlacement::Bottom,
+        }
+    }
+}
+
+impl PaneLayout {
+    /// Grows (or shrinks) the focused pane by one step. Returns false when
+    /// already at the limit.
+    pub fn resize(&mut self, focus: PaneFocus, grow: bool) -> bool {
+        let before = *self;
+        let (list, detail) = match (focus, self.log) {
+            (PaneFocus::List, _) => (Some(grow), None),
+            (PaneFocus::Detail, LogPlacement::Hidden) => (Some(!grow), None),
+            (PaneFocus::Detail, _) => (None, Some(grow)),
+            (PaneFocus::Log, LogPlacement::Hidden) => (None, None),
+            (PaneFocus::Log, _) => (None, Some(!grow)),
+        };
+        if let Some(grow) = list {
+            self.list_percent = step(self.list_percent, grow, LIST_PERCENT_RANGE);
+        }
+        if let Some(grow) = detail {
+            self.detail_percent = step(self.detail_percent, grow, DETAIL_PERCENT_RANGE);
+        }
+        *self != before
+    }
+
+    /// Values from a hand-edited file pulled back into the supported ranges.
+    fn clamped(self) -> Self {
+        Self {
+            list_percent: self
+                .list_percent
+                .clamp(LIST_PERCENT_RANGE.0, LIST_PERCENT_RANGE.1),
+            detail_percent: self
+                .detail_percent
+                .clamp(DETAIL_PERCENT_RANGE.0, DETAIL_PERCENT_RANGE.1),
+            log: self.log,
+        }
+    }
+}
+
+fn step(value: u16, grow: bool, (min, max): (u16, u16)) -> u16 {
+    if grow {
+        (value + RESIZE_STEP).min(max)
+    } else {
+        value.saturating_sub(RESIZE_STEP).max(min)
+    }
+}
+
+/// Screen areas of the three panes; `None` for panes not shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PaneAreas {
+    pub list: Option<Rect>,
+    pub detail: Option<Rect>,
+    pub log: Option<Rect>,
+}
+
+/// Splits `area` according to `layout`; a zoomed pane takes all of it.
+pub(crate) fn pane_areas(area: Rect, layout: PaneLayout, zoom: Option<PaneFocus>) -> PaneAreas {
+    let layout = layout.clamped();
+    match zoom {
+        Some(PaneFocus::List) => {
+            return PaneAreas {
+                list: Some(area),
+                detail: None,
+                log: None,
+            };
+        }
+        Some(PaneFocus::Detail) => {
+            return PaneAreas {
+                list: None,
+                detail: Some(area),
+                log: None,
+            };
+        }
+        Some(PaneFocus::Log) if layout.log != LogPlacement::Hidden => {
+            return PaneAreas {
+                list: None,
+                detail: None,
+                log: Some(area),
+            };
+        }
+        _ => {}
+    }
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(layout.list_percent),
+            Constraint::Percentage(100 - layout.list_percent),
+        ])
+        .split(area);
+    let split = |direction| {
+        Layout::default()
+            .direction(direction)
+            .constraints([
+                Constraint::Percentage(layout.detail_percent),
+                Constraint::Percentage(100 - layout.detail_percent),
+            ])
+            .split(columns[1])
+    };
+    let (detail, log) = match layout.log {
+        LogPlacement::Hidden => (columns[1], None),
+        LogPlacement::Bottom => {
+            let rows = split(Direction::Vertical);
+            (rows[0], Some(rows[1]))
+        }
+        LogPlacement::Right => {
+            let right = split(Direction::Horizontal);
+            (right[0], Some(right[1]))
+        }
+    };
+    PaneAreas {
+        list: Some(columns[0]),
+        detail: Some(detail),
+        log,
+    }
+}
+
+pub(crate) fn default_layout_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("chezmoi-tui").join("layout.json"))
+}
+
+/// Loads the saved layout; a missing or unreadable file yields `fallback`.
+pub(crate) fn load_layout(path: &Path, fallback: PaneLayout) -> PaneLayout {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<PaneLayout>(&content).ok())
+        .unwrap_or(fallback)
+        .clamped()
+}
+
+pub(crate) fn save_layout(path: &Path, layout: &PaneLayout) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut content = serde_json::to_string_pretty(layout).context("failed to encode layout")?;
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_moves_the_shared_border_of_the_focused_pane() {
+        let mut layout = PaneLayout::default();
+        assert!(layout.resize(PaneFocus::List, true));
+        assert_eq!(layout.list_percent, 40);
+        assert!(layout.resize(PaneFocus::Log, true));
+        assert_eq!(layout.detail_percent, 60);
+
+        layout.log = LogPlacement::Hidden;
+        assert!(!layout.resize(PaneFocus::Log, true));
+        assert!(layout.resize(PaneFocus::Detail, true));
+        assert_eq!(layout.list_percent, 35);
+
+        layout.list_percent = 80;
+        assert!(!layout.resize(PaneFocus::List, true));
+    }
+
+    #[test]
+    fn pane_areas_follow_log_placement_and_zoom() {
+        let area = Rect::new(0, 0, 100, 40);
+        let mut layout = PaneLayout::default();
+        let areas = pane_areas(area, layout, None);
+        assert_eq!(areas.list, Some(Rect::new(0, 0, 35, 40)));
+        assert_eq!(areas.detail, Some(Rect::new(35, 0, 65, 26)));
+        assert_eq!(areas.log, Some(Rect::new(35, 26, 65, 14)));
+
+        layout.log = LogPlacement::Right;
+        let areas = pane_areas(area, layout, None);
+        assert_eq!(areas.log.map(|log| log.height), Some(40));
+
+        layout.log = LogPlacement::Hidden;
+        let areas = pane_areas(area, layout, None);
+        assert_eq!(areas.detail, Some(Rect::new(35, 0, 65, 40)));
+        assert_eq!(areas.log, None);
+
+        let zoomed = pane_areas(area, layout, Some(PaneFocus::Detail));
+        assert_eq!(zoomed.detail, Some(area));
+        assert_eq!(zoomed.list, None);
+    }
+
+    #[test]
+    fn saved_layout_overrides_config_and_is_clamped() {
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_layout_{}_{}.json",
+            std::process::id(),
+            line!()
+        ));
+        let fallback = PaneLayout {
+            log: LogPlacement::Right,
+            ..PaneLayout::default()
+        };
+        assert_eq!(load_layout(&path, fallback), fallback);
+
+        fs::write(&path, r#"{"list_percent": 95, "log": "hidden"}"#).expect("write");
+        let loaded = load_layout(&path, fallback);
+        assert_eq!(loaded.list_percent, 80);
+        assert_eq!(loaded.log, LogPlacement::Hidden);
+
+        save_layout(&path, &PaneLayout::default()).expect("save");
+        assert_eq!(load_layout(&path, fallback), PaneLayout::default());
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,38 +1,106 @@
 mod actions;
 mod app;
+mod audit;
 mod backend;
+mod binary;
+mod chattr;
 mod config;
+mod diff;
 mod domain;
+mod exclude;
+mod fuzzy;
 mod handlers;
+mod highlight;
+mod hunk;
 mod ignore;
 mod infra;
+mod layout;
+mod merge;
+mod metadata;
+mod mouse;
+mod plan;
+mod policy;
 mod preview;
+mod repo_diff;
+mod search;
+mod sort;
 mod terminal;
+mod theme;
 mod ui;
+mod unmanaged_index;
+mod watch;
 
 use crate::actions::{run_foreground_action, send_task};
 use crate::app::{App, BackendEvent, BackendTask};
+use crate::audit::AuditLog;
 use crate::backend::worker_loop;
 use crate::config::AppConfig;
-use crate::handlers::{handle_backend_event, handle_key_event};
+use crate::handlers::{
+    handle_backend_event, handle_focus_gained, handle_key_event, handle_paste, run_queued_refresh,
+};
 use crate::infra::{ChezmoiClient, ShellChezmoiClient};
+use crate::layout::default_layout_path;
+use crate::metadata::process_umask;
+use crate::mouse::handle_mouse_event;
+use crate::plan::load_plan;
+use crate::sort::default_view_orders_path;
 use crate::terminal::{restore_terminal, setup_terminal};
-use anyhow::{Context, Result};
+use crate::unmanaged_index::default_index_path;
+use crate::watch::{WatchSet, watch_loop};
+use anyhow::{Context, Result, bail};
 use crossterm::event::{self, Event, KeyEventKind};
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// Upper bound on terminal events handled between two frames, so a flood
+/// of input cannot starve rendering.
+const MAX_EVENTS_PER_FRAME: usize = 64;
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    setup_terminal()?;
+    let mut config = match AppConfig::default_path() {
+        Some(path) => AppConfig::load(&path)?,
+        None => AppConfig::default(),
+    };
+    let startup_plan = apply_cli_args(&mut config, std::env::args().skip(1))?;
+    if config.audit_log_path.is_none() {
+        config.audit_log_path = AuditLog::default_path();
+    }
+    if config.umask.is_none() {
+        config.umask = Some(process_umask());
+    }
+    if config.view_orders_path.is_none() {
+        config.view_orders_path = default_view_orders_path();
+    }
+    if config.layout_path.is_none() {
+        config.layout_path = default_layout_path();
+    }
+    if config.unmanaged_index_path.is_none() {
+        config.unmanaged_index_path = default_index_path();
+    }
+
+    let mut app = App::new(config);
+    if let Some(path) = startup_plan {
+        app.action_queue = load_plan(&path, &app.home_dir)?;
+        app.log(format!(
+            "plan loaded: {} ({} item(s)); review and press Enter to run",
+            path.display(),
+            app.action_queue.len()
+        ));
+        app.open_queue();
+    }
+
+    setup_terminal(app.config.mouse)?;
     let mut terminal =
         Terminal::new(CrosstermBackend::new(io::stdout())).context("failed to create terminal")?;
 
-    let run_result = run_app(&mut terminal, AppConfig::default());
+    let run_result = run_app(&mut terminal, app);
 
     restore_terminal(&mut terminal)?;
     if let Err(err) = run_result {
@@ -43,36 +111,109 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, config: AppConfig) -> Result<()> {
-    let mut app = App::new(config);
+/// Command-line flags override the config file. Returns a plan file to load
+/// into the action queue at startup, if one was given.
+fn apply_cli_args(
+    config: &mut AppConfig,
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<PathBuf>> {
+    let mut plan = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--read-only" => config.read_only = true,
+            "--plan" => {
+                let Some(path) = args.next() else {
+                    bail!("--plan requires a file path");
+                };
+                let path = PathBuf::from(path);
+                config.plan_path = Some(path.clone());
+                plan = Some(path);
+            }
+            other => bail!("unknown argument: {other} (supported: --read-only, --plan <file>)"),
+        }
+    }
+    Ok(plan)
+}
+
+fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut app: App) -> Result<()> {
     let client: Arc<dyn ChezmoiClient> = Arc::new(ShellChezmoiClient::default());
 
     let (task_tx, task_rx) = mpsc::unbounded_channel::<BackendTask>();
     let (event_tx, mut event_rx) = mpsc::unbounded_channel::<BackendEvent>();
 
-    tokio::spawn(worker_loop(client, task_rx, event_tx));
+    let (watch_tx, watch_rx) = tokio::sync::watch::channel(WatchSet::default());
+    if app.config.watch {
+        tokio::spawn(watch_loop(client.clone(), watch_rx, event_tx.clone()));
+    }
+    tokio::spawn(worker_loop(client, task_rx, event_tx, app.config.read_only));
 
     send_task(&mut app, &task_tx, BackendTask::RefreshAll)?;
 
+    // Frames are drawn only after something changed; queued terminal events
+    // are drained first so a burst (resize drag, key repeat) costs one frame.
+    let mut redraw = true;
     while !app.should_quit {
         while let Ok(event) = event_rx.try_recv() {
             handle_backend_event(&mut app, &task_tx, event)?;
+            redraw = true;
         }
+        if let Some(set) = app.take_watch_set_update() {
+            watch_tx.send_replace(set);
+        }
+        if let Some(job) = app.take_unmanaged_index_job() {
+            task_tx
+                .send(BackendTask::IndexUnmanaged { job })
+                .context("failed to dispatch index job")?;
+            redraw = true;
+        }
+        run_queued_refresh(&mut app, &task_tx)?;
 
         if let Some(request) = app.pending_foreground.take() {
             run_foreground_action(terminal, &mut app, &task_tx, &request)?;
+            redraw = true;
         }
 
-        app.flush_staged_filter(Instant::now());
-        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+        redraw |= app.flush_staged_filter(Instant::now());
+        if redraw {
+            terminal.draw(|frame| ui::draw(frame, &mut app))?;
+            redraw = false;
+        }
 
-        if event::poll(Duration::from_millis(100)).context("event poll failed")?
-            && let Event::Key(key) = event::read().context("event read failed")?
-            && key.kind == KeyEventKind::Press
-        {
-            handle_key_event(&mut app, key, &task_tx)?;
+        if event::poll(Duration::from_millis(100)).context("event poll failed")? {
+            for _ in 0..MAX_EVENTS_PER_FRAME {
+                let event = event::read().context("event read failed")?;
+                handle_terminal_event(&mut app, event, &task_tx)?;
+                redraw = true;
+                if app.should_quit
+                    || app.pending_foreground.is_some()
+                    || !event::poll(Duration::ZERO).context("event poll failed")?
+                {
+                    break;
+                }
+            }
         }
     }
 
     Ok(())
 }
+
+fn handle_terminal_event(
+    app: &mut App,
+    event: Event,
+    task_tx: &mpsc::UnboundedSender<BackendTask>,
+) -> Result<()> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            handle_key_event(app, key, task_tx)?;
+        }
+        Event::Mouse(mouse) => handle_mouse_event(app, mouse, task_tx)?,
+        Event::Resize(width, height) => {
+            let panes = ui::main_panes(app, Rect::new(0, 0, width, height));
+            app.clamp_scroll_to(&panes);
+        }
+        Event::FocusGained => handle_focus_gained(app, task_tx)?,
+        Event::Paste(text) => handle_paste(app, &text),
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+
+/// Upper bound on the LCS table size (lines × lines) after trimming the common
+/// prefix and suffix, to keep the built-in merge responsive.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Resolution {
+    /// Keep the destination lines.
+    Ours,
+    /// Keep the source lines.
+    Theirs,
+    /// Destination lines followed by source lines.
+    Both,
+}
+
+impl Resolution {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Resolution::Ours => "destination",
+            Resolution::Theirs => "source",
+            Resolution::Both => "both",
+        }
+    }
+}
+
+/// A region where destination and/or source differ from the base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MergeChange {
+    pub ours: Vec<String>,
+    pub base: Vec<String>,
+    pub theirs: Vec<String>,
+    /// Both sides changed the base differently.
+    pub conflict: bool,
+    pub resolution: Option<Resolution>,
+}
+
+impl MergeChange {
+    fn resolved_lines(&self) -> Option<Vec<&str>> {
+        let ours = self.ours.iter().map(String::as_str);
+        let theirs = self.theirs.iter().map(String::as_str);
+        Some(match self.resolution? {
+            Resolution::Ours => ours.collect(),
+            Resolution::Theirs => theirs.collect(),
+            Resolution::Both => ours.chain(theirs).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MergeBlock {
+    Stable(Vec<String>),
+    Change(MergeChange),
+}
+
+/// State of the built-in three-way merge screen. "Ours" is the destination
+/// file, "theirs" the source file and the base is the rendered target state;
+/// the result is written back to the source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MergeView {
+    pub target: PathBuf,
+    pub source: PathBuf,
+    pub blocks: Vec<MergeBlock>,
+    /// Index into the change blocks, not into `blocks`.
+    pub selected: usize,
+    pub message: Option<String>,
+    pub protected_by: Option<String>,
+    /// Phrase typed so far while confirming a save to a protected target.
+    pub confirm: Option<String>,
+    trailing_newline: bool,
+}
+
+impl MergeView {
+    pub(crate) fn new(
+        target: PathBuf,
+        source: PathBuf,
+        ours: &str,
+        base: &str,
+        theirs: &str,
+    ) -> Result<Self> {
+        let blocks = merge3(&split(ours), &split(base), &split(theirs))?;
+        let mut view = Self {
+            target,
+            source,
+            blocks,
+            selected: 0,
+            message: None,
+            protected_by: None,
+            confirm: None,
+            trailing_newline: theirs.is_empty() || theirs.ends_with('\n'),
+        };
+        // Start on the first conflict rather than on an already resolved block.
+        let first_unresolved = view
+            .changes()
+            .position(|change| change.resolution.is_none());
+        view.selected = first_unresolved.unwrap_or(0);
+        Ok(view)
+    }
+
+    pub(crate) fn changes(&self) -> impl Iterator<Item = &MergeChange> {
+        self.blocks.iter().filter_map(|block| match block {
+            MergeBlock::Change(change) => Some(change),
+            MergeBlock::Stable(_) => None,
+        })
+    }
+
+    pub(crate) fn change_count(&self) -> usize {
+        self.changes().count()
+    }
+
+    pub(crate) fn conflict_count(&self) -> usize {
+        self.changes().filter(|change| change.conflict).count()
+    }
+
+    pub(crate) fn unresolved_count(&self) -> usize {
+        self.changes()
+            .filter(|change| change.resolution.is_none())
+            .count()
+    }
+
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let count = self.change_count();
+        if count == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            self.selected.checked_sub(1).unwrap_or(count - 1)
+        };
+    }
+
+    pub(crate) fn resolve(&mut self, resolution: Resolution) {
+        let selected = self.selected;
+        if let Some(change) = self
+            .blocks
+            .iter_mut()
+            .filter_map(|block| match block {
+                MergeBlock::Change(change) => Some(change),
+                MergeBlock::Stable(_) => None,
+            })
+            .nth(selected)
+        {
+            change.resolution = Some(resolution);
+        }
+    }
+
+    /// Phrase typed to confirm writing the source of a protected target.
+    pub(crate) fn confirmation_phrase(&self) -> String {
+        format!("MERGE {}", self.target.display())
+    }
+
+    /// Merged file content, or `None` while any block is unresolved.
+    pub(crate) fn result(&self) -> Option<String> {
+        let mut lines: Vec<&str> = Vec::new();
+        for block in &self.blocks {
+            match block {
+                MergeBlock::Stable(stable) => lines.extend(stable.iter().map(String::as_str)),
+                MergeBlock::Change(change) => lines.extend(change.resolved_lines()?),
+            }
+        }
+        let mut content = lines.join("\n");
+        if self.trailing_newline && !lines.is_empty() {
+            content.push('\n');
+        }
+        Some(content)
+    }
+}
+
+fn split(text: &str) -> Vec<&str> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    text.strip_suffix('\n')
+        .unwrap_or(text)
+        .split('\n')
+        .collect()
+}
+
+/// For each line of `a`, the index of the matching line in `b` under a longest
+/// common subsequence.
+fn lcs_matches(a: &[&str], b: &[&str]) -> Result<Vec<Option<usize>>> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_LCS_CELLS {
+        bail!(
+            "file too large for the built-in merge ({} x {} changed lines)",
+            a_mid.len(),
+            b_mid.len()
+        );
+    }
+
+    let mut matches = vec![None; a.len()];
+    for (index, slot) in matches.iter_mut().enumerate().take(prefix) {
+        *slot = Some(index);
+    }
+    for offset in 0..suffix {
+        matches[a.len() - suffix + offset] = Some(b.len() - suffix + offset);
+    }
+
+    let (n, m) = (a_mid.len(), b_mid.len());
+    let width = m + 1;
+    let mut table = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * width + j] = if a_mid[i] == b_mid[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a_mid[i] == b_mid[j] {
+            matches[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    Ok(matches)
+}
+
+/// diff3-style merge: base lines matched on both sides are stable, everything
+/// in between is a change block. Blocks changed on one side only (or the same
+/// way on both) come pre-resolved; the rest are conflicts.
+fn merge3(ours: &[&str], base: &[&str], theirs: &[&str]) -> Result<Vec<MergeBlock>> {
+    let to_ours = lcs_matches(base, ours)?;
+    let to_theirs = lcs_matches(base, theirs)?;
+    let owned = |lines: &[&str]| {
+        lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let mut blocks = Vec::new();
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        let next =
+            (b..base.len()).find_map(|index| Some((index, to_ours[index]?, to_theirs[index]?)));
+        let (next_b, next_o, next_t) = next.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        if next_b > b || next_o > o || next_t > t {
+            let ours_part = &ours[o..next_o];
+            let base_part = &base[b..next_b];
+            let theirs_part = &theirs[t..next_t];
+            let (conflict, resolution) = if ours_part == base_part {
+                (false, Some(Resolution::Theirs))
+            } else if theirs_part == base_part || ours_part == theirs_part {
+                (false, Some(Resolution::Ours))
+            } else {
+                (true, None)
+            };
+            blocks.push(MergeBlock::Change(MergeChange {
+                ours: owned(ours_part),
+                base: owned(base_part),
+                theirs: owned(theirs_part),
+                conflict,
+                resolution,
+            }));
+        }
+        if next.is_none() {
+            break;
+        }
+
+        let (mut sb, mut so, mut st) = (next_b, next_o, next_t);
+        let mut stable = Vec::new();
+        while sb < base.len() && to_ours[sb] == Some(so) && to_theirs[sb] == Some(st) {
+            stable.push(base[sb].to_string());
+            sb += 1;
+            so += 1;
+            st += 1;
+        }
+        blocks.push(MergeBlock::Stable(stable));
+        (b, o, t) = (sb, so, st);
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(ours: &str, base: &str, theirs: &str) -> MergeView {
+        MergeView::new(
+            PathBuf::from("/home/u/.zshrc"),
+            PathBuf::from("/src/dot_zshrc"),
+            ours,
+            base,
+            theirs,
+        )
+        .expect("merge")
+    }
+
+    #[test]
+    fn one_sided_changes_merge_cleanly() {
+        let merge = view("a\nB\nc\nd\n", "a\nb\nc\nd\n", "a\nb\nc\nD\n");
+        assert_eq!(merge.change_count(), 2);
+        assert_eq!(merge.conflict_count(), 0);
+        assert_eq!(merge.result().as_deref(), Some("a\nB\nc\nD\n"));
+    }
+
+    #[test]
+    fn conflicts_need_a_resolution_per_block() {
+        let mut merge = view("a\nours\nc\n", "a\nbase\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(merge.conflict_count(), 1);
+        assert_eq!(merge.unresolved_count(), 1);
+        assert_eq!(merge.result(), None);
+
+        merge.resolve(Resolution::Both);
+        assert_eq!(merge.result().as_deref(), Some("a\nours\ntheirs\nc\n"));
+        merge.resolve(Resolution::Theirs);
+        assert_eq!(merge.result().as_deref(), Some("a\ntheirs\nc\n"));
+    }
+
+    #[test]
+    fn selection_starts_on_first_unresolved_block_and_wraps() {
+        let mut merge = view("X\nb\nY\n", "a\nb\nc\n", "a\nb\nZ\n");
+        assert_eq!(merge.change_count(), 2);
+        assert_eq!(merge.selected, 1);
+        merge.move_selection(true);
+        assert_eq!(merge.selected, 0);
+        merge.move_selection(false);
+        assert_eq!(merge.selected, 1);
+    }
+}
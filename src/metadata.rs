@@ -0,0 +1,376 @@
+use crate::chattr::{ChattrAttribute, SourceInfo};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Umask assumed when neither the config nor the process supplies one.
+pub(crate) const DEFAULT_UMASK: u32 = 0o022;
+
+/// Largest buffer offered to `getpwuid_r` / `getgrgid_r`; groups with many
+/// members can need more than the initial size.
+const MAX_ID_LOOKUP_BUFFER: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+impl FileKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            FileKind::File => "file",
+            FileKind::Directory => "directory",
+            FileKind::Symlink => "symlink",
+            FileKind::Other => "special",
+        }
+    }
+}
+
+/// Destination file attributes shown above the preview. The entry itself is
+/// described, not a symlink's target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileMetadata {
+    pub kind: FileKind,
+    /// Permission bits only (`0o7777`).
+    pub mode: u32,
+    pub owner: String,
+    pub group: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub symlink_target: Option<PathBuf>,
+}
+
+impl FileMetadata {
+    pub(crate) fn is_executable(&self) -> bool {
+        self.kind == FileKind::File && self.mode & 0o111 != 0
+    }
+}
+
+/// Source state entry of a managed target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceDetails {
+    pub path: PathBuf,
+    pub info: SourceInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EntryMetadata {
+    pub file: FileMetadata,
+    pub source: Option<SourceDetails>,
+}
+
+pub(crate) fn read_file_metadata(path: &Path) -> Result<FileMetadata> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("failed to read metadata: {}", path.display()))?;
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_symlink() {
+        FileKind::Symlink
+    } else if file_type.is_dir() {
+        FileKind::Directory
+    } else if file_type.is_file() {
+        FileKind::File
+    } else {
+        FileKind::Other
+    };
+    let symlink_target = if kind == FileKind::Symlink {
+        fs::read_link(path).ok()
+    } else {
+        None
+    };
+
+    Ok(FileMetadata {
+        kind,
+        mode: metadata.permissions().mode() & 0o7777,
+        owner: id_name(IdKind::User, metadata.uid()).unwrap_or_else(|| metadata.uid().to_string()),
+        group: id_name(IdKind::Group, metadata.gid()).unwrap_or_else(|| metadata.gid().to_string()),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        symlink_target,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum IdKind {
+    User,
+    Group,
+}
+
+/// Looked-up names, `None` for ids the system does not know.
+type IdNames = HashMap<(IdKind, u32), Option<String>>;
+
+/// Name of user or group `id` as the system resolves it, NSS and directory
+/// services included. Answers are kept for the session: lookups can go over
+/// the network and every preview asks again.
+fn id_name(kind: IdKind, id: u32) -> Option<String> {
+    static NAMES: OnceLock<Mutex<IdNames>> = OnceLock::new();
+    let names = NAMES.get_or_init(Mutex::default);
+    if let Some(name) = names.lock().ok()?.get(&(kind, id)) {
+        return name.clone();
+    }
+    let name = match kind {
+        IdKind::User => user_name(id),
+        IdKind::Group => group_name(id),
+    };
+    names.lock().ok()?.insert((kind, id), name.clone());
+    name
+}
+
+fn user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: `passwd` is plain data that `getpwuid_r` fills in.
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut found = std::ptr::null_mut();
+        // SAFETY: every pointer is valid for the call and `buffer.len()` is
+        // the size of the buffer passed.
+        let code = unsafe {
+            libc::getpwuid_r(
+                uid,
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut found,
+            )
+        };
+        if code == libc::ERANGE && buffer.len() < MAX_ID_LOOKUP_BUFFER {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || found.is_null() || entry.pw_name.is_null() {
+            return None;
+        }
+        // SAFETY: on success `pw_name` is a NUL-terminated string in `buffer`.
+        let name = unsafe { CStr::from_ptr(entry.pw_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+fn group_name(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: `group` is plain data that `getgrgid_r` fills in.
+        let mut entry: libc::group = unsafe { std::mem::zeroed() };
+        let mut found = std::ptr::null_mut();
+        // SAFETY: every pointer is valid for the call and `buffer.len()` is
+        // the size of the buffer passed.
+        let code = unsafe {
+            libc::getgrgid_r(
+                gid,
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut found,
+            )
+        };
+        if code == libc::ERANGE && buffer.len() < MAX_ID_LOOKUP_BUFFER {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || found.is_null() || entry.gr_name.is_null() {
+            return None;
+        }
+        // SAFETY: on success `gr_name` is a NUL-terminated string in `buffer`.
+        let name = unsafe { CStr::from_ptr(entry.gr_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+/// The process umask, which chezmoi applies unless its config sets `umask`.
+/// Reading it means setting it, so this runs once at startup before any
+/// other thread creates files.
+pub(crate) fn process_umask() -> u32 {
+    // SAFETY: `umask` only swaps the file mode creation mask; the old mask is
+    // restored straight away.
+    let mask = unsafe { libc::umask(DEFAULT_UMASK as libc::mode_t) };
+    // SAFETY: as above.
+    unsafe { libc::umask(mask) };
+    // `mode_t` is `u16` on macOS.
+    #[allow(clippy::useless_conversion)]
+    u32::from(mask)
+}
+
+/// `rwxr-xr-x`-style rendering of permission bits, with the type letter.
+pub(crate) fn mode_string(kind: FileKind, mode: u32) -> String {
+    let type_char = match kind {
+        FileKind::Directory => 'd',
+        FileKind::Symlink => 'l',
+        FileKind::File => '-',
+        FileKind::Other => '?',
+    };
+    let mut text = String::from(type_char);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    text
+}
+
+/// Ways the destination's permissions disagree with the mode chezmoi would
+/// write for the source name's `private_` / `executable_` prefixes under
+/// `umask`; these show up as `M` status rows. Directories are only checked
+/// when they are `exact_` or `private_`.
+pub(crate) fn permission_mismatches(
+    file: &FileMetadata,
+    source: &SourceInfo,
+    umask: u32,
+) -> Vec<String> {
+    if file.kind == FileKind::Symlink || source.is_script {
+        return Vec::new();
+    }
+
+    let mut mismatches = Vec::new();
+    let private = source.attributes.contains(&ChattrAttribute::Private);
+    let directory = file.kind == FileKind::Directory;
+    if !directory || private || source.attributes.contains(&ChattrAttribute::Exact) {
+        let executable = source.attributes.contains(&ChattrAttribute::Executable);
+        let full = if directory || executable {
+            0o777
+        } else {
+            0o666
+        };
+        let expected = full & !umask & if private { 0o700 } else { 0o777 };
+        // Group and other bits; execute bits of files are checked below.
+        let bits = if directory { 0o077 } else { 0o066 };
+        if file.mode & bits != expected & bits {
+            mismatches.push(if private {
+                format!(
+                    "source is private_ but the destination is {:04o} (group/other access)",
+                    file.mode
+                )
+            } else if file.mode & 0o077 == 0 {
+                format!(
+                    "destination is {:04o} (owner only) but the source has no private_ prefix",
+                    file.mode
+                )
+            } else {
+                format!(
+                    "destination is {:04o} but chezmoi writes {expected:04o} with umask {umask:03o}",
+                    file.mode
+                )
+            });
+        }
+    }
+
+    if file.kind == FileKind::File {
+        let executable = source.attributes.contains(&ChattrAttribute::Executable);
+        if executable && !file.is_executable() {
+            mismatches
+                .push("source is executable_ but the destination has no execute bit".to_string());
+        } else if !executable && file.is_executable() {
+            mismatches.push(
+                "destination is executable but the source has no executable_ prefix".to_string(),
+            );
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chattr::source_info;
+
+    fn file(mode: u32) -> FileMetadata {
+        FileMetadata {
+            kind: FileKind::File,
+            mode,
+            owner: "u".to_string(),
+            group: "u".to_string(),
+            size: 0,
+            modified: None,
+            symlink_target: None,
+        }
+    }
+
+    #[test]
+    fn permission_mismatches_compare_mode_with_source_prefixes() {
+        let private_exec = source_info(Path::new("/src/private_executable_dot_run"), false);
+        assert!(permission_mismatches(&file(0o700), &private_exec, 0o022).is_empty());
+
+        let mismatches = permission_mismatches(&file(0o644), &private_exec, 0o022);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("0644"));
+        assert!(mismatches[1].contains("execute bit"));
+
+        let plain = source_info(Path::new("/src/dot_zshrc"), false);
+        assert!(permission_mismatches(&file(0o644), &plain, 0o022).is_empty());
+        let mismatches = permission_mismatches(&file(0o755), &plain, 0o022);
+        assert_eq!(
+            mismatches,
+            vec!["destination is executable but the source has no executable_ prefix"]
+        );
+    }
+
+    #[test]
+    fn permission_mismatches_follow_the_umask() {
+        let plain = source_info(Path::new("/src/dot_zshrc"), false);
+        assert!(permission_mismatches(&file(0o600), &plain, 0o077).is_empty());
+        assert_eq!(
+            permission_mismatches(&file(0o600), &plain, 0o022),
+            vec!["destination is 0600 (owner only) but the source has no private_ prefix"]
+        );
+        assert_eq!(
+            permission_mismatches(&file(0o664), &plain, 0o022),
+            vec!["destination is 0664 but chezmoi writes 0644 with umask 022"]
+        );
+
+        let directory = |mode| FileMetadata {
+            kind: FileKind::Directory,
+            ..file(mode)
+        };
+        let plain_dir = source_info(Path::new("/src/dot_config"), false);
+        assert!(permission_mismatches(&directory(0o700), &plain_dir, 0o022).is_empty());
+        let exact_dir = source_info(Path::new("/src/exact_dot_config"), false);
+        assert_eq!(
+            permission_mismatches(&directory(0o700), &exact_dir, 0o022).len(),
+            1
+        );
+        assert!(permission_mismatches(&directory(0o700), &exact_dir, 0o077).is_empty());
+    }
+
+    #[test]
+    fn id_names_come_from_the_system_databases() {
+        assert_eq!(id_name(IdKind::User, 0).as_deref(), Some("root"));
+        assert_eq!(id_name(IdKind::User, 0).as_deref(), Some("root"));
+        assert!(id_name(IdKind::Group, 0).is_some());
+        assert_eq!(id_name(IdKind::User, u32::MAX - 1), None);
+    }
+
+    #[test]
+    fn read_file_metadata_describes_links_without_following_them() {
+        let root = std::env::temp_dir().join(format!(
+            "chezmoi_tui_metadata_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("create root");
+        let target = root.join("script.sh");
+        fs::write(&target, "echo hi\n").expect("write");
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o750)).expect("chmod");
+        let link = root.join("link");
+        std::os::unix::fs::symlink(&target, &link).expect("symlink");
+
+        let meta = read_file_metadata(&target).expect("file metadata");
+        assert_eq!(meta.kind, FileKind::File);
+        assert_eq!(mode_string(meta.kind, meta.mode), "-rwxr-x---");
+        assert_eq!(meta.size, 8);
+        assert!(meta.is_executable());
+
+        let meta = read_file_metadata(&link).expect("link metadata");
+        assert_eq!(meta.kind, FileKind::Symlink);
+        assert_eq!(meta.symlink_target, Some(target));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::actions::send_task;
+use crate::app::{App, BackendTask, ModalState, PaneFocus};
+use crate::domain::ListView;
+use crate::handlers::handle_key_event;
+use crate::preview::maybe_enqueue_auto_detail;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Lines moved per wheel notch.
+const WHEEL_LINES: usize = 3;
+/// Width of the list's `▶ ` highlight symbol, reserved on every row.
+const LIST_HIGHLIGHT_WIDTH: u16 = 2;
+
+/// Clickable footer hint, with the key text it was rendered from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HintRegion {
+    pub area: Rect,
+    pub key: &'static str,
+}
+
+/// Where the last frame put each pane, for mapping mouse positions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ScreenLayout {
+    pub list: Rect,
+    pub detail: Rect,
+    pub log: Rect,
+    pub hints: Vec<HintRegion>,
+}
+
+/// Clicks select list rows, toggle fold markers, focus panes and run footer
+/// hints; the wheel scrolls whichever pane is under the cursor. Modals are
+/// keyboard-only, so mouse input is ignored while one is open.
+pub(crate) fn handle_mouse_event(
+    app: &mut App,
+    mouse: MouseEvent,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    if !matches!(app.modal, ModalState::None) {
+        return Ok(());
+    }
+    let position = Position::new(mouse.column, mouse.row);
+    let screen = app.screen.clone();
+
+    match mouse.kind {
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let down = mouse.kind == MouseEventKind::ScrollDown;
+            if screen.list.contains(position) {
+                let rows = usize::from(screen.list.height.saturating_sub(2));
+                if app.scroll_list(down, WHEEL_LINES, rows) {
+                    maybe_enqueue_auto_detail(app, task_tx)?;
+                }
+            } else if screen.detail.contains(position) {
+                if down {
+                    app.scroll_detail_down(WHEEL_LINES);
+                } else {
+                    app.scroll_detail_up(WHEEL_LINES);
+                }
+            } else if screen.log.contains(position) {
+                if down {
+                    app.scroll_log_down(WHEEL_LINES);
+                } else {
+                    app.scroll_log_up(WHEEL_LINES);
+                }
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(region) = screen
+                .hints
+                .iter()
+                .find(|region| region.area.contains(position))
+            {
+                for key in hint_key_events(region.key, app.view) {
+                    handle_key_event(app, key, task_tx)?;
+                }
+            } else if screen.list.contains(position) {
+                app.focus = PaneFocus::List;
+                click_list_row(app, &screen.list, position, task_tx)?;
+            } else if screen.detail.contains(position) {
+                app.focus = PaneFocus::Detail;
+            } else if screen.log.contains(position) {
+                app.focus = PaneFocus::Log;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn click_list_row(
+    app: &mut App,
+    list: &Rect,
+    position: Position,
+    task_tx: &UnboundedSender<BackendTask>,
+) -> Result<()> {
+    let inner_top = list.y + 1;
+    let inner_bottom = list.y + list.height.saturating_sub(1);
+    if position.y < inner_top || position.y >= inner_bottom {
+        return Ok(());
+    }
+    let index = app.list_scroll() + usize::from(position.y - inner_top);
+    if index >= app.current_len() {
+        return Ok(());
+    }
+
+    let mut changed = app.select_visible_index(index);
+    let text_start = list.x + 1 + LIST_HIGHLIGHT_WIDTH;
+    if let Some(column) = position.x.checked_sub(text_start)
+        && app.is_fold_marker_at(index, usize::from(column))
+        && app.toggle_selected_fold()
+    {
+        changed = true;
+        if let Some(dir) = app.unmanaged_dir_to_refresh() {
+            send_task(app, task_tx, BackendTask::RefreshUnmanagedDir { dir })?;
+        }
+    }
+    if changed {
+        maybe_enqueue_auto_detail(app, task_tx)?;
+    }
+    Ok(())
+}
+
+/// Keys a footer hint stands for. Paired hints (`j/k`, `[/]`) run the forward
+/// half; the view-switch hint moves to the next list.
+fn hint_key_events(key: &str, view: ListView) -> Vec<KeyEvent> {
+    let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    match key {
+        "Space" => vec![press(KeyCode::Char(' '))],
+        "Tab" => vec![press(KeyCode::Tab)],
+        "PgUp/PgDn" => vec![press(KeyCode::PageDown)],
+        "C-u/d" => vec![KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+        "]h/[h" => vec![press(KeyCode::Char(']')), press(KeyCode::Char('h'))],
+        "[/]" => vec![press(KeyCode::Char(']'))],
+        "1-3" => {
+            let next = match view {
+                ListView::Status => '2',
+                ListView::Managed => '3',
+                ListView::Unmanaged => '1',
+            };
+            vec![press(KeyCode::Char(next))]
+        }
+        other => other
+            .chars()
+            .next()
+            .map(|ch| press(KeyCode::Char(ch)))
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use std::path::PathBuf;
+    use tokio::sync::mpsc;
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn app_with_screen() -> App {
+        let mut app = App::new(AppConfig::default());
+        app.status_entries = (0..20)
+            .map(|index| crate::domain::StatusEntry {
+                path: PathBuf::from(format!(".file{index:02}")),
+                actual_vs_state: crate::domain::ChangeKind::Modified,
+                actual_vs_target: crate::domain::ChangeKind::Modified,
+            })
+            .collect();
+        app.rebuild_visible_entries();
+        app.screen = ScreenLayout {
+            list: Rect::new(0, 0, 30, 10),
+            detail: Rect::new(30, 0, 50, 6),
+            log: Rect::new(30, 6, 50, 4),
+            hints: vec![HintRegion {
+                area: Rect::new(60, 10, 5, 1),
+                key: "?",
+            }],
+        };
+        app
+    }
+
+    #[test]
+    fn clicks_select_rows_and_focus_panes_and_run_hints() {
+        let mut app = app_with_screen();
+        let (task_tx, mut task_rx) = mpsc::unbounded_channel::<BackendTask>();
+        app.focus = PaneFocus::Detail;
+
+        handle_mouse_event(
+            &mut app,
+            mouse(MouseEventKind::Down(MouseButton::Left), 5, 4),
+            &task_tx,
+        )
+        .expect("click");
+        assert_eq!(app.focus, PaneFocus::List);
+        assert_eq!(app.selected_index, 3);
+        assert!(matches!(
+            task_rx.try_recv(),
+            Ok(BackendTask::LoadDiff { .. })
+        ));
+
+        handle_mouse_event(
+            &mut app,
+            mouse(MouseEventKind::Down(MouseButton::Left), 40, 7),
+            &task_tx,
+        )
+        .expect("click");
+        assert_eq!(app.focus, PaneFocus::Log);
+
+        handle_mouse_event(
+            &mut app,
+            mouse(MouseEventKind::Down(MouseButton::Left), 62, 10),
+            &task_tx,
+        )
+        .expect("click");
+        assert!(app.footer_help);
+    }
+
+    #[test]
+    fn wheel_scrolls_the_list_and_keeps_selection_visible() {
+        let mut app = app_with_screen();
+        let (task_tx, _task_rx) = mpsc::unbounded_channel::<BackendTask>();
+
+        for _ in 0..2 {
+            handle_mouse_event(&mut app, mouse(MouseEventKind::ScrollDown, 5, 5), &task_tx)
+                .expect("wheel");
+        }
+        assert_eq!(app.list_scroll(), 6);
+        assert_eq!(app.selected_index, 6);
+
+        for _ in 0..5 {
+            handle_mouse_event(&mut app, mouse(MouseEventKind::ScrollDown, 5, 5), &task_tx)
+                .expect("wheel");
+        }
+        assert_eq!(app.list_scroll(), 12);
+
+        handle_mouse_event(&mut app, mouse(MouseEventKind::ScrollUp, 5, 5), &task_tx)
+            .expect("wheel");
+        assert_eq!(app.list_scroll(), 9);
+        assert_eq!(app.selected_index, 12);
+    }
+
+    #[test]
+    fn paired_hints_run_their_forward_key() {
+        assert_eq!(
+            hint_key_events("j/k", ListView::Status),
+            vec![KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)]
+        );
+        assert_eq!(
+            hint_key_events("1-3", ListView::Unmanaged),
+            vec![KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE)]
+        );
+        assert_eq!(hint_key_events("]h/[h", ListView::Status).len(), 2);
+    }
+}
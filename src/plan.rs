@@ -0,0 +1,188 @@
+use crate::domain::{Action, ActionRequest};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const PLAN_FORMAT_VERSION: u32 = 1;
+
+/// On-disk form of a staged action queue. Targets under the home directory are
+/// stored home-relative so a plan can be replayed on another machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PlanFile {
+    version: u32,
+    items: Vec<PlanItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PlanItem {
+    action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    attrs: Option<String>,
+}
+
+pub(crate) fn default_plan_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("chezmoi-tui").join("plan.json"))
+}
+
+pub(crate) fn save_plan(path: &Path, requests: &[ActionRequest], home_dir: &Path) -> Result<()> {
+    let plan = PlanFile {
+        version: PLAN_FORMAT_VERSION,
+        items: requests
+            .iter()
+            .map(|request| PlanItem {
+                action: request.action.label().to_string(),
+                target: request.target.as_deref().map(|target| {
+                    target
+                        .strip_prefix(home_dir)
+                        .unwrap_or(target)
+                        .display()
+                        .to_string()
+                }),
+                attrs: request.chattr_attrs.clone(),
+            })
+            .collect(),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut content = serde_json::to_string_pretty(&plan).context("failed to encode plan")?;
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub(crate) fn load_plan(path: &Path, home_dir: &Path) -> Result<Vec<ActionRequest>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let plan: PlanFile = serde_json::from_str(&content)
+        .with_context(|| format!("invalid plan file: {}", path.display()))?;
+    if plan.version != PLAN_FORMAT_VERSION {
+        bail!(
+            "unsupported plan version {} in {} (expected {PLAN_FORMAT_VERSION})",
+            plan.version,
+            path.display()
+        );
+    }
+
+    plan.items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let action = Action::from_label(&item.action).with_context(|| {
+                format!("plan item {}: unknown action {:?}", index + 1, item.action)
+            })?;
+            if let Some(target) = &item.target
+                && !is_home_relative(Path::new(target))
+            {
+                bail!(
+                    "plan item {}: target {target:?} is not inside the home directory",
+                    index + 1
+                );
+            }
+            let target = item.target.map(|target| home_dir.join(target));
+            if action.needs_target() && target.is_none() {
+                bail!("plan item {}: {} requires a target", index + 1, item.action);
+            }
+            Ok(ActionRequest {
+                action,
+                target,
+                chattr_attrs: item.attrs,
+            })
+        })
+        .collect()
+}
+
+/// Whether `target` stays under the directory it is joined to: relative and
+/// without `..` segments.
+fn is_home_relative(target: &Path) -> bool {
+    target
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_plan_path(tag: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "chezmoi_tui_plan_{tag}_{}_{}.json",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("time")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn plan_roundtrip_rebases_home_relative_targets() {
+        let path = temp_plan_path("roundtrip");
+        let requests = vec![
+            ActionRequest {
+                action: Action::ReAdd,
+                target: Some(PathBuf::from("/home/alice/.zshrc")),
+                chattr_attrs: None,
+            },
+            ActionRequest {
+                action: Action::Chattr,
+                target: Some(PathBuf::from("/home/alice/.gitconfig")),
+                chattr_attrs: Some("+private".to_string()),
+            },
+            ActionRequest {
+                action: Action::Apply,
+                target: None,
+                chattr_attrs: None,
+            },
+        ];
+
+        save_plan(&path, &requests, Path::new("/home/alice")).expect("save");
+        let content = fs::read_to_string(&path).expect("read");
+        assert!(content.contains("\"target\": \".zshrc\""));
+
+        let loaded = load_plan(&path, Path::new("/Users/bob")).expect("load");
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0].action, Action::ReAdd);
+        assert_eq!(loaded[0].target, Some(PathBuf::from("/Users/bob/.zshrc")));
+        assert_eq!(loaded[1].chattr_attrs.as_deref(), Some("+private"));
+        assert_eq!(loaded[2].target, None);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_plan_rejects_unknown_actions() {
+        let path = temp_plan_path("unknown");
+        fs::write(
+            &path,
+            r#"{"version":1,"items":[{"action":"format-disk","target":".zshrc"}]}"#,
+        )
+        .expect("write");
+        let err = load_plan(&path, Path::new("/home/alice")).expect_err("unknown action");
+        assert!(format!("{err:#}").contains("unknown action"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_plan_rejects_targets_outside_the_home_directory() {
+        for target in ["/etc/hosts", "../bob/.zshrc", ".config/../../etc"] {
+            let path = temp_plan_path("outside");
+            fs::write(
+                &path,
+                format!(r#"{{"version":1,"items":[{{"action":"forget","target":"{target}"}}]}}"#),
+            )
+            .expect("write");
+            let err = load_plan(&path, Path::new("/home/alice")).expect_err("outside home");
+            assert!(
+                format!("{err:#}").contains("is not inside the home directory"),
+                "{err:#}"
+            );
+            let _ = fs::remove_file(path);
+        }
+    }
+}
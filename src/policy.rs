@@ -0,0 +1,171 @@
+use std::path::Path;
+
+/// Returns the first pattern that protects `relative`, a home-relative path.
+///
+/// A path is protected when it matches a pattern directly, or when it is an
+/// ancestor of a pattern's literal prefix (acting on `.ssh` touches `.ssh/**`).
+pub(crate) fn protected_pattern<'a>(patterns: &'a [String], relative: &Path) -> Option<&'a str> {
+    let path = normalize_relative(relative);
+    if path.is_empty() {
+        return None;
+    }
+
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .find(|pattern| {
+            glob_match(pattern, &path)
+                || literal_prefix(pattern)
+                    .is_some_and(|prefix| prefix == path || prefix.starts_with(&format!("{path}/")))
+        })
+}
+
+/// Matches a `/`-separated path against a gitignore-style glob.
+///
+/// Supported syntax: `*` and `?` within a component, `[abc]`, `[a-z]`, `[!x]`,
+/// `**` as a whole component for any depth, and `\` to escape the next char.
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+    let pattern_parts: Vec<&str> = pattern.split('/').collect();
+    let path_parts: Vec<&str> = path
+        .trim_start_matches('/')
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    match_components(&pattern_parts, &path_parts)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_components(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(head, tail)| {
+            match_component(
+                &first.chars().collect::<Vec<_>>(),
+                &head.chars().collect::<Vec<_>>(),
+            ) && match_components(rest, tail)
+        }),
+    }
+}
+
+fn match_component(pattern: &[char], name: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return name.is_empty();
+    };
+
+    match first {
+        '*' => (0..=name.len()).any(|skip| match_component(rest, &name[skip..])),
+        '?' => !name.is_empty() && match_component(rest, &name[1..]),
+        '[' => {
+            let Some((&ch, name_rest)) = name.split_first() else {
+                return false;
+            };
+            match parse_class(rest) {
+                Some((matches, consumed)) => {
+                    matches(ch) && match_component(&rest[consumed..], name_rest)
+                }
+                None => ch == '[' && match_component(rest, name_rest),
+            }
+        }
+        '\\' if !rest.is_empty() => {
+            name.first() == Some(&rest[0]) && match_component(&rest[1..], &name[1..])
+        }
+        literal => name.first() == Some(&literal) && match_component(rest, &name[1..]),
+    }
+}
+
+/// Parses a bracket class body (after `[`), returning a predicate and the
+/// number of chars consumed including the closing `]`.
+fn parse_class(body: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let mut i = 0usize;
+    let negate = matches!(body.first(), Some('!' | '^'));
+    if negate {
+        i += 1;
+    }
+
+    let mut ranges: Vec<(char, char)> = Vec::new();
+    let start = i;
+    while i < body.len() {
+        let ch = body[i];
+        if ch == ']' && i > start {
+            let predicate = move |candidate: char| {
+                let hit = ranges
+                    .iter()
+                    .any(|(lo, hi)| (*lo..=*hi).contains(&candidate));
+                hit != negate
+            };
+            return Some((predicate, i + 1));
+        }
+        if i + 2 < body.len() && body[i + 1] == '-' && body[i + 2] != ']' {
+            ranges.push((ch, body[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((ch, ch));
+            i += 1;
+        }
+    }
+
+    None
+}
+
+fn literal_prefix(pattern: &str) -> Option<String> {
+    let parts: Vec<&str> = pattern
+        .trim_start_matches('/')
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[', '\\']))
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+fn normalize_relative(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .trim_matches('/')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_supports_double_star_and_wildcards() {
+        assert!(glob_match(".ssh/**", ".ssh/id_ed25519"));
+        assert!(glob_match(".ssh/**", ".ssh/keys/work"));
+        assert!(glob_match("**/.git/**", "dev/project/.git/config"));
+        assert!(glob_match("*.age", "secret.age"));
+        assert!(!glob_match("*.age", "dir/secret.age"));
+        assert!(glob_match(".config/*/init.lua", ".config/nvim/init.lua"));
+        assert!(glob_match("file-[0-9]", "file-7"));
+        assert!(!glob_match("file-[!0-9]", "file-7"));
+        assert!(glob_match("\\*literal", "*literal"));
+        assert!(!glob_match("\\*literal", "xliteral"));
+    }
+
+    #[test]
+    fn protected_pattern_matches_targets_and_their_ancestors() {
+        let patterns = vec![".ssh/**".to_string(), ".config/chezmoi/**".to_string()];
+
+        assert_eq!(
+            protected_pattern(&patterns, Path::new(".ssh/config")),
+            Some(".ssh/**")
+        );
+        assert_eq!(
+            protected_pattern(&patterns, Path::new(".ssh")),
+            Some(".ssh/**")
+        );
+        assert_eq!(
+            protected_pattern(&patterns, Path::new(".config")),
+            Some(".config/chezmoi/**")
+        );
+        assert_eq!(protected_pattern(&patterns, Path::new(".zshrc")), None);
+        assert_eq!(protected_pattern(&patterns, Path::new(".sshrc")), None);
+    }
+}
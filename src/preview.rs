@@ -1,5 +1,6 @@
 use crate::actions::send_task;
 use crate::app::{App, BackendTask, DetailKind};
+use crate::binary::{HEX_PAGE_BYTES, HexPage, hex_page_text, summarize_binary};
 use crate::domain::ListView;
 use anyhow::{Context, Result};
 use std::fmt::Write as _;
@@ -9,27 +10,43 @@ use std::path::Path;
 use tokio::sync::mpsc::UnboundedSender;
 
 const PREVIEW_MAX_BYTES: usize = 64 * 1024;
-const PREVIEW_BINARY_SAMPLE_BYTES: usize = 4096;
+pub(crate) const PREVIEW_BINARY_SAMPLE_BYTES: usize = 4096;
 
-pub(crate) fn load_file_preview(path: &Path) -> Result<String> {
+/// Preview text; binary files get a format summary and the first hex page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FilePreview {
+    pub text: String,
+    pub hex: Option<HexPage>,
+}
+
+impl From<String> for FilePreview {
+    fn from(text: String) -> Self {
+        Self { text, hex: None }
+    }
+}
+
+pub(crate) fn load_file_preview(path: &Path) -> Result<FilePreview> {
     let metadata = fs::symlink_metadata(path)
         .with_context(|| format!("preview target metadata failed: {}", path.display()))?;
     let kind = metadata.file_type();
 
     if kind.is_dir() {
-        return Ok("This is a directory. Expand it and select a file inside.".to_string());
+        return Ok("This is a directory. Expand it and select a file inside."
+            .to_string()
+            .into());
     }
     if kind.is_symlink() {
         match fs::metadata(path) {
             Ok(target) if target.is_dir() => {
                 return Ok(
                     "This is a directory symlink. Directory links are shown but not expanded by default."
-                        .to_string(),
+                        .to_string()
+                        .into(),
                 );
             }
             Ok(_) => {}
             Err(err) if err.kind() == ErrorKind::NotFound => {
-                return Ok("Cannot preview broken symlink.".to_string());
+                return Ok("Cannot preview broken symlink.".to_string().into());
             }
             Err(err) => {
                 return Err(err).with_context(|| {
@@ -39,15 +56,15 @@ pub(crate) fn load_file_preview(path: &Path) -> Result<String> {
         }
     }
 
+    let file_size = fs::metadata(path).map_or(metadata.len(), |target| target.len());
     let file = File::open(path).with_context(|| format!("failed to read: {}", path.display()))?;
     let mut bytes = Vec::with_capacity(PREVIEW_MAX_BYTES + 1);
     file.take((PREVIEW_MAX_BYTES + 1) as u64)
         .read_to_end(&mut bytes)
         .with_context(|| format!("failed to read: {}", path.display()))?;
 
-    let sample_len = bytes.len().min(PREVIEW_BINARY_SAMPLE_BYTES);
-    if bytes[..sample_len].contains(&0) {
-        return Ok("Cannot preview binary file.".to_string());
+    if looks_binary(&bytes) {
+        return Ok(binary_preview(path, &bytes, file_size));
     }
 
     let is_truncated = bytes.len() > PREVIEW_MAX_BYTES;
@@ -60,11 +77,39 @@ pub(crate) fn load_file_preview(path: &Path) -> Result<String> {
         let _ = write!(
             text,
             "\n\n--- preview truncated at {} bytes (file size: {} bytes) ---",
-            PREVIEW_MAX_BYTES,
-            metadata.len()
+            PREVIEW_MAX_BYTES, file_size
         );
     }
-    Ok(text)
+    Ok(text.into())
+}
+
+/// Whether `bytes` look like a binary file: a NUL byte near the start.
+pub(crate) fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(PREVIEW_BINARY_SAMPLE_BYTES)].contains(&0)
+}
+
+fn binary_preview(path: &Path, head: &[u8], file_size: u64) -> FilePreview {
+    let page = HexPage {
+        offset: 0,
+        file_size,
+    };
+    let first_page = &head[..head.len().min(HEX_PAGE_BYTES as usize)];
+    let mut text = String::new();
+    let summary = summarize_binary(path, head, file_size);
+    if summary.is_empty() {
+        let _ = writeln!(text, "binary file, {file_size} bytes");
+    } else {
+        for line in summary {
+            let _ = writeln!(text, "{line}");
+        }
+        let _ = writeln!(text, "(binary file, {file_size} bytes)");
+    }
+    text.push('\n');
+    text.push_str(&hex_page_text(first_page, page));
+    FilePreview {
+        text,
+        hex: Some(page),
+    }
 }
 
 fn maybe_enqueue_unmanaged_preview(
@@ -87,7 +132,16 @@ fn maybe_enqueue_unmanaged_preview(
         return Ok(());
     }
 
-    send_task(app, task_tx, BackendTask::LoadPreview { target, absolute })
+    let managed = app.is_absolute_path_managed(&absolute);
+    send_task(
+        app,
+        task_tx,
+        BackendTask::LoadPreview {
+            target,
+            absolute,
+            managed,
+        },
+    )
 }
 
 fn maybe_enqueue_managed_preview(
@@ -110,7 +164,16 @@ fn maybe_enqueue_managed_preview(
         return Ok(());
     }
 
-    send_task(app, task_tx, BackendTask::LoadPreview { target, absolute })
+    let managed = app.is_absolute_path_managed(&absolute);
+    send_task(
+        app,
+        task_tx,
+        BackendTask::LoadPreview {
+            target,
+            absolute,
+            managed,
+        },
+    )
 }
 
 fn maybe_enqueue_status_diff(app: &mut App, task_tx: &UnboundedSender<BackendTask>) -> Result<()> {
@@ -155,7 +218,15 @@ mod tests {
             std::env::temp_dir().join(format!("chezmoi_tui_preview_bin_{}", std::process::id()));
         std::fs::write(&file, [0, 159, 146, 150]).expect("write binary");
         let got = load_file_preview(&file).expect("preview");
-        assert!(got.contains("binary file"));
+        assert!(got.text.contains("binary file, 4 bytes"));
+        assert!(got.text.contains("00000000  00 9f 92 96"));
+        assert_eq!(
+            got.hex,
+            Some(HexPage {
+                offset: 0,
+                file_size: 4
+            })
+        );
         let _ = std::fs::remove_file(file);
     }
 
@@ -165,7 +236,7 @@ mod tests {
             std::env::temp_dir().join(format!("chezmoi_tui_preview_txt_{}", std::process::id()));
         let payload = "a".repeat(PREVIEW_MAX_BYTES + 128);
         std::fs::write(&file, payload).expect("write text");
-        let got = load_file_preview(&file).expect("preview");
+        let got = load_file_preview(&file).expect("preview").text;
         assert!(got.contains("preview truncated"));
         let _ = std::fs::remove_file(file);
     }
@@ -189,7 +260,7 @@ mod tests {
         let link = root.join("linkdir");
         symlink(&real_dir, &link).expect("create symlink");
 
-        let got = load_file_preview(&link).expect("preview");
+        let got = load_file_preview(&link).expect("preview").text;
         assert!(got.contains("directory symlink"));
         let _ = std::fs::remove_dir_all(root);
     }
@@ -211,7 +282,7 @@ mod tests {
         let link = root.join("broken");
         symlink(root.join("missing.txt"), &link).expect("create broken symlink");
 
-        let got = load_file_preview(&link).expect("preview");
+        let got = load_file_preview(&link).expect("preview").text;
         assert!(got.contains("broken symlink"));
         let _ = std::fs::remove_dir_all(root);
     }
@@ -0,0 +1,207 @@
+use crate::diff::{parse_hunk_header, strip_ansi};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// The part of a whole-repo `chezmoi diff` that belongs to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileDiff {
+    /// Destination-relative path, as listed by `chezmoi status`.
+    pub path: PathBuf,
+    /// Raw section text, colour codes included, starting at `diff --git`.
+    pub text: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl FileDiff {
+    fn line_count(&self) -> usize {
+        self.text.lines().count()
+    }
+}
+
+/// Splits a multi-file unified diff at its `diff --git` headers. Anything
+/// before the first header is dropped.
+pub(crate) fn split_files(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut in_hunk = false;
+
+    for raw in diff.lines() {
+        let line = strip_ansi(raw);
+        if line.starts_with("diff --git ") {
+            in_hunk = false;
+            let path = line
+                .rsplit_once(" b/")
+                .map_or_else(|| PathBuf::from(&line), |(_, path)| PathBuf::from(path));
+            files.push(FileDiff {
+                path,
+                text: String::new(),
+                added: 0,
+                removed: 0,
+            });
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        file.text.push_str(raw);
+        file.text.push('\n');
+
+        if parse_hunk_header(&line).is_some() {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            file.added += 1;
+        } else if in_hunk && line.starts_with('-') {
+            file.removed += 1;
+        }
+    }
+
+    files
+}
+
+/// State of the whole-repo diff browser: a file index plus the per-file
+/// sections shown next to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RepoDiffBrowser {
+    pub files: Vec<FileDiff>,
+    pub selected: usize,
+    pub collapsed: BTreeSet<usize>,
+    /// First visible line of the sections pane.
+    pub scroll: usize,
+}
+
+impl RepoDiffBrowser {
+    pub(crate) fn new(diff: &str) -> Self {
+        Self {
+            files: split_files(diff),
+            selected: 0,
+            collapsed: BTreeSet::new(),
+            scroll: 0,
+        }
+    }
+
+    pub(crate) fn selected_file(&self) -> Option<&FileDiff> {
+        self.files.get(self.selected)
+    }
+
+    /// Rendered height of a section; a collapsed section is one header line.
+    fn section_len(&self, index: usize) -> usize {
+        if self.collapsed.contains(&index) {
+            1
+        } else {
+            self.files[index].line_count()
+        }
+    }
+
+    /// Line where each file's section starts.
+    pub(crate) fn section_offsets(&self) -> Vec<usize> {
+        let mut offset = 0;
+        (0..self.files.len())
+            .map(|index| {
+                let start = offset;
+                offset += self.section_len(index);
+                start
+            })
+            .collect()
+    }
+
+    fn total_lines(&self) -> usize {
+        (0..self.files.len())
+            .map(|index| self.section_len(index))
+            .sum()
+    }
+
+    /// Selects `index` and scrolls its section to the top.
+    pub(crate) fn jump_to(&mut self, index: usize) {
+        if index >= self.files.len() {
+            return;
+        }
+        self.selected = index;
+        self.scroll = self.section_offsets()[index];
+    }
+
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let count = self.files.len();
+        if count == 0 {
+            return;
+        }
+        let next = if forward {
+            (self.selected + 1).min(count - 1)
+        } else {
+            self.selected.saturating_sub(1)
+        };
+        self.jump_to(next);
+    }
+
+    pub(crate) fn scroll_by(&mut self, lines: isize) {
+        let max = self.total_lines().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    pub(crate) fn toggle_collapsed(&mut self) {
+        if !self.collapsed.remove(&self.selected) {
+            self.collapsed.insert(self.selected);
+        }
+        self.jump_to(self.selected);
+    }
+
+    /// Collapses every section, or expands all when everything is collapsed.
+    pub(crate) fn toggle_all_collapsed(&mut self) {
+        if self.collapsed.len() == self.files.len() {
+            self.collapsed.clear();
+        } else {
+            self.collapsed = (0..self.files.len()).collect();
+        }
+        self.jump_to(self.selected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPO_DIFF: &str = concat!(
+        "\u{1b}[1mdiff --git a/.zshrc b/.zshrc\u{1b}[0m\n",
+        "--- a/.zshrc\n",
+        "+++ b/.zshrc\n",
+        "@@ -1,2 +1,3 @@\n",
+        " keep\n",
+        "-old\n",
+        "+new\n",
+        "+more\n",
+        "diff --git a/.config/git/config b/.config/git/config\n",
+        "--- a/.config/git/config\n",
+        "+++ b/.config/git/config\n",
+        "@@ -1 +0,0 @@\n",
+        "-[user]\n",
+    );
+
+    #[test]
+    fn split_files_counts_changes_per_file() {
+        let files = split_files(REPO_DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, PathBuf::from(".zshrc"));
+        assert_eq!((files[0].added, files[0].removed), (2, 1));
+        assert_eq!(files[1].path, PathBuf::from(".config/git/config"));
+        assert_eq!((files[1].added, files[1].removed), (0, 1));
+        assert!(files[0].text.starts_with("\u{1b}[1mdiff --git"));
+    }
+
+    #[test]
+    fn collapsing_sections_shifts_offsets_and_jumps() {
+        let mut browser = RepoDiffBrowser::new(REPO_DIFF);
+        assert_eq!(browser.section_offsets(), vec![0, 8]);
+
+        browser.move_selection(true);
+        assert_eq!(browser.scroll, 8);
+
+        browser.jump_to(0);
+        browser.toggle_collapsed();
+        assert_eq!(browser.section_offsets(), vec![0, 1]);
+        browser.move_selection(true);
+        assert_eq!(browser.scroll, 1);
+
+        browser.toggle_all_collapsed();
+        assert_eq!(browser.collapsed.len(), 2);
+        browser.toggle_all_collapsed();
+        assert!(browser.collapsed.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+use crate::infra::ChezmoiClient;
+use crate::preview::{PREVIEW_BINARY_SAMPLE_BYTES, looks_binary};
+use anyhow::Result;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Matches kept per search; the modal says when more were found.
+const MAX_CONTENT_MATCHES: usize = 1_000;
+/// Characters of a matching line shown in the result list.
+const MAX_SNIPPET_CHARS: usize = 200;
+/// Characters kept before the match when a long line is cut.
+const SNIPPET_LEAD_CHARS: usize = 40;
+
+/// Which version of each managed file a content search reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum SearchScope {
+    /// The file in the home directory.
+    #[default]
+    Destination,
+    /// The file in the source directory, templates unrendered.
+    Source,
+    /// The target state as `chezmoi cat` renders it.
+    Rendered,
+}
+
+impl SearchScope {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Destination => "destination",
+            Self::Source => "source",
+            Self::Rendered => "rendered",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Self::Destination => Self::Source,
+            Self::Source => Self::Rendered,
+            Self::Rendered => Self::Destination,
+        }
+    }
+}
+
+/// One matching line of a managed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ContentMatch {
+    /// Path as shown in the managed list.
+    pub path: PathBuf,
+    /// 1-based line number.
+    pub line: usize,
+    pub snippet: String,
+}
+
+/// Where the preview picked from a content search should scroll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchJump {
+    pub path: PathBuf,
+    pub line: usize,
+    pub scope: SearchScope,
+    pub query: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ContentResults {
+    pub matches: Vec<ContentMatch>,
+    /// Set when matching stopped at `MAX_CONTENT_MATCHES`.
+    pub truncated: bool,
+}
+
+/// State of the content search modal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ContentSearch {
+    pub query: String,
+    pub scope: SearchScope,
+    pub results: ContentResults,
+    pub selected: usize,
+    /// Query and scope `results` belong to; Enter searches again once
+    /// either differs.
+    pub searched: Option<(String, SearchScope)>,
+    pub searching: bool,
+    pub message: Option<String>,
+}
+
+impl ContentSearch {
+    /// Whether `results` answer the current query and scope.
+    pub(crate) fn is_current(&self) -> bool {
+        self.searched
+            .as_ref()
+            .is_some_and(|(query, scope)| *query == self.query && *scope == self.scope)
+    }
+
+    /// Takes results for `query` in `scope`; late answers to an older
+    /// search are dropped.
+    pub(crate) fn set_results(
+        &mut self,
+        query: String,
+        scope: SearchScope,
+        results: ContentResults,
+    ) {
+        if !self.searching || query != self.query || scope != self.scope {
+            return;
+        }
+        self.searching = false;
+        self.message = None;
+        self.results = results;
+        self.selected = 0;
+        self.searched = Some((query, scope));
+    }
+
+    pub(crate) fn selected_match(&self) -> Option<&ContentMatch> {
+        self.results.matches.get(self.selected)
+    }
+
+    pub(crate) fn move_selection(&mut self, forward: bool) {
+        let count = self.results.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % count
+        } else {
+            self.selected.checked_sub(1).unwrap_or(count - 1)
+        };
+    }
+}
+
+/// Greps `targets` (shown path, absolute destination) in `scope` for
+/// `query`, case-insensitively. Files that cannot be read, or look binary,
+/// are skipped. Returns `None` once `cancel` is set.
+pub(crate) fn search_contents(
+    client: &dyn ChezmoiClient,
+    query: &str,
+    scope: SearchScope,
+    targets: &[(PathBuf, PathBuf)],
+    cancel: &AtomicBool,
+) -> Result<Option<ContentResults>> {
+    let files: Vec<(PathBuf, PathBuf)> = targets
+        .iter()
+        .filter(|(_, absolute)| !absolute.is_dir())
+        .cloned()
+        .collect();
+    let sources = match scope {
+        SearchScope::Source if !files.is_empty() => {
+            let absolutes: Vec<PathBuf> =
+                files.iter().map(|(_, absolute)| absolute.clone()).collect();
+            Some(client.source_paths(&absolutes)?)
+        }
+        _ => None,
+    };
+
+    let needle = query.to_ascii_lowercase();
+    let mut results = ContentResults::default();
+    for (index, (path, absolute)) in files.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let content = match (scope, &sources) {
+            (SearchScope::Source, Some(sources)) => {
+                sources.get(index).and_then(|source| read_text(source))
+            }
+            (SearchScope::Rendered, _) => client
+                .cat(absolute)
+                .ok()
+                .filter(|text| !looks_binary(text.as_bytes())),
+            _ => read_text(absolute),
+        };
+        let Some(content) = content else {
+            continue;
+        };
+        for (line, snippet) in grep_lines(&content, &needle) {
+            if results.matches.len() >= MAX_CONTENT_MATCHES {
+                results.truncated = true;
+                return Ok(Some(results));
+            }
+            results.matches.push(ContentMatch {
+                path: path.clone(),
+                line,
+                snippet,
+            });
+        }
+    }
+    Ok(Some(results))
+}
+
+/// Contents of the file at `path`; only its head is read when that looks
+/// binary.
+fn read_text(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    file.by_ref()
+        .take(PREVIEW_BINARY_SAMPLE_BYTES as u64)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if looks_binary(&bytes) {
+        return None;
+    }
+    file.read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// 1-based numbers and snippets of the lines containing `needle`, which is
+/// already lowercase.
+fn grep_lines(content: &str, needle: &str) -> Vec<(usize, String)> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let found = line.to_ascii_lowercase().find(needle)?;
+            Some((index + 1, snippet(line, found)))
+        })
+        .collect()
+}
+
+/// `line` without surrounding whitespace, cut to `MAX_SNIPPET_CHARS` so the
+/// match at byte offset `found` stays visible.
+fn snippet(line: &str, found: usize) -> String {
+    let trimmed_start = line.len() - line.trim_start().len();
+    let line = line.trim();
+    if line.chars().count() <= MAX_SNIPPET_CHARS {
+        return line.to_string();
+    }
+    let match_char = line[..found.saturating_sub(trimmed_start).min(line.len())]
+        .chars()
+        .count();
+    let start = match_char.saturating_sub(SNIPPET_LEAD_CHARS);
+    let cut: String = line.chars().skip(start).take(MAX_SNIPPET_CHARS).collect();
+    if start > 0 { format!("…{cut}") } else { cut }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grep_lines_matches_case_insensitively_and_cuts_long_lines() {
+        let content = "export EDITOR=nvim\n  alias vi=NVIM  \nset -o vi\n";
+        assert_eq!(
+            grep_lines(content, "nvim"),
+            vec![
+                (1, "export EDITOR=nvim".to_string()),
+                (2, "alias vi=NVIM".to_string())
+            ]
+        );
+        assert!(grep_lines(content, "").is_empty());
+
+        let long = format!("{}needle{}", "x".repeat(300), "y".repeat(300));
+        let (_, cut) = grep_lines(&long, "needle").remove(0);
+        assert!(cut.starts_with('…'));
+        assert!(cut.contains("needle"));
+        assert_eq!(cut.chars().count(), MAX_SNIPPET_CHARS + 1);
+    }
+
+    #[test]
+    fn search_state_drops_stale_results_and_wraps_selection() {
+        let mut search = ContentSearch {
+            query: "alias".to_string(),
+            searching: true,
+            ..ContentSearch::default()
+        };
+        let hit = |line| ContentMatch {
+            path: PathBuf::from(".zshrc"),
+            line,
+            snippet: "alias".to_string(),
+        };
+        let results = ContentResults {
+            matches: vec![hit(1), hit(4)],
+            truncated: false,
+        };
+
+        search.set_results(
+            "alia".to_string(),
+            SearchScope::Destination,
+            results.clone(),
+        );
+        assert!(search.searching);
+        search.set_results("alias".to_string(), SearchScope::Destination, results);
+        assert!(!search.searching);
+        assert!(search.is_current());
+
+        search.move_selection(false);
+        assert_eq!(search.selected_match().map(|hit| hit.line), Some(4));
+        search.scope = search.scope.next();
+        assert!(!search.is_current());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::domain::{ChangeKind, ListView, StatusEntry};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortMode {
+    /// Path order.
+    #[default]
+    Name,
+    /// By change kind, then path (status view only).
+    Change,
+    /// Most recently modified first.
+    Modified,
+    /// Largest first.
+    Size,
+}
+
+impl SortMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::Change => "change",
+            SortMode::Modified => "mtime",
+            SortMode::Size => "size",
+        }
+    }
+
+    /// Next mode offered in `view`; change-kind sorting needs status data.
+    pub fn next(self, view: ListView) -> Self {
+        match self {
+            SortMode::Name if view == ListView::Status => SortMode::Change,
+            SortMode::Name | SortMode::Change => SortMode::Modified,
+            SortMode::Modified => SortMode::Size,
+            SortMode::Size => SortMode::Name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupMode {
+    #[default]
+    None,
+    /// By first path component; files directly under the root share a group.
+    Directory,
+    /// By the change `chezmoi apply` would make.
+    Change,
+}
+
+impl GroupMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupMode::None => "none",
+            GroupMode::Directory => "directory",
+            GroupMode::Change => "change",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            GroupMode::None => GroupMode::Directory,
+            GroupMode::Directory => GroupMode::Change,
+            GroupMode::Change => GroupMode::None,
+        }
+    }
+}
+
+/// Sort and group settings of one list view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewOrder {
+    pub sort: SortMode,
+    /// Only used by the status view.
+    pub group: GroupMode,
+}
+
+/// Per-view orders, saved between sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewOrders {
+    pub status: ViewOrder,
+    pub managed: ViewOrder,
+    pub unmanaged: ViewOrder,
+}
+
+impl ViewOrders {
+    pub fn get(&self, view: ListView) -> ViewOrder {
+        match view {
+            ListView::Status => self.status,
+            ListView::Managed => self.managed,
+            ListView::Unmanaged => self.unmanaged,
+        }
+    }
+
+    pub fn get_mut(&mut self, view: ListView) -> &mut ViewOrder {
+        match view {
+            ListView::Status => &mut self.status,
+            ListView::Managed => &mut self.managed,
+            ListView::Unmanaged => &mut self.unmanaged,
+        }
+    }
+}
+
+pub(crate) fn default_view_orders_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("chezmoi-tui").join("views.json"))
+}
+
+/// Loads saved orders; a missing or unreadable file yields the defaults.
+pub(crate) fn load_view_orders(path: &Path) -> ViewOrders {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_view_orders(path: &Path, orders: &ViewOrders) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut content =
+        serde_json::to_string_pretty(orders).context("failed to encode view orders")?;
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// The change `chezmoi apply` would make, falling back to the
+/// destination-vs-last-state column when the target matches.
+pub(crate) fn effective_change(entry: &StatusEntry) -> ChangeKind {
+    if entry.actual_vs_target == ChangeKind::None {
+        entry.actual_vs_state
+    } else {
+        entry.actual_vs_target
+    }
+}
+
+/// Sort rank of a change kind: the most consequential changes first.
+pub(crate) fn change_rank(kind: ChangeKind) -> u8 {
+    match kind {
+        ChangeKind::Deleted => 0,
+        ChangeKind::Modified => 1,
+        ChangeKind::Added => 2,
+        ChangeKind::Run => 3,
+        ChangeKind::Unknown(_) => 4,
+        ChangeKind::None => 5,
+    }
+}
+
+pub(crate) fn change_group_label(kind: ChangeKind) -> String {
+    match kind {
+        ChangeKind::None => "unchanged".to_string(),
+        ChangeKind::Added => "added".to_string(),
+        ChangeKind::Deleted => "deleted".to_string(),
+        ChangeKind::Modified => "modified".to_string(),
+        ChangeKind::Run => "scripts".to_string(),
+        ChangeKind::Unknown(c) => format!("other ({c})"),
+    }
+}
+
+/// Group of a list path by its first component; top-level files use `~`.
+pub(crate) fn directory_group_label(path: &Path) -> String {
+    let mut components = path.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => format!("{}/", first.as_os_str().to_string_lossy()),
+        _ => "~".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_modes_cycle_per_view_and_orders_round_trip() {
+        assert_eq!(SortMode::Name.next(ListView::Status), SortMode::Change);
+        assert_eq!(SortMode::Name.next(ListView::Managed), SortMode::Modified);
+        assert_eq!(SortMode::Size.next(ListView::Managed), SortMode::Name);
+
+        let path = std::env::temp_dir().join(format!(
+            "chezmoi_tui_views_{}_{}.json",
+            std::process::id(),
+            line!()
+        ));
+        let mut orders = ViewOrders::default();
+        orders.get_mut(ListView::Status).group = GroupMode::Change;
+        orders.get_mut(ListView::Managed).sort = SortMode::Size;
+        save_view_orders(&path, &orders).expect("save");
+        assert_eq!(load_view_orders(&path), orders);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(load_view_orders(&path), ViewOrders::default());
+    }
+
+    #[test]
+    fn group_labels_use_top_level_directory_and_apply_change() {
+        assert_eq!(
+            directory_group_label(Path::new(".config/git/config")),
+            ".config/"
+        );
+        assert_eq!(directory_group_label(Path::new(".zshrc")), "~");
+
+        let entry = StatusEntry {
+            path: PathBuf::from(".zshrc"),
+            actual_vs_state: ChangeKind::Modified,
+            actual_vs_target: ChangeKind::None,
+        };
+        assert_eq!(effective_change(&entry), ChangeKind::Modified);
+        let entry = StatusEntry {
+            actual_vs_target: ChangeKind::Added,
+            ..entry
+        };
+        assert_eq!(change_group_label(effective_change(&entry)), "added");
+    }
+}
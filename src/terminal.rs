@@ -1,4 +1,8 @@
 use anyhow::{Context, Result};
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
 use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
@@ -7,9 +11,14 @@ use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 use std::io;
 
-pub(crate) fn setup_terminal() -> Result<()> {
+pub(crate) fn setup_terminal(mouse: bool) -> Result<()> {
     enable_raw_mode().context("failed to enable raw mode")?;
     execute!(io::stdout(), EnterAlternateScreen).context("failed to enter alternate screen")?;
+    execute!(io::stdout(), EnableBracketedPaste, EnableFocusChange)
+        .context("failed to enable paste and focus events")?;
+    if mouse {
+        execute!(io::stdout(), EnableMouseCapture).context("failed to enable mouse capture")?;
+    }
     Ok(())
 }
 
@@ -17,8 +26,14 @@ pub(crate) fn restore_terminal(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
 ) -> Result<()> {
     disable_raw_mode().context("failed to disable raw mode")?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)
-        .context("failed to leave alternate screen")?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        DisableFocusChange,
+        LeaveAlternateScreen
+    )
+    .context("failed to leave alternate screen")?;
     terminal.show_cursor().context("failed to show cursor")?;
     Ok(())
 }
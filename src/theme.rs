@@ -0,0 +1,368 @@
+use anyhow::{Result, bail};
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    /// For terminals with a light background.
+    Light,
+    HighContrast,
+    /// Blue / orange instead of green / red for additions and removals.
+    Deuteranopia,
+}
+
+/// `[theme]` table of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub preset: ThemePreset,
+    /// Per-role overrides (`diff_add = "#5fafff"`); values are colour names,
+    /// `#rrggbb` or a 256-colour index.
+    pub colors: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    /// The preset with overrides applied; unknown roles and unparsable
+    /// colours are errors so typos surface when the config is loaded.
+    pub fn build(&self) -> Result<Theme> {
+        let mut theme = Theme::preset(self.preset);
+        for (role, value) in &self.colors {
+            let Ok(color) = value.parse::<Color>() else {
+                bail!("theme colour {role} = {value:?} is not a colour name, #rrggbb or 0-255");
+            };
+            if !theme.set_role(role, color) {
+                bail!("unknown theme colour role: {role}");
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Declares the palette struct and the name lookup used by config overrides
+/// from one role list, so the two cannot drift apart.
+macro_rules! theme_roles {
+    ($($(#[$doc:meta])* $role:ident,)*) => {
+        /// Colours used by the renderer, one per semantic role.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub(crate) struct Theme {
+            $($(#[$doc])* pub $role: Color,)*
+        }
+
+        impl Theme {
+            fn set_role(&mut self, role: &str, color: Color) -> bool {
+                match role {
+                    $(stringify!($role) => self.$role = color,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+theme_roles! {
+    /// Border of the focused pane.
+    border_focus,
+    /// Border of dialogs.
+    border_modal,
+    /// Border of dialogs for destructive or failed operations.
+    border_danger,
+    border_warning,
+    /// Selected list row.
+    selection_fg,
+    selection_bg,
+    /// Cursor row inside dialogs.
+    cursor_fg,
+    cursor_bg,
+    text,
+    /// Secondary text and labels.
+    muted,
+    /// De-emphasised text: separators, placeholders, comments.
+    dim,
+    accent,
+    accent_strong,
+    heading,
+    warning,
+    notice,
+    danger,
+    error,
+    success,
+    /// Tertiary highlight (zoom badge).
+    special,
+    search_match_fg,
+    search_match_bg,
+    bar_bg,
+    badge_fg,
+    badge_view_bg,
+    badge_read_only_bg,
+    keycap_primary_fg,
+    keycap_primary_bg,
+    keycap_fg,
+    keycap_bg,
+    diff_add,
+    diff_remove,
+    diff_add_bg,
+    diff_remove_bg,
+    /// Changed characters within a modified line.
+    diff_add_emphasis_bg,
+    diff_remove_emphasis_bg,
+    diff_hunk,
+    diff_file,
+    diff_meta,
+    diff_context,
+    /// Source side of the three-way merge (the destination uses `diff_add`).
+    merge_source,
+    syntax_keyword,
+    syntax_key,
+    syntax_string,
+    syntax_number,
+    syntax_punctuation,
+    syntax_comment,
+    /// Shell / template variables and YAML anchors.
+    syntax_variable,
+    /// Go template `{{ ... }}` delimiters.
+    syntax_template,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub(crate) fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Dark => Self::dark(),
+            ThemePreset::Light => Self::light(),
+            ThemePreset::HighContrast => Self::high_contrast(),
+            ThemePreset::Deuteranopia => Self::deuteranopia(),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            border_focus: Color::Cyan,
+            border_modal: Color::LightBlue,
+            border_danger: Color::LightRed,
+            border_warning: Color::Yellow,
+            selection_fg: Color::Black,
+            selection_bg: Color::LightGreen,
+            cursor_fg: Color::Black,
+            cursor_bg: Color::LightYellow,
+            text: Color::White,
+            muted: Color::Gray,
+            dim: Color::DarkGray,
+            accent: Color::Cyan,
+            accent_strong: Color::LightCyan,
+            heading: Color::LightBlue,
+            warning: Color::Yellow,
+            notice: Color::LightYellow,
+            danger: Color::LightRed,
+            error: Color::Red,
+            success: Color::LightGreen,
+            special: Color::LightMagenta,
+            search_match_fg: Color::Black,
+            search_match_bg: Color::Yellow,
+            bar_bg: Color::Rgb(14, 16, 20),
+            badge_fg: Color::Black,
+            badge_view_bg: Color::Rgb(35, 118, 210),
+            badge_read_only_bg: Color::LightRed,
+            keycap_primary_fg: Color::Black,
+            keycap_primary_bg: Color::Rgb(70, 160, 250),
+            keycap_fg: Color::White,
+            keycap_bg: Color::Rgb(34, 38, 46),
+            diff_add: Color::Green,
+            diff_remove: Color::Red,
+            diff_add_bg: Color::Rgb(12, 32, 12),
+            diff_remove_bg: Color::Rgb(40, 14, 14),
+            diff_add_emphasis_bg: Color::Rgb(24, 80, 24),
+            diff_remove_emphasis_bg: Color::Rgb(96, 24, 24),
+            diff_file: Color::Cyan,
+            diff_hunk: Color::Yellow,
+            diff_meta: Color::DarkGray,
+            diff_context: Color::Gray,
+            merge_source: Color::Blue,
+            syntax_keyword: Color::Cyan,
+            syntax_key: Color::LightCyan,
+            syntax_string: Color::Yellow,
+            syntax_number: Color::Magenta,
+            syntax_punctuation: Color::Gray,
+            syntax_comment: Color::DarkGray,
+            syntax_variable: Color::LightBlue,
+            syntax_template: Color::LightMagenta,
+        }
+    }
+
+    /// Dark-on-light variant: saturated mid-tones instead of the light
+    /// ANSI colours, which wash out on white backgrounds.
+    fn light() -> Self {
+        Self {
+            border_focus: Color::Rgb(0, 110, 160),
+            border_modal: Color::Rgb(30, 80, 170),
+            border_danger: Color::Rgb(190, 30, 30),
+            border_warning: Color::Rgb(170, 110, 0),
+            selection_fg: Color::White,
+            selection_bg: Color::Rgb(30, 120, 60),
+            cursor_fg: Color::Black,
+            cursor_bg: Color::Rgb(250, 220, 120),
+            text: Color::Black,
+            muted: Color::Rgb(80, 80, 80),
+            dim: Color::Rgb(130, 130, 130),
+            accent: Color::Rgb(0, 110, 160),
+            accent_strong: Color::Rgb(0, 90, 140),
+            heading: Color::Rgb(30, 80, 170),
+            warning: Color::Rgb(160, 100, 0),
+            notice: Color::Rgb(150, 90, 0),
+            danger: Color::Rgb(190, 30, 30),
+            error: Color::Rgb(170, 0, 0),
+            success: Color::Rgb(20, 120, 40),
+            special: Color::Rgb(140, 40, 150),
+            search_match_fg: Color::Black,
+            search_match_bg: Color::Rgb(255, 210, 80),
+            bar_bg: Color::Rgb(228, 230, 235),
+            badge_fg: Color::White,
+            badge_view_bg: Color::Rgb(35, 100, 190),
+            badge_read_only_bg: Color::Rgb(190, 30, 30),
+            keycap_primary_fg: Color::White,
+            keycap_primary_bg: Color::Rgb(35, 100, 190),
+            keycap_fg: Color::Black,
+            keycap_bg: Color::Rgb(200, 204, 212),
+            diff_add: Color::Rgb(20, 120, 40),
+            diff_remove: Color::Rgb(180, 30, 30),
+            diff_add_bg: Color::Rgb(220, 245, 220),
+            diff_remove_bg: Color::Rgb(250, 222, 222),
+            diff_add_emphasis_bg: Color::Rgb(170, 230, 170),
+            diff_remove_emphasis_bg: Color::Rgb(245, 180, 180),
+            diff_file: Color::Rgb(0, 110, 160),
+            diff_hunk: Color::Rgb(140, 90, 0),
+            diff_meta: Color::Rgb(120, 120, 120),
+            diff_context: Color::Rgb(70, 70, 70),
+            merge_source: Color::Rgb(30, 80, 170),
+            syntax_keyword: Color::Rgb(120, 40, 160),
+            syntax_key: Color::Rgb(0, 100, 140),
+            syntax_string: Color::Rgb(150, 90, 0),
+            syntax_number: Color::Rgb(170, 40, 110),
+            syntax_punctuation: Color::Rgb(90, 90, 90),
+            syntax_comment: Color::Rgb(130, 130, 130),
+            syntax_variable: Color::Rgb(30, 80, 170),
+            syntax_template: Color::Rgb(150, 30, 150),
+        }
+    }
+
+    /// Pure ANSI colours at full intensity with black backgrounds, so it
+    /// follows the terminal palette and never relies on subtle tints.
+    fn high_contrast() -> Self {
+        Self {
+            border_focus: Color::LightYellow,
+            border_modal: Color::White,
+            border_danger: Color::LightRed,
+            border_warning: Color::LightYellow,
+            selection_fg: Color::Black,
+            selection_bg: Color::LightYellow,
+            cursor_fg: Color::Black,
+            cursor_bg: Color::White,
+            text: Color::White,
+            muted: Color::White,
+            dim: Color::Gray,
+            accent: Color::LightCyan,
+            accent_strong: Color::LightCyan,
+            heading: Color::LightYellow,
+            warning: Color::LightYellow,
+            notice: Color::LightYellow,
+            danger: Color::LightRed,
+            error: Color::LightRed,
+            success: Color::LightGreen,
+            special: Color::LightMagenta,
+            search_match_fg: Color::Black,
+            search_match_bg: Color::LightCyan,
+            bar_bg: Color::Black,
+            badge_fg: Color::Black,
+            badge_view_bg: Color::White,
+            badge_read_only_bg: Color::LightRed,
+            keycap_primary_fg: Color::Black,
+            keycap_primary_bg: Color::LightYellow,
+            keycap_fg: Color::Black,
+            keycap_bg: Color::White,
+            diff_add: Color::LightGreen,
+            diff_remove: Color::LightRed,
+            diff_add_bg: Color::Black,
+            diff_remove_bg: Color::Black,
+            diff_add_emphasis_bg: Color::Green,
+            diff_remove_emphasis_bg: Color::Red,
+            diff_file: Color::LightCyan,
+            diff_hunk: Color::LightYellow,
+            diff_meta: Color::Gray,
+            diff_context: Color::White,
+            merge_source: Color::LightBlue,
+            syntax_keyword: Color::LightCyan,
+            syntax_key: Color::LightYellow,
+            syntax_string: Color::LightGreen,
+            syntax_number: Color::LightMagenta,
+            syntax_punctuation: Color::White,
+            syntax_comment: Color::Gray,
+            syntax_variable: Color::LightBlue,
+            syntax_template: Color::LightMagenta,
+        }
+    }
+
+    /// The dark palette with additions in blue and removals in orange, a
+    /// pair that stays distinct for red-green colour blindness.
+    fn deuteranopia() -> Self {
+        Self {
+            selection_bg: Color::Rgb(86, 180, 233),
+            success: Color::Rgb(86, 180, 233),
+            diff_add: Color::Rgb(86, 180, 233),
+            diff_remove: Color::Rgb(230, 159, 0),
+            diff_add_bg: Color::Rgb(10, 28, 44),
+            diff_remove_bg: Color::Rgb(44, 30, 6),
+            diff_add_emphasis_bg: Color::Rgb(20, 70, 110),
+            diff_remove_emphasis_bg: Color::Rgb(110, 70, 0),
+            merge_source: Color::Rgb(204, 121, 167),
+            ..Self::dark()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_overrides_apply_on_top_of_the_preset() {
+        let config = ThemeConfig {
+            preset: ThemePreset::Light,
+            colors: BTreeMap::from([
+                ("diff_add".to_string(), "#0000ff".to_string()),
+                ("bar_bg".to_string(), "white".to_string()),
+            ]),
+        };
+        let theme = config.build().expect("theme");
+        assert_eq!(theme.diff_add, Color::Rgb(0, 0, 255));
+        assert_eq!(theme.bar_bg, Color::White);
+        assert_eq!(theme.text, Theme::preset(ThemePreset::Light).text);
+
+        let typo = ThemeConfig {
+            colors: BTreeMap::from([("diff_added".to_string(), "red".to_string())]),
+            ..ThemeConfig::default()
+        };
+        assert!(typo.build().is_err());
+        let bad_value = ThemeConfig {
+            colors: BTreeMap::from([("diff_add".to_string(), "greenish".to_string())]),
+            ..ThemeConfig::default()
+        };
+        assert!(bad_value.build().is_err());
+    }
+
+    #[test]
+    fn deuteranopia_preset_avoids_red_green_diff_pair() {
+        let theme = Theme::preset(ThemePreset::Deuteranopia);
+        assert_ne!(theme.diff_add, Color::Green);
+        assert_ne!(theme.diff_remove, Color::Red);
+        assert_eq!(theme.text, Theme::default().text);
+    }
+}
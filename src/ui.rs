@@ -1,34 +1,70 @@
-use crate::app::{App, ConfirmStep, DetailKind, InputKind, ModalState, PaneFocus};
-use crate::domain::{Action, ListView};
+use crate::actions::squash_lines;
+use crate::app::{
+    App, BatchItemState, BatchSummary, ConfirmStep, DetailKind, DiffLayout, InputKind, ModalState,
+    PaneFocus,
+};
+use crate::audit::format_rfc3339_utc;
+use crate::chattr::{AttrChange, ChattrAttribute, ChattrPicker, TriState};
+use crate::diff::{Hunk, SplitCell, SplitRowKind, changed_span, split_rows};
+use crate::domain::{Action, ActionRequest, ListView};
+use crate::fuzzy::FuzzyFinder;
+use crate::highlight::{Token, highlight};
+use crate::hunk::HunkDirection;
+use crate::layout::{PaneAreas, pane_areas};
+use crate::merge::{MergeBlock, MergeView};
+use crate::metadata::{DEFAULT_UMASK, EntryMetadata, mode_string, permission_mismatches};
+use crate::mouse::{HintRegion, ScreenLayout};
+use crate::repo_diff::RepoDiffBrowser;
+use crate::search::ContentSearch;
+use crate::sort::{GroupMode, SortMode};
+use crate::theme::Theme;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::prelude::{Alignment, Color, Line, Modifier, Span, Style};
 use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
 use std::collections::HashSet;
 use std::path::Path;
+use std::rc::Rc;
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
+    let outer = outer_areas(app, frame.area());
+    let panes = pane_areas(outer[0], app.layout, app.zoomed.then_some(app.focus));
+    if let Some(area) = panes.list {
+        draw_list(frame, app, area);
+    }
+    if let Some(area) = panes.detail {
+        draw_detail(frame, app, area);
+    }
+    if let Some(area) = panes.log {
+        draw_logs(frame, app, area);
+    }
+    let hints = draw_status_bar(frame, app, outer[1]);
+    draw_modal(frame, app);
+    app.screen = ScreenLayout {
+        list: panes.list.unwrap_or_default(),
+        detail: panes.detail.unwrap_or_default(),
+        log: panes.log.unwrap_or_default(),
+        hints,
+    };
+}
+
+/// Pane area and footer of a terminal of size `area`.
+fn outer_areas(app: &App, area: Rect) -> Rc<[Rect]> {
     let footer_height = if app.footer_help { 2 } else { 1 };
-    let outer = Layout::default()
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(1), Constraint::Length(footer_height)])
-        .split(frame.area());
-
-    let main = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
-        .split(outer[0]);
-
-    let right = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-        .split(main[1]);
+        .split(area)
+}
 
-    draw_list(frame, app, main[0]);
-    draw_detail(frame, app, right[0]);
-    draw_logs(frame, app, right[1]);
-    draw_status_bar(frame, app, outer[1]);
-    draw_modal(frame, app);
+/// Where `draw` will put the panes on a terminal of size `area`; lets a
+/// resize be handled before the next frame.
+pub(crate) fn main_panes(app: &App, area: Rect) -> PaneAreas {
+    pane_areas(
+        outer_areas(app, area)[0],
+        app.layout,
+        app.zoomed.then_some(app.focus),
+    )
 }
 
 fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -37,15 +73,16 @@ fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
     app.sync_list_scroll(viewport_rows);
 
     let border_style = if app.focus == PaneFocus::List {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.border_focus)
     } else {
         Style::default()
     };
 
+    let title = format!(" {}{} ", app.view.title(), list_order_badge(app));
     let title = if app.list_filter().trim().is_empty() {
-        format!(" {} ", app.view.title())
+        title
     } else {
-        format!(" {} /{} ", app.view.title(), app.list_filter())
+        format!("{title}/{} ", app.list_filter())
     };
 
     let list = List::new(items)
@@ -57,8 +94,8 @@ fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
         )
         .highlight_style(
             Style::default()
-                .fg(Color::Black)
-                .bg(Color::LightGreen)
+                .fg(app.theme.selection_fg)
+                .bg(app.theme.selection_bg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("▶ ");
@@ -71,13 +108,64 @@ fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// Non-default sort / grouping of the current list, disabled unmanaged
+/// exclusions and indexing progress, for its title.
+fn list_order_badge(app: &App) -> String {
+    let order = app.view_order();
+    let mut parts = Vec::new();
+    if order.sort != SortMode::Name {
+        parts.push(format!("by {}", order.sort.label()));
+    }
+    if app.view == ListView::Status && order.group != GroupMode::None {
+        parts.push(format!("grouped: {}", order.group.label()));
+    }
+    if app.view == ListView::Unmanaged && !app.unmanaged_excludes.enabled {
+        parts.push("noise shown".to_string());
+    }
+    if app.view == ListView::Unmanaged
+        && let Some(indexed) = app.unmanaged_index_progress()
+    {
+        parts.push(format!("indexing {indexed}"));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", parts.join(", "))
+    }
+}
+
 fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
     let border_style = if app.focus == PaneFocus::Detail {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.border_focus)
     } else {
         Style::default()
     };
 
+    let area = match &app.detail_metadata {
+        Some(metadata) if app.detail_kind == DetailKind::Preview => {
+            let umask = app.config.umask.unwrap_or(DEFAULT_UMASK);
+            let lines = metadata_lines(metadata, &app.theme, umask);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(clamp_to_u16(lines.len() + 2)),
+                    Constraint::Min(3),
+                ])
+                .split(area);
+            let panel = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(" Metadata ")
+                        .borders(Borders::ALL)
+                        .border_style(border_style),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(panel, rows[0]);
+            rows[1]
+        }
+        _ => area,
+    };
+
     let lines = if app.detail_text.trim().is_empty() {
         if app.view == ListView::Unmanaged && app.selected_is_directory() {
             vec![Line::from("")]
@@ -87,16 +175,32 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
                 Line::from("Enter / d: diff, v: file preview"),
             ]
         }
+    } else if app.split_diff_active() {
+        split_diff_lines(
+            &app.detail_text,
+            usize::from(area.width.saturating_sub(2)),
+            &app.theme,
+        )
     } else if app.detail_kind == DetailKind::Diff {
-        colorized_diff_lines(&app.detail_text)
+        colorized_diff_lines(&app.detail_text, &app.theme)
+    } else if app.detail_hex.is_some() {
+        hex_preview_lines(&app.detail_text, &app.theme)
+    } else {
+        colorized_preview_lines(app.detail_target.as_deref(), &app.detail_text, &app.theme)
+    };
+    let lines = if app.detail_search().is_empty() {
+        lines
     } else {
-        colorized_preview_lines(app.detail_target.as_deref(), &app.detail_text)
+        lines
+            .into_iter()
+            .map(|line| highlight_matches(line, app.detail_search(), &app.theme))
+            .collect()
     };
 
     let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
-                .title(format!(" {} ", app.detail_title))
+                .title(detail_title(app))
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -106,24 +210,206 @@ fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+/// Hex dump rows get a dim offset and cyan ASCII column; the format summary
+/// above them stays plain and the page header is dimmed.
+fn hex_preview_lines(text: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(theme.dim);
+    text.lines()
+        .map(|line| {
+            let is_row = line.len() > 10
+                && line.as_bytes()[..8].iter().all(u8::is_ascii_hexdigit)
+                && line[8..].starts_with("  ");
+            match line.rfind(" |") {
+                Some(ascii) if is_row => Line::from(vec![
+                    Span::styled(line[..8].to_string(), dim),
+                    Span::raw(line[8..ascii].to_string()),
+                    Span::styled(line[ascii..].to_string(), Style::default().fg(theme.accent)),
+                ]),
+                _ if line.starts_with("hex page ") => {
+                    Line::from(Span::styled(line.to_string(), dim))
+                }
+                _ => Line::from(line.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Destination metadata plus, for managed entries, the source path and its
+/// decoded attributes; permission mismatches are called out last.
+fn metadata_lines(metadata: &EntryMetadata, theme: &Theme, umask: u32) -> Vec<Line<'static>> {
+    let label = |text: &str| Span::styled(format!("{text:<9}"), Style::default().fg(theme.muted));
+    let file = &metadata.file;
+    let mut lines = vec![
+        Line::from(vec![
+            label("mode"),
+            Span::raw(format!(
+                "{} ({:04o}) {}",
+                mode_string(file.kind, file.mode),
+                file.mode,
+                file.kind.label()
+            )),
+            Span::styled(
+                if file.is_executable() {
+                    "  executable"
+                } else {
+                    ""
+                },
+                Style::default().fg(theme.success),
+            ),
+        ]),
+        Line::from(vec![
+            label("owner"),
+            Span::raw(format!("{}:{}", file.owner, file.group)),
+            Span::styled("  size ", Style::default().fg(theme.muted)),
+            Span::raw(format!("{} bytes", file.size)),
+        ]),
+        Line::from(vec![
+            label("modified"),
+            Span::raw(
+                file.modified
+                    .map_or_else(|| "unknown".to_string(), format_rfc3339_utc),
+            ),
+        ]),
+    ];
+    if let Some(target) = &file.symlink_target {
+        lines.push(Line::from(vec![
+            label("link"),
+            Span::styled(
+                format!("-> {}", target.display()),
+                Style::default().fg(theme.accent),
+            ),
+        ]));
+    }
+
+    let Some(source) = &metadata.source else {
+        return lines;
+    };
+    lines.push(Line::from(vec![
+        label("source"),
+        Span::raw(source.path.display().to_string()),
+    ]));
+    let attributes: Vec<&str> = source
+        .info
+        .attributes
+        .iter()
+        .map(|attribute| attribute.label())
+        .collect();
+    lines.push(Line::from(vec![
+        label("attrs"),
+        Span::raw(if attributes.is_empty() {
+            "none".to_string()
+        } else {
+            attributes.join(", ")
+        }),
+    ]));
+    for mismatch in permission_mismatches(file, &source.info, umask) {
+        lines.push(Line::from(Span::styled(
+            format!("! {mismatch}"),
+            Style::default().fg(theme.warning),
+        )));
+    }
+    lines
+}
+
+/// Byte ranges of case-insensitive (ASCII) occurrences of `query` in `text`.
+fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let haystack = text.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    haystack
+        .match_indices(&needle)
+        .map(|(start, matched)| (start, start + matched.len()))
+        .collect()
+}
+
+/// Re-splits `line` so search matches get a highlight on top of their
+/// existing style, even when a match crosses span boundaries.
+fn highlight_matches(mut line: Line<'static>, query: &str, theme: &Theme) -> Line<'static> {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let ranges = match_ranges(&text, query);
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let highlight = Style::default()
+        .fg(theme.search_match_fg)
+        .bg(theme.search_match_bg)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in std::mem::take(&mut line.spans) {
+        let end = offset + span.content.len();
+        let mut cursor = offset;
+        for &(match_start, match_end) in &ranges {
+            let (start, stop) = (match_start.max(offset), match_end.min(end));
+            if start >= stop {
+                continue;
+            }
+            if start > cursor {
+                spans.push(Span::styled(text[cursor..start].to_string(), span.style));
+            }
+            spans.push(Span::styled(
+                text[start..stop].to_string(),
+                span.style.patch(highlight),
+            ));
+            cursor = stop;
+        }
+        if cursor < end {
+            spans.push(Span::styled(text[cursor..end].to_string(), span.style));
+        }
+        offset = end;
+    }
+    line.spans = spans;
+    line
+}
+
+fn detail_title(app: &App) -> String {
+    let mut title = format!(" {} ", app.detail_title);
+    if app.split_diff_active() {
+        title.push_str("[split] ");
+    }
+    if !app.detail_search().is_empty() {
+        let (current, total) = app.detail_match_position().unwrap_or((0, 0));
+        title.push_str(&format!("[/{} {current}/{total}] ", app.detail_search()));
+    }
+    if let Some((index, total)) = app.current_hunk() {
+        title.push_str(&format!("[hunk {}/{total}] ", index + 1));
+    }
+    title
+}
+
 fn draw_logs(frame: &mut Frame, app: &App, area: Rect) {
     let border_style = if app.focus == PaneFocus::Log {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(app.theme.border_focus)
     } else {
         Style::default()
     };
 
-    let lines: Vec<Line> = app
-        .logs
-        .iter()
-        .map(|line| Line::from(line.as_str()))
-        .collect();
+    let (title, lines): (String, Vec<Line>) = match app.audit_history_lines() {
+        Some(history) => (
+            log_history_title(app),
+            history.into_iter().map(Line::from).collect(),
+        ),
+        None => (
+            " Log ".to_string(),
+            app.logs
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect(),
+        ),
+    };
     let scroll = log_scroll_offset(lines.len(), area.height, app.log_tail_offset);
 
     let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
-                .title(" Log ")
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -133,6 +419,30 @@ fn draw_logs(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+fn log_history_title(app: &App) -> String {
+    let Some(history) = &app.audit_history else {
+        return " Log ".to_string();
+    };
+    let Some(session) = history.sessions.get(history.selected) else {
+        return " Audit ".to_string();
+    };
+    let current = if app.is_current_audit_session(&session.id) {
+        " (current)"
+    } else {
+        ""
+    };
+    format!(
+        " Audit {}/{}: {}{} ",
+        history.selected + 1,
+        history.sessions.len(),
+        session
+            .records
+            .first()
+            .map_or(session.id.as_str(), |record| record.timestamp.as_str()),
+        current
+    )
+}
+
 fn log_scroll_offset(total_lines: usize, area_height: u16, tail_offset: usize) -> u16 {
     let visible_rows = area_height.saturating_sub(2) as usize;
     let max_offset = total_lines.saturating_sub(visible_rows.max(1));
@@ -143,8 +453,8 @@ fn clamp_to_u16(value: usize) -> u16 {
     u16::try_from(value).unwrap_or(u16::MAX)
 }
 
-fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    FooterBar::draw(frame, app, area);
+fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) -> Vec<HintRegion> {
+    FooterBar::draw(frame, app, area)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -208,9 +518,9 @@ const TARGET_HINT_COUNT: usize = 7;
 const TRUNCATED_HINT_LABEL_WIDTH: usize = 6;
 
 impl FooterBar {
-    fn draw(frame: &mut Frame, app: &App, area: Rect) {
+    fn draw(frame: &mut Frame, app: &App, area: Rect) -> Vec<HintRegion> {
         if area.height == 0 {
-            return;
+            return Vec::new();
         }
 
         let rows = if app.footer_help && area.height >= 2 {
@@ -225,16 +535,17 @@ impl FooterBar {
                 .split(area)
         };
 
-        Self::draw_main_row(frame, app, rows[0]);
+        let hints = Self::draw_main_row(frame, app, rows[0]);
         if rows.len() > 1 {
             Self::draw_cheat_row(frame, app, rows[1]);
         }
+        hints
     }
 
-    fn draw_main_row(frame: &mut Frame, app: &App, area: Rect) {
+    fn draw_main_row(frame: &mut Frame, app: &App, area: Rect) -> Vec<HintRegion> {
         let total_width = area.width as usize;
         if total_width == 0 {
-            return;
+            return Vec::new();
         }
 
         let min_right = MIN_RIGHT_HINT_WIDTH.min(total_width.saturating_sub(1));
@@ -247,7 +558,7 @@ impl FooterBar {
         // Help ON でも 1行目は通常フッターの最重要ヒントだけを表示する。
         // 追加説明キーは 2行目の Help シートにのみ集約する。
         let rendered = layout_hints(right_budget, footer_hints(app));
-        let (right_spans, right_width) = render_hints(&rendered);
+        let (right_spans, right_width) = render_hints(&rendered, &app.theme);
 
         let gap = total_width.saturating_sub(left_width + right_width);
 
@@ -261,8 +572,14 @@ impl FooterBar {
 
         let paragraph = Paragraph::new(Line::from(line))
             .alignment(Alignment::Left)
-            .style(Style::default().bg(Color::Rgb(14, 16, 20)));
+            .style(Style::default().bg(app.theme.bar_bg));
         frame.render_widget(paragraph, area);
+
+        let hints_x = area.x.saturating_add(clamp_to_u16(left_width + gap));
+        hint_regions(&rendered, hints_x, area.y)
+            .into_iter()
+            .filter(|region| region.area.right() <= area.right())
+            .collect()
     }
 
     fn draw_cheat_row(frame: &mut Frame, app: &App, area: Rect) {
@@ -275,7 +592,7 @@ impl FooterBar {
             Span::styled(
                 "Help:",
                 Style::default()
-                    .fg(Color::LightBlue)
+                    .fg(app.theme.heading)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" "),
@@ -288,7 +605,7 @@ impl FooterBar {
         let line = Line::from(spans);
         let paragraph = Paragraph::new(line)
             .alignment(Alignment::Left)
-            .style(Style::default().bg(Color::Rgb(14, 16, 20)));
+            .style(Style::default().bg(app.theme.bar_bg));
         frame.render_widget(paragraph, area);
     }
 }
@@ -305,20 +622,67 @@ fn footer_left(app: &App, max_width: usize) -> (Vec<Span<'static>>, usize) {
         app.selected_index.min(item_count - 1) + 1
     };
     let marked_count = app.marked_count();
+    let theme = &app.theme;
     let mut segments = vec![LeftSegment {
         text: app.view.title().to_string(),
         style: Style::default()
-            .bg(Color::Rgb(35, 118, 210))
-            .fg(Color::Black)
+            .bg(theme.badge_view_bg)
+            .fg(theme.badge_fg)
             .add_modifier(Modifier::BOLD),
         essential: true,
         badge: true,
     }];
 
+    if app.config.read_only {
+        segments.push(LeftSegment {
+            text: "READ-ONLY".to_string(),
+            style: Style::default()
+                .bg(theme.badge_read_only_bg)
+                .fg(theme.badge_fg)
+                .add_modifier(Modifier::BOLD),
+            essential: true,
+            badge: true,
+        });
+    }
+
+    if app.batch_in_progress() {
+        segments.push(LeftSegment {
+            text: format!("Run {}/{}", app.batch_completed_count(), app.batch_total()),
+            style: Style::default().fg(theme.accent_strong),
+            essential: false,
+            badge: false,
+        });
+    } else if !app.action_queue.is_empty() {
+        segments.push(LeftSegment {
+            text: format!("{} queued", app.action_queue.len()),
+            style: Style::default().fg(theme.accent_strong),
+            essential: false,
+            badge: false,
+        });
+    }
+
+    if app.zoomed {
+        segments.push(LeftSegment {
+            text: "Zoom".to_string(),
+            style: Style::default().fg(theme.special),
+            essential: false,
+            badge: false,
+        });
+    }
+
+    if app.stale {
+        segments.push(LeftSegment {
+            text: "Stale".to_string(),
+            style: Style::default().fg(theme.warning),
+            essential: false,
+            badge: false,
+        });
+    }
+
     if app.busy {
         segments.push(LeftSegment {
             text: "Busy".to_string(),
-            style: Style::default().fg(Color::LightYellow),
+            style: Style::default().fg(theme.notice),
             essential: false,
             badge: false,
         });
@@ -332,13 +696,13 @@ fn footer_left(app: &App, max_width: usize) -> (Vec<Span<'static>>, usize) {
                 item_count,
                 item_word(item_count)
             ),
-            style: Style::default().fg(Color::Gray),
+            style: Style::default().fg(theme.muted),
             essential: true,
             badge: false,
         },
         LeftSegment {
             text: format!("{marked_count} marked"),
-            style: Style::default().fg(Color::Gray),
+            style: Style::default().fg(theme.muted),
             essential: true,
             badge: false,
         },
@@ -347,7 +711,7 @@ fn footer_left(app: &App, max_width: usize) -> (Vec<Span<'static>>, usize) {
     if !app.list_filter().trim().is_empty() {
         segments.push(LeftSegment {
             text: format!("/{}", compact_label(app.list_filter(), 18)),
-            style: Style::default().fg(Color::LightYellow),
+            style: Style::default().fg(theme.notice),
             essential: false,
             badge: false,
         });
@@ -360,7 +724,7 @@ fn footer_left(app: &App, max_width: usize) -> (Vec<Span<'static>>, usize) {
     for (idx, seg) in segments.iter().enumerate() {
         if idx > 0 {
             let sep = " • ";
-            spans.push(Span::styled(sep, Style::default().fg(Color::DarkGray)));
+            spans.push(Span::styled(sep, Style::default().fg(theme.dim)));
             width += text_width(sep);
         }
 
@@ -424,7 +788,8 @@ fn left_segments_width(segments: &[LeftSegment]) -> usize {
 fn footer_hints(app: &App) -> Vec<Hint> {
     let mut hints = match app.focus {
         PaneFocus::List => list_focus_hints(app),
-        PaneFocus::Detail | PaneFocus::Log => detail_focus_hints(),
+        PaneFocus::Detail => detail_pane_hints(app),
+        PaneFocus::Log => log_focus_hints(app),
     };
 
     if app.footer_help {
@@ -482,6 +847,17 @@ fn list_focus_hints(app: &App) -> Vec<Hint> {
             app.view != ListView::Status && !app.selected_is_directory(),
             false,
         ),
+        hint(
+            "m",
+            "Merge",
+            Some("detail"),
+            80,
+            HintTone::Secondary,
+            app.view == ListView::Status
+                && app.selected_is_managed()
+                && !app.selected_is_directory(),
+            false,
+        ),
         hint(
             "c",
             "Clear",
@@ -497,7 +873,36 @@ fn list_focus_hints(app: &App) -> Vec<Hint> {
             Some("tree"),
             62,
             HintTone::Muted,
-            app.footer_help && matches!(app.view, ListView::Managed | ListView::Unmanaged),
+            app.footer_help
+                && (matches!(app.view, ListView::Managed | ListView::Unmanaged)
+                    || app.view_order().group != GroupMode::None),
+            false,
+        ),
+        hint(
+            "o",
+            "Sort",
+            Some("list"),
+            60,
+            HintTone::Muted,
+            app.footer_help,
+            false,
+        ),
+        hint(
+            "g",
+            "Group",
+            Some("list"),
+            59,
+            HintTone::Muted,
+            app.footer_help && app.view == ListView::Status,
+            false,
+        ),
+        hint(
+            "I",
+            "Noise",
+            Some("list"),
+            58,
+            HintTone::Muted,
+            app.footer_help && app.view == ListView::Unmanaged,
             false,
         ),
     ]
@@ -535,8 +940,145 @@ fn detail_focus_hints() -> Vec<Hint> {
     ]
 }
 
-fn help_only_global_hints() -> [Hint; 3] {
+fn detail_pane_hints(app: &App) -> Vec<Hint> {
+    let mut hints = detail_focus_hints();
+    hints.push(hint(
+        "/",
+        "Search",
+        Some("search"),
+        88,
+        HintTone::Secondary,
+        true,
+        false,
+    ));
+    hints.push(hint(
+        "n/N",
+        "Match",
+        Some("search"),
+        87,
+        HintTone::Secondary,
+        !app.detail_search().is_empty(),
+        false,
+    ));
+    if app.detail_hex.is_some() {
+        hints.push(hint(
+            "[/]",
+            "Hex page",
+            Some("preview"),
+            85,
+            HintTone::Secondary,
+            true,
+            false,
+        ));
+    }
+    if app.detail_kind == DetailKind::Diff {
+        let label = match app.diff_layout {
+            DiffLayout::Unified => "Split",
+            DiffLayout::Split => "Unified",
+        };
+        hints.push(hint(
+            "s",
+            label,
+            Some("diff"),
+            85,
+            HintTone::Secondary,
+            true,
+            false,
+        ));
+        let has_hunks = app.current_hunk().is_some();
+        hints.push(hint(
+            "]h/[h",
+            "Hunk",
+            Some("diff"),
+            84,
+            HintTone::Secondary,
+            has_hunks,
+            false,
+        ));
+        let writable = has_hunks && !app.config.read_only;
+        hints.push(hint(
+            "A",
+            "Apply hunk",
+            Some("hunk"),
+            83,
+            HintTone::Secondary,
+            writable,
+            false,
+        ));
+        hints.push(hint(
+            "R",
+            "Re-add hunk",
+            Some("hunk"),
+            82,
+            HintTone::Secondary,
+            writable,
+            false,
+        ));
+    }
+    hints
+}
+
+fn log_focus_hints(app: &App) -> Vec<Hint> {
+    let mut hints = detail_focus_hints();
+    hints.push(hint(
+        "H",
+        "History",
+        Some("log"),
+        85,
+        HintTone::Secondary,
+        true,
+        false,
+    ));
+    hints.push(hint(
+        "[/]",
+        "Session",
+        Some("log"),
+        84,
+        HintTone::Secondary,
+        app.audit_history.is_some(),
+        false,
+    ));
+    hints
+}
+
+fn help_only_global_hints() -> [Hint; 7] {
     [
+        hint(
+            "f",
+            "Fuzzy",
+            Some("global"),
+            63,
+            HintTone::Muted,
+            true,
+            false,
+        ),
+        hint(
+            "F",
+            "Grep",
+            Some("global"),
+            59,
+            HintTone::Muted,
+            true,
+            false,
+        ),
+        hint(
+            "p",
+            "Queue",
+            Some("global"),
+            62,
+            HintTone::Muted,
+            true,
+            false,
+        ),
+        hint(
+            "D",
+            "All diffs",
+            Some("global"),
+            61,
+            HintTone::Muted,
+            true,
+            false,
+        ),
         hint(
             "Tab",
             "Pane",
@@ -732,7 +1274,34 @@ fn hints_width(hints: &[HintRendered]) -> usize {
         .sum()
 }
 
-fn render_hints(hints: &[HintRendered]) -> (Vec<Span<'static>>, usize) {
+/// Screen cells covered by each rendered hint (keycap plus label), laid out
+/// from `x` the same way `render_hints` places them.
+fn hint_regions(hints: &[HintRendered], x: u16, y: u16) -> Vec<HintRegion> {
+    let mut regions = Vec::new();
+    let mut offset = 0usize;
+    for (index, hint) in hints.iter().enumerate() {
+        if index > 0 {
+            offset += 2;
+        }
+        let mut width = keycap_width(hint.key);
+        if !hint.label.is_empty() {
+            width += 1 + text_width(&hint.label);
+        }
+        regions.push(HintRegion {
+            area: Rect::new(
+                x.saturating_add(clamp_to_u16(offset)),
+                y,
+                clamp_to_u16(width),
+                1,
+            ),
+            key: hint.key,
+        });
+        offset += width;
+    }
+    regions
+}
+
+fn render_hints(hints: &[HintRendered], theme: &Theme) -> (Vec<Span<'static>>, usize) {
     let mut spans = Vec::new();
     let mut width = 0usize;
 
@@ -742,8 +1311,8 @@ fn render_hints(hints: &[HintRendered]) -> (Vec<Span<'static>>, usize) {
             width += 2;
         }
 
-        let keycap_style = keycap_style(hint.tone);
-        let label_style = hint_label_style(hint.tone);
+        let keycap_style = keycap_style(hint.tone, theme);
+        let label_style = hint_label_style(hint.tone, theme);
         spans.push(Span::styled(format!(" {} ", hint.key), keycap_style));
         width += keycap_width(hint.key);
 
@@ -757,34 +1326,40 @@ fn render_hints(hints: &[HintRendered]) -> (Vec<Span<'static>>, usize) {
     (spans, width)
 }
 
-fn keycap_style(tone: HintTone) -> Style {
+fn keycap_style(tone: HintTone, theme: &Theme) -> Style {
     match tone {
         HintTone::Primary => Style::default()
-            .bg(Color::Rgb(70, 160, 250))
-            .fg(Color::Black)
+            .bg(theme.keycap_primary_bg)
+            .fg(theme.keycap_primary_fg)
             .add_modifier(Modifier::BOLD),
-        HintTone::Secondary => Style::default().bg(Color::Rgb(34, 38, 46)).fg(Color::White),
-        HintTone::Muted => Style::default().fg(Color::DarkGray),
+        HintTone::Secondary => Style::default().bg(theme.keycap_bg).fg(theme.keycap_fg),
+        HintTone::Muted => Style::default().fg(theme.dim),
     }
 }
 
-fn hint_label_style(tone: HintTone) -> Style {
+fn hint_label_style(tone: HintTone, theme: &Theme) -> Style {
     match tone {
         HintTone::Primary => Style::default()
-            .fg(Color::LightCyan)
+            .fg(theme.accent_strong)
             .add_modifier(Modifier::BOLD),
-        HintTone::Secondary => Style::default().fg(Color::Gray),
-        HintTone::Muted => Style::default().fg(Color::DarkGray),
+        HintTone::Secondary => Style::default().fg(theme.muted),
+        HintTone::Muted => Style::default().fg(theme.dim),
     }
 }
 
 fn cheat_groups(app: &App) -> Vec<CheatGroup> {
     let mut nav_items = Vec::new();
     let mut view_items = Vec::new();
-    let mut global_items = vec![CheatItem {
-        key: "a",
-        label: "Actions",
-    }];
+    let mut global_items = vec![
+        CheatItem {
+            key: "a",
+            label: "Actions",
+        },
+        CheatItem {
+            key: "p",
+            label: "Queue",
+        },
+    ];
     if !app.busy {
         global_items.push(CheatItem {
             key: "r",
@@ -823,6 +1398,10 @@ fn cheat_groups(app: &App) -> Vec<CheatGroup> {
                     key: "d",
                     label: "Diff",
                 });
+                nav_items.push(CheatItem {
+                    key: "m",
+                    label: "Merge",
+                });
             } else if !app.selected_is_directory() {
                 nav_items.push(CheatItem {
                     key: "v",
@@ -836,12 +1415,30 @@ fn cheat_groups(app: &App) -> Vec<CheatGroup> {
                 });
             }
 
-            if matches!(app.view, ListView::Managed | ListView::Unmanaged) {
+            if matches!(app.view, ListView::Managed | ListView::Unmanaged)
+                || app.view_order().group != GroupMode::None
+            {
                 view_items.push(CheatItem {
                     key: "h/l",
                     label: "Fold",
                 });
             }
+            view_items.push(CheatItem {
+                key: "o",
+                label: "Sort",
+            });
+            if app.view == ListView::Status {
+                view_items.push(CheatItem {
+                    key: "g",
+                    label: "Group",
+                });
+            }
+            if app.view == ListView::Unmanaged {
+                view_items.push(CheatItem {
+                    key: "I",
+                    label: "Noise",
+                });
+            }
         }
         PaneFocus::Detail | PaneFocus::Log => {
             nav_items.extend([
@@ -858,6 +1455,39 @@ fn cheat_groups(app: &App) -> Vec<CheatGroup> {
                     label: "Jump",
                 },
             ]);
+            if app.focus == PaneFocus::Log {
+                nav_items.push(CheatItem {
+                    key: "H",
+                    label: "History",
+                });
+            } else {
+                nav_items.push(CheatItem {
+                    key: "/",
+                    label: "Search",
+                });
+            }
+            if app.focus == PaneFocus::Detail && app.detail_hex.is_some() {
+                nav_items.push(CheatItem {
+                    key: "[/]",
+                    label: "Prev/next hex page",
+                });
+            }
+            if app.focus == PaneFocus::Detail && app.detail_kind == DetailKind::Diff {
+                nav_items.extend([
+                    CheatItem {
+                        key: "s",
+                        label: "Split/Unified",
+                    },
+                    CheatItem {
+                        key: "]h/[h",
+                        label: "Next/prev hunk",
+                    },
+                    CheatItem {
+                        key: "A/R",
+                        label: "Apply/re-add hunk",
+                    },
+                ]);
+            }
         }
     }
 
@@ -870,6 +1500,18 @@ fn cheat_groups(app: &App) -> Vec<CheatGroup> {
             key: "1-3",
             label: "Switch",
         },
+        CheatItem {
+            key: "+/-",
+            label: "Resize",
+        },
+        CheatItem {
+            key: "z",
+            label: if app.zoomed { "Unzoom" } else { "Zoom" },
+        },
+        CheatItem {
+            key: "L",
+            label: "Log pane",
+        },
     ]);
 
     vec![
@@ -896,6 +1538,7 @@ fn render_help_groups(app: &App, max_width: usize) -> Vec<Span<'static>> {
         return Vec::new();
     }
 
+    let theme = &app.theme;
     let groups = cheat_groups(app);
     let (selected, omitted) = fit_cheat_groups(&groups, max_width);
     let mut spans = Vec::new();
@@ -912,7 +1555,7 @@ fn render_help_groups(app: &App, max_width: usize) -> Vec<Span<'static>> {
         spans.push(Span::styled(
             format!("{}:", group.title),
             Style::default()
-                .fg(Color::LightBlue)
+                .fg(theme.heading)
                 .add_modifier(Modifier::BOLD),
         ));
         spans.push(Span::raw(" "));
@@ -924,13 +1567,13 @@ fn render_help_groups(app: &App, max_width: usize) -> Vec<Span<'static>> {
             spans.push(Span::styled(
                 item.key.to_string(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.accent)
                     .add_modifier(Modifier::BOLD),
             ));
             spans.push(Span::raw(" "));
             spans.push(Span::styled(
                 item.label.to_string(),
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme.muted),
             ));
         }
     }
@@ -939,7 +1582,7 @@ fn render_help_groups(app: &App, max_width: usize) -> Vec<Span<'static>> {
         if !spans.is_empty() {
             spans.push(Span::raw("  "));
         }
-        spans.push(Span::styled("…", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled("…", Style::default().fg(theme.dim)));
     }
     spans
 }
@@ -1066,6 +1709,7 @@ fn compact_label(value: &str, max_chars: usize) -> String {
 }
 
 fn draw_modal(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
     match &app.modal {
         ModalState::None => {}
         ModalState::ListFilter { value, .. } => {
@@ -1082,8 +1726,8 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                 Line::from("Type to filter visible list items by path."),
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("query: ", Style::default().fg(Color::Gray)),
-                    Span::styled(shown, Style::default().fg(Color::Yellow)),
+                    Span::styled("query: ", Style::default().fg(theme.muted)),
+                    Span::styled(shown, Style::default().fg(theme.warning)),
                 ]),
                 Line::from(""),
                 Line::from("Enter: apply and close  Esc: cancel  Backspace: delete"),
@@ -1094,12 +1738,16 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     Block::default()
                         .title(" List Filter ")
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightBlue)),
+                        .border_style(Style::default().fg(theme.border_modal)),
                 )
                 .wrap(Wrap { trim: false });
             frame.render_widget(p, area);
         }
-        ModalState::Ignore { requests, selected } => {
+        ModalState::Ignore {
+            requests,
+            selected,
+            stage,
+        } => {
             let area = centered_rect(70, 42, frame.area());
             frame.render_widget(Clear, area);
 
@@ -1131,32 +1779,31 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                         format!("{prefix} {label}"),
                         if index == *selected {
                             Style::default()
-                                .fg(Color::Black)
-                                .bg(Color::LightYellow)
+                                .fg(theme.cursor_fg)
+                                .bg(theme.cursor_bg)
                                 .add_modifier(Modifier::BOLD)
                         } else {
-                            Style::default().fg(Color::White)
+                            Style::default().fg(theme.text)
                         },
                     ),
                     Span::raw("  "),
-                    Span::styled(
-                        description.to_string(),
-                        Style::default().fg(Color::DarkGray),
-                    ),
+                    Span::styled(description.to_string(), Style::default().fg(theme.dim)),
                 ]));
             }
 
             lines.push(Line::from(""));
-            lines.push(Line::from(
-                "Up/Down or j/k: select  Enter: apply  Esc: cancel",
-            ));
+            lines.push(Line::from(if *stage {
+                "Up/Down or j/k: select  Enter: add to queue  Esc: cancel"
+            } else {
+                "Up/Down or j/k: select  Enter: apply  Esc: cancel"
+            }));
 
             let p = Paragraph::new(lines)
                 .block(
                     Block::default()
                         .title(" Ignore Rule ")
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightBlue)),
+                        .border_style(Style::default().fg(theme.border_modal)),
                 )
                 .wrap(Wrap { trim: false });
             frame.render_widget(p, area);
@@ -1175,22 +1822,22 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                 filter.clone()
             };
             let query_style = if filter.is_empty() {
-                Style::default().fg(Color::DarkGray)
+                Style::default().fg(theme.dim)
             } else {
-                Style::default().fg(Color::Yellow)
+                Style::default().fg(theme.warning)
             };
             let filter_widget = Paragraph::new(vec![
                 Line::from(vec![
-                    Span::styled("query: ", Style::default().fg(Color::Gray)),
+                    Span::styled("query: ", Style::default().fg(theme.muted)),
                     Span::styled(query, query_style),
                 ]),
-                Line::from("Backspace: delete  Up/Down: select  Enter: run  Esc: close"),
+                Line::from("Up/Down: select  Enter: run  Tab: add to queue  Esc: close"),
             ])
             .block(
                 Block::default()
                     .title(" Action Filter ")
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::LightBlue)),
+                    .border_style(Style::default().fg(theme.border_modal)),
             )
             .wrap(Wrap { trim: false });
             frame.render_widget(filter_widget, sections[0]);
@@ -1217,7 +1864,7 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                         if matches!(row, ActionMenuRow::Action(_)) {
                             selectable.push(row_index);
                         }
-                        action_menu_row_item(row)
+                        action_menu_row_item(row, theme)
                     })
                     .collect();
                 (items, selectable)
@@ -1228,12 +1875,12 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     Block::default()
                         .title(" Action Menu ")
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Cyan)),
+                        .border_style(Style::default().fg(theme.border_focus)),
                 )
                 .highlight_style(
                     Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::LightYellow)
+                        .fg(theme.cursor_fg)
+                        .bg(theme.cursor_bg)
                         .add_modifier(Modifier::BOLD),
                 )
                 .highlight_symbol("▶ ");
@@ -1250,6 +1897,7 @@ fn draw_modal(frame: &mut Frame, app: &App) {
             request,
             step,
             typed,
+            protected_by,
         } => {
             let area = centered_rect(70, 45, frame.area());
             frame.render_widget(Clear, area);
@@ -1272,11 +1920,25 @@ fn draw_modal(frame: &mut Frame, app: &App) {
             if let Some(attrs) = &request.chattr_attrs {
                 lines.push(Line::from(format!("attributes: {attrs}")));
             }
+            if let Some(pattern) = protected_by {
+                lines.push(
+                    Line::from(format!("protected path: matches {pattern}")).style(
+                        Style::default()
+                            .fg(theme.danger)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+            }
 
             lines.push(Line::from(""));
             match step {
                 ConfirmStep::Primary => {
-                    if request.requires_strict_confirmation() {
+                    if protected_by.is_some() && !request.requires_strict_confirmation() {
+                        lines.push(Line::from("Enter: Continue  Esc: Cancel"));
+                        lines.push(Line::from(
+                            "This target is protected. A confirmation phrase is required next.",
+                        ));
+                    } else if request.requires_strict_confirmation() {
                         lines.push(Line::from("Enter: Continue  Esc: Cancel"));
                         lines.push(Line::from(
                             "This is a dangerous action. A confirmation phrase is always required.",
@@ -1284,7 +1946,10 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     } else {
                         lines.push(Line::from("Enter: Run  Esc: Cancel"));
                     }
-                    if request.action.is_dangerous() && !request.requires_strict_confirmation() {
+                    if request.action.is_dangerous()
+                        && !request.requires_strict_confirmation()
+                        && protected_by.is_none()
+                    {
                         lines.push(Line::from(
                             "This is a dangerous action. A confirmation phrase is required next.",
                         ));
@@ -1294,16 +1959,18 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     lines.push(Line::from(
                         "Type the confirmation phrase and press Enter to run, Esc to cancel.",
                     ));
-                    if let Some(phrase) = request.confirmation_phrase() {
+                    if let Some(phrase) = request.required_phrase(protected_by.is_some()) {
                         lines.push(
                             Line::from(format!("required: {phrase}")).style(
-                                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                                Style::default()
+                                    .fg(theme.error)
+                                    .add_modifier(Modifier::BOLD),
                             ),
                         );
                     }
                     lines.push(
                         Line::from(format!("input: {typed}"))
-                            .style(Style::default().fg(Color::Yellow)),
+                            .style(Style::default().fg(theme.warning)),
                     );
                 }
             }
@@ -1313,7 +1980,7 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     Block::default()
                         .title(title)
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightRed)),
+                        .border_style(Style::default().fg(theme.border_danger)),
                 )
                 .wrap(Wrap { trim: false });
 
@@ -1342,7 +2009,7 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                 )),
                 Line::from(""),
                 Line::from(prompt),
-                Line::from(format!("> {value}")).style(Style::default().fg(Color::Yellow)),
+                Line::from(format!("> {value}")).style(Style::default().fg(theme.warning)),
                 Line::from("Enter: Confirm  Esc: Cancel"),
             ];
 
@@ -1351,12 +2018,870 @@ fn draw_modal(frame: &mut Frame, app: &App) {
                     Block::default()
                         .title(" Input ")
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::LightBlue)),
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(p, area);
+        }
+        ModalState::ChattrPicker(picker) => {
+            let area = centered_rect(64, 70, frame.area());
+            frame.render_widget(Clear, area);
+
+            let p = Paragraph::new(chattr_picker_lines(picker, theme))
+                .block(
+                    Block::default()
+                        .title(" Attributes ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(p, area);
+        }
+        ModalState::Queue { selected } => {
+            let area = centered_rect(80, 60, frame.area());
+            frame.render_widget(Clear, area);
+
+            let title = if app.batch_in_progress() {
+                format!(
+                    " Action Queue: running {}/{} ",
+                    app.batch_completed_count(),
+                    app.batch_total()
+                )
+            } else {
+                format!(" Action Queue ({}) ", app.action_queue.len())
+            };
+            let p = Paragraph::new(queue_lines(app, *selected))
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(p, area);
+        }
+        ModalState::BatchResults { summary, selected } => {
+            let area = centered_rect(80, 60, frame.area());
+            frame.render_widget(Clear, area);
+
+            let p = Paragraph::new(batch_results_lines(summary, *selected, theme))
+                .block(
+                    Block::default()
+                        .title(" Batch Results ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_danger)),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(p, area);
+        }
+        ModalState::DetailSearch { value, .. } => {
+            let area = centered_rect(62, 22, frame.area());
+            frame.render_widget(Clear, area);
+
+            let matches = match app.detail_match_position() {
+                Some((_, total)) => format!("{total} matching line(s)"),
+                None if value.is_empty() => String::new(),
+                None => "no matches".to_string(),
+            };
+            let lines = vec![
+                Line::from("Search the diff / preview text (case-insensitive)."),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("query: ", Style::default().fg(theme.muted)),
+                    Span::styled(value.clone(), Style::default().fg(theme.warning)),
+                ]),
+                Line::from(Span::styled(matches, Style::default().fg(theme.muted))),
+                Line::from("Enter: keep  Esc: cancel  n/N: next/prev match after closing"),
+            ];
+
+            let p = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(" Detail Search ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
                 )
                 .wrap(Wrap { trim: false });
             frame.render_widget(p, area);
         }
+        ModalState::Finder(finder) => {
+            let area = centered_rect(70, 70, frame.area());
+            frame.render_widget(Clear, area);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ])
+                .split(area);
+
+            let query = Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().fg(theme.muted)),
+                Span::styled(finder.query.clone(), Style::default().fg(theme.warning)),
+            ]))
+            .block(
+                Block::default()
+                    .title(format!(
+                        " Find ({}/{}) ",
+                        finder.results.len(),
+                        finder.candidates.len()
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border_modal)),
+            );
+            frame.render_widget(query, rows[0]);
+
+            let items: Vec<ListItem> = finder_lines(finder, theme)
+                .into_iter()
+                .map(ListItem::new)
+                .collect();
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .highlight_style(Style::default().bg(theme.dim));
+            let mut state = ListState::default();
+            state.select((!finder.results.is_empty()).then_some(finder.selected));
+            frame.render_stateful_widget(list, rows[1], &mut state);
+
+            frame.render_widget(
+                Paragraph::new("type: filter  Up/Down or C-n/C-p: move  Enter: go to  Esc: close")
+                    .style(Style::default().fg(theme.muted)),
+                rows[2],
+            );
+        }
+        ModalState::ContentSearch(search) => {
+            let area = centered_rect(80, 70, frame.area());
+            frame.render_widget(Clear, area);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ])
+                .split(area);
+
+            let status = if search.searching {
+                "searching…".to_string()
+            } else if let Some(message) = &search.message {
+                message.clone()
+            } else if search.searched.is_some() {
+                let count = search.results.matches.len();
+                let more = if search.results.truncated { "+" } else { "" };
+                let stale = if search.is_current() {
+                    ""
+                } else {
+                    ", Enter to update"
+                };
+                format!("{count}{more} matches{stale}")
+            } else {
+                "Enter to search".to_string()
+            };
+            let query = Paragraph::new(Line::from(vec![
+                Span::styled("> ", Style::default().fg(theme.muted)),
+                Span::styled(search.query.clone(), Style::default().fg(theme.warning)),
+            ]))
+            .block(
+                Block::default()
+                    .title(format!(
+                        " Search {} files ({status}) ",
+                        search.scope.label()
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.border_modal)),
+            );
+            frame.render_widget(query, rows[0]);
+
+            let items: Vec<ListItem> = content_search_lines(search, theme)
+                .into_iter()
+                .map(ListItem::new)
+                .collect();
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .highlight_style(Style::default().bg(theme.dim));
+            let mut state = ListState::default();
+            state.select((!search.results.matches.is_empty()).then_some(search.selected));
+            frame.render_stateful_widget(list, rows[1], &mut state);
+
+            frame.render_widget(
+                Paragraph::new(
+                    "type: query  Tab: destination/source/rendered  Enter: search / open  Up/Down: move  Esc: close",
+                )
+                .style(Style::default().fg(theme.muted)),
+                rows[2],
+            );
+        }
+        ModalState::RepoDiff(browser) => {
+            let area = centered_rect(95, 95, frame.area());
+            frame.render_widget(Clear, area);
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(area);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .split(rows[0]);
+
+            let items: Vec<ListItem> = repo_diff_index_lines(app, browser)
+                .into_iter()
+                .map(ListItem::new)
+                .collect();
+            let index = List::new(items)
+                .block(
+                    Block::default()
+                        .title(format!(" Files ({}) ", browser.files.len()))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .highlight_style(
+                    Style::default()
+                        .fg(theme.selection_fg)
+                        .bg(theme.selection_bg)
+                        .add_modifier(Modifier::BOLD),
+                );
+            let mut state = ListState::default();
+            state.select(Some(browser.selected));
+            frame.render_stateful_widget(index, columns[0], &mut state);
+
+            let sections = Paragraph::new(repo_diff_section_lines(browser, theme))
+                .block(
+                    Block::default()
+                        .title(" Diff: (all) ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .scroll((clamp_to_u16(browser.scroll), 0));
+            frame.render_widget(sections, columns[1]);
+
+            frame.render_widget(
+                Paragraph::new(
+                    "j/k: file  Enter: fold  z: fold all  Space: mark  PgUp/PgDn: scroll  Esc: close",
+                )
+                .style(Style::default().fg(theme.muted)),
+                rows[1],
+            );
+        }
+        ModalState::Merge(view) => {
+            let area = centered_rect(90, 90, frame.area());
+            frame.render_widget(Clear, area);
+
+            let (lines, selected_line) = merge_lines(view, theme);
+            let scroll = selected_line.saturating_sub(usize::from(area.height / 3));
+            let p = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(" Merge ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_modal)),
+                )
+                .scroll((clamp_to_u16(scroll), 0));
+            frame.render_widget(p, area);
+        }
+        ModalState::HunkConfirm {
+            target,
+            hunk,
+            direction,
+            position,
+            protected_by,
+            typed,
+        } => {
+            let area = centered_rect(70, 55, frame.area());
+            frame.render_widget(Clear, area);
+
+            let lines = hunk_confirm_lines(
+                target,
+                hunk,
+                *direction,
+                *position,
+                protected_by.as_deref(),
+                typed,
+                theme,
+            );
+            let p = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(" Accept Hunk ")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_warning)),
+                )
+                .wrap(Wrap { trim: false });
+            frame.render_widget(p, area);
+        }
+    }
+}
+
+/// One row per finder result: the list it comes from, then the path with the
+/// matched characters highlighted.
+fn finder_lines(finder: &FuzzyFinder, theme: &Theme) -> Vec<Line<'static>> {
+    let matched = Style::default()
+        .fg(theme.warning)
+        .add_modifier(Modifier::BOLD);
+    finder
+        .results
+        .iter()
+        .filter_map(|result| {
+            let candidate = finder.candidates.get(result.candidate)?;
+            let mut spans = vec![Span::styled(
+                format!("{:<10}", candidate.view.title()),
+                Style::default().fg(theme.muted),
+            )];
+            let path = candidate.path.to_string_lossy();
+            let mut run = String::new();
+            let mut run_matched = false;
+            for (index, ch) in path.chars().enumerate() {
+                let is_match = result.positions.contains(&index);
+                if is_match != run_matched && !run.is_empty() {
+                    let style = if run_matched {
+                        matched
+                    } else {
+                        Style::default()
+                    };
+                    spans.push(Span::styled(std::mem::take(&mut run), style));
+                }
+                run_matched = is_match;
+                run.push(ch);
+            }
+            if !run.is_empty() {
+                let style = if run_matched {
+                    matched
+                } else {
+                    Style::default()
+                };
+                spans.push(Span::styled(run, style));
+            }
+            Some(Line::from(spans))
+        })
+        .collect()
+}
+
+/// One row per content match, `path:line: snippet`, with the searched text
+/// highlighted in the snippet.
+fn content_search_lines(search: &ContentSearch, theme: &Theme) -> Vec<Line<'static>> {
+    let query = search
+        .searched
+        .as_ref()
+        .map_or("", |(query, _)| query.as_str());
+    search
+        .results
+        .matches
+        .iter()
+        .map(|hit| {
+            let snippet = highlight_matches(Line::from(hit.snippet.clone()), query, theme);
+            let mut spans = vec![
+                Span::raw(hit.path.to_string_lossy().into_owned()),
+                Span::styled(format!(":{}: ", hit.line), Style::default().fg(theme.muted)),
+            ];
+            spans.extend(snippet.spans);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn repo_diff_index_lines(app: &App, browser: &RepoDiffBrowser) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    browser
+        .files
+        .iter()
+        .map(|file| {
+            let mark = if app.is_marked(&file.path) {
+                "[x] "
+            } else {
+                "[ ] "
+            };
+            Line::from(vec![
+                Span::raw(mark),
+                Span::raw(file.path.display().to_string()),
+                Span::styled(
+                    format!(" +{}", file.added),
+                    Style::default().fg(theme.diff_add),
+                ),
+                Span::styled(
+                    format!(" -{}", file.removed),
+                    Style::default().fg(theme.diff_remove),
+                ),
+            ])
+        })
+        .collect()
+}
+
+/// Per-file sections; each starts with a fold header in place of its
+/// `diff --git` line so line offsets match `RepoDiffBrowser::section_offsets`.
+fn repo_diff_section_lines(browser: &RepoDiffBrowser, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for (index, file) in browser.files.iter().enumerate() {
+        let collapsed = browser.collapsed.contains(&index);
+        let mut header_style = Style::default()
+            .fg(theme.diff_file)
+            .add_modifier(Modifier::BOLD);
+        if index == browser.selected {
+            header_style = header_style.add_modifier(Modifier::REVERSED);
+        }
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(
+                    "{} {}",
+                    if collapsed { "▶" } else { "▼" },
+                    file.path.display()
+                ),
+                header_style,
+            ),
+            Span::styled(
+                format!("  +{}", file.added),
+                Style::default().fg(theme.diff_add),
+            ),
+            Span::styled(
+                format!(" -{}", file.removed),
+                Style::default().fg(theme.diff_remove),
+            ),
+        ]));
+        if !collapsed {
+            lines.extend(colorized_diff_lines(&file.text, theme).into_iter().skip(1));
+        }
+    }
+    lines
+}
+
+/// Unchanged lines kept around each change block in the merge screen.
+const MERGE_CONTEXT_LINES: usize = 3;
+
+/// Lines of the merge screen and the line index where the selected change
+/// block starts, used to keep it in view.
+fn merge_lines(view: &MergeView, theme: &Theme) -> (Vec<Line<'static>>, usize) {
+    let mut lines = vec![
+        Line::from(format!(
+            "{} <- {}",
+            view.target.display(),
+            view.source.display()
+        )),
+        Line::from(format!(
+            "{} block(s), {} conflict(s), {} unresolved",
+            view.change_count(),
+            view.conflict_count(),
+            view.unresolved_count()
+        )),
+    ];
+    if let Some(pattern) = &view.protected_by {
+        lines.push(
+            Line::from(format!("protected path: matches {pattern}")).style(
+                Style::default()
+                    .fg(theme.danger)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    if let Some(message) = &view.message {
+        lines.push(Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(theme.warning),
+        )));
+    }
+    if let Some(typed) = &view.confirm {
+        lines.push(Line::from(
+            "Type the confirmation phrase and press Enter to write, Esc to go back.",
+        ));
+        lines.push(
+            Line::from(format!("required: {}", view.confirmation_phrase())).style(
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+        lines.push(Line::from(format!("typed: {typed}")));
+    } else {
+        lines.push(Line::from(
+            "j/k: block  o: destination  t: source  b: both  w: write source  Esc: cancel",
+        ));
+    }
+
+    let context = Style::default().fg(theme.muted);
+    let muted = Style::default().fg(theme.dim);
+    let mut selected_line = 0;
+    let mut change_index = 0;
+    let last_block = view.blocks.len().saturating_sub(1);
+    for (block_index, block) in view.blocks.iter().enumerate() {
+        match block {
+            MergeBlock::Stable(stable) => {
+                let head = if block_index == 0 {
+                    0
+                } else {
+                    MERGE_CONTEXT_LINES
+                };
+                let tail = if block_index == last_block {
+                    0
+                } else {
+                    MERGE_CONTEXT_LINES
+                };
+                if stable.len() > head + tail {
+                    for line in &stable[..head] {
+                        lines.push(Line::from(Span::styled(format!("  {line}"), context)));
+                    }
+                    lines.push(Line::from(Span::styled(
+                        format!("  ... {} unchanged line(s)", stable.len() - head - tail),
+                        muted,
+                    )));
+                    for line in &stable[stable.len() - tail..] {
+                        lines.push(Line::from(Span::styled(format!("  {line}"), context)));
+                    }
+                } else {
+                    for line in stable {
+                        lines.push(Line::from(Span::styled(format!("  {line}"), context)));
+                    }
+                }
+            }
+            MergeBlock::Change(change) => {
+                let is_selected = change_index == view.selected;
+                if is_selected {
+                    selected_line = lines.len();
+                }
+                let state = match (change.resolution, change.conflict) {
+                    (Some(resolution), _) => format!("-> {}", resolution.label()),
+                    (None, true) => "conflict, unresolved".to_string(),
+                    (None, false) => "unresolved".to_string(),
+                };
+                let mut header_style = if change.resolution.is_none() {
+                    Style::default().fg(theme.danger)
+                } else {
+                    Style::default().fg(theme.accent)
+                };
+                if is_selected {
+                    header_style = header_style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+                }
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "== block {}/{} ({state})",
+                        change_index + 1,
+                        view.change_count()
+                    ),
+                    header_style,
+                )));
+
+                let sections = [
+                    ("destination", &change.ours, theme.diff_add),
+                    ("base", &change.base, theme.dim),
+                    ("source", &change.theirs, theme.merge_source),
+                ];
+                for (label, side, color) in sections {
+                    lines.push(Line::from(Span::styled(
+                        format!(" {label}:"),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    )));
+                    if side.is_empty() {
+                        lines.push(Line::from(Span::styled("   (no lines)", muted)));
+                    }
+                    for line in side.iter() {
+                        lines.push(Line::from(Span::styled(
+                            format!("   {line}"),
+                            Style::default().fg(color),
+                        )));
+                    }
+                }
+                change_index += 1;
+            }
+        }
+    }
+
+    (lines, selected_line)
+}
+
+const HUNK_PREVIEW_LINES: usize = 12;
+
+fn hunk_confirm_lines(
+    target: &Path,
+    hunk: &Hunk,
+    direction: HunkDirection,
+    (number, total): (usize, usize),
+    protected_by: Option<&str>,
+    typed: &str,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let destination = match direction {
+        HunkDirection::ToDestination => "the destination file (partial apply)",
+        HunkDirection::ToSource => "the source file (partial re-add)",
+    };
+    let mut lines = vec![
+        Line::from(format!("hunk {number}/{total} of {}", target.display())),
+        Line::from(format!("write into {destination}")),
+    ];
+    if let Some(pattern) = protected_by {
+        lines.push(
+            Line::from(format!("protected path: matches {pattern}")).style(
+                Style::default()
+                    .fg(theme.danger)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    lines.push(Line::from(""));
+
+    // Show the change as it will be written: a re-add runs the diff backwards.
+    let changes: Vec<Line<'static>> = hunk
+        .lines
+        .iter()
+        .map(|line| {
+            let (marker, text) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+            let marker = match (direction, marker) {
+                (HunkDirection::ToSource, "-") => "+",
+                (HunkDirection::ToSource, "+") => "-",
+                (_, marker) => marker,
+            };
+            let style = match marker {
+                "-" => Style::default().fg(theme.diff_remove),
+                "+" => Style::default().fg(theme.diff_add),
+                _ => Style::default().fg(theme.dim),
+            };
+            Line::from(Span::styled(format!("{marker}{text}"), style))
+        })
+        .collect();
+    let hidden = changes.len().saturating_sub(HUNK_PREVIEW_LINES);
+    lines.extend(changes.into_iter().take(HUNK_PREVIEW_LINES));
+    if hidden > 0 {
+        lines.push(Line::from(format!("... {hidden} more line(s)")));
+    }
+    lines.push(Line::from(""));
+
+    if protected_by.is_some() {
+        lines.push(Line::from(
+            "Type the confirmation phrase and press Enter to write, Esc to cancel.",
+        ));
+        lines.push(
+            Line::from(format!(
+                "required: {}",
+                direction.confirmation_phrase(target)
+            ))
+            .style(
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+        lines.push(Line::from(format!("typed: {typed}")));
+    } else {
+        lines.push(Line::from("Enter/y: write  Esc/n: cancel"));
+    }
+    lines
+}
+
+fn chattr_picker_lines(picker: &ChattrPicker, theme: &Theme) -> Vec<Line<'static>> {
+    let sample = picker
+        .targets
+        .first()
+        .map_or_else(|| "(none)".to_string(), |path| path.display().to_string());
+    let mut lines = vec![
+        Line::from(format!("targets: {}", picker.targets.len())),
+        Line::from(format!("sample target: {sample}")),
+    ];
+    if picker.sources.is_none() && picker.message.is_none() {
+        lines.push(Line::from(Span::styled(
+            "loading current attributes…",
+            Style::default().fg(theme.dim),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    for (index, attribute) in ChattrAttribute::ALL.iter().enumerate() {
+        let checkbox = match picker.effective(index) {
+            TriState::On => "[x]",
+            TriState::Off => "[ ]",
+            TriState::Mixed => "[-]",
+            TriState::Unknown => "[?]",
+        };
+        let change = match picker.changes[index] {
+            AttrChange::Keep => "",
+            AttrChange::Set => "  (+ set)",
+            AttrChange::Clear => "  (- clear)",
+        };
+        let style = if index == picker.selected {
+            Style::default()
+                .fg(theme.cursor_fg)
+                .bg(theme.cursor_bg)
+                .add_modifier(Modifier::BOLD)
+        } else if picker.changes[index] == AttrChange::Keep {
+            Style::default().fg(theme.text)
+        } else {
+            Style::default().fg(theme.accent_strong)
+        };
+        let prefix = if index == picker.selected { "▶" } else { " " };
+        lines.push(Line::from(Span::styled(
+            format!("{prefix} {checkbox} {}{change}", attribute.label()),
+            style,
+        )));
+    }
+
+    lines.push(Line::from(""));
+    let modifiers = picker.attribute_string();
+    lines.push(Line::from(format!(
+        "chattr: {}",
+        if modifiers.is_empty() {
+            "(no changes)"
+        } else {
+            &modifiers
+        }
+    )));
+    if let Some(message) = &picker.message {
+        lines.push(Line::from(Span::styled(
+            message.clone(),
+            Style::default().fg(theme.danger),
+        )));
+    }
+    lines.push(Line::from(
+        "Space: toggle  j/k: select  Enter: apply  i: type modifiers  Esc: cancel",
+    ));
+    lines
+}
+
+fn queue_lines(app: &App, selected: usize) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    let describe = |request: &ActionRequest| {
+        let target = request
+            .target
+            .as_ref()
+            .map_or_else(|| "(none)".to_string(), |p| p.display().to_string());
+        match &request.chattr_attrs {
+            Some(attrs) => format!("{:<12} {target}  [{attrs}]", request.action.label()),
+            None => format!("{:<12} {target}", request.action.label()),
+        }
+    };
+
+    if app.batch_in_progress() {
+        let mut lines: Vec<Line<'static>> = app
+            .batch_progress_items()
+            .into_iter()
+            .map(|(request, state)| {
+                let (mark, style) = match state {
+                    BatchItemState::Pending => ("·", Style::default().fg(theme.dim)),
+                    BatchItemState::Running => (
+                        "▶",
+                        Style::default()
+                            .fg(theme.notice)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    BatchItemState::Passed => ("✓", Style::default().fg(theme.success)),
+                    BatchItemState::Failed => ("✗", Style::default().fg(theme.danger)),
+                };
+                Line::from(Span::styled(
+                    format!("{mark} {}", describe(&request)),
+                    style,
+                ))
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from("Esc: close (the queue keeps running)"));
+        return lines;
+    }
+
+    let mut lines = Vec::new();
+    if app.action_queue.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "Queue is empty. Press Tab in the action menu to stage an action.",
+            Style::default().fg(theme.dim),
+        )));
+    }
+    for (index, request) in app.action_queue.iter().enumerate() {
+        let style = if index == selected {
+            Style::default()
+                .fg(theme.cursor_fg)
+                .bg(theme.cursor_bg)
+                .add_modifier(Modifier::BOLD)
+        } else if request.action.is_dangerous() {
+            Style::default().fg(theme.danger)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{:>2}. {}", index + 1, describe(request)),
+            style,
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "Enter: run all  j/k: select  J/K: move  x: drop  s/o: save/load plan  Esc: close",
+    ));
+    if let Some(path) = app.plan_path() {
+        lines.push(Line::from(Span::styled(
+            format!("plan file: {}", path.display()),
+            Style::default().fg(theme.dim),
+        )));
+    }
+    lines
+}
+
+fn batch_results_lines(
+    summary: &BatchSummary,
+    selected: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let failures = summary.failures();
+    let mut lines = vec![
+        Line::from(format!("action: {}", summary.label)),
+        Line::from(vec![
+            Span::styled(
+                format!("ok {}", summary.passed()),
+                Style::default().fg(theme.success),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("failed {}", failures.len()),
+                Style::default().fg(theme.danger),
+            ),
+            Span::raw("  "),
+            Span::styled(
+                format!("skipped {}", summary.skipped.len()),
+                Style::default().fg(theme.muted),
+            ),
+        ]),
+        Line::from(""),
+    ];
+
+    if !failures.is_empty() {
+        lines.push(Line::from("Failed targets:"));
+    }
+    for (index, failure) in failures.iter().enumerate() {
+        let target = failure
+            .request
+            .target
+            .as_ref()
+            .map_or_else(|| "(none)".to_string(), |p| p.display().to_string());
+        let style = if index == selected {
+            Style::default()
+                .fg(theme.cursor_fg)
+                .bg(theme.cursor_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let prefix = if index == selected { "▶" } else { " " };
+        lines.push(Line::from(Span::styled(
+            format!("{prefix} {target}"),
+            style,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("    {}", squash_lines(&failure.detail)),
+            Style::default().fg(theme.danger),
+        )));
+    }
+
+    if !summary.skipped.is_empty() {
+        lines.push(Line::from(format!(
+            "{} item(s) skipped after the batch stopped.",
+            summary.skipped.len()
+        )));
     }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        "r: Retry failed (and skipped)  j/k: Select  Enter/Esc: Close",
+    ));
+    lines
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1447,34 +2972,34 @@ fn action_menu_text(action: Action) -> String {
     }
 }
 
-fn action_menu_item(action: Action) -> ListItem<'static> {
+fn action_menu_item(action: Action, theme: &Theme) -> ListItem<'static> {
     let text = action_menu_text(action);
     let style = if action.is_dangerous() {
-        Style::default().fg(Color::LightRed)
+        Style::default().fg(theme.danger)
     } else {
-        Style::default().fg(Color::Gray)
+        Style::default().fg(theme.muted)
     };
     ListItem::new(Line::styled(text, style))
 }
 
-fn action_menu_row_item(row: ActionMenuRow) -> ListItem<'static> {
+fn action_menu_row_item(row: ActionMenuRow, theme: &Theme) -> ListItem<'static> {
     match row {
         ActionMenuRow::Header(section) => ListItem::new(Line::styled(
             format!(" {} ", section.title()),
             Style::default()
-                .fg(Color::LightBlue)
+                .fg(theme.heading)
                 .add_modifier(Modifier::BOLD),
         )),
         ActionMenuRow::Spacer => ListItem::new(Line::from("")),
-        ActionMenuRow::Action(action) => action_menu_item(action),
+        ActionMenuRow::Action(action) => action_menu_item(action, theme),
     }
 }
 
-fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
+fn colorized_diff_lines(diff: &str, theme: &Theme) -> Vec<Line<'static>> {
     if diff.trim().is_empty() {
         return vec![Line::from(Span::styled(
             "No diff available.",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.diff_meta),
         ))];
     }
 
@@ -1490,7 +3015,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.diff_file)
                     .add_modifier(Modifier::BOLD),
             )));
             continue;
@@ -1505,7 +3030,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.diff_meta),
             )));
             continue;
         }
@@ -1513,7 +3038,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with("--- ") {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.diff_remove),
             )));
             continue;
         }
@@ -1521,7 +3046,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with("+++ ") {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme.diff_add),
             )));
             continue;
         }
@@ -1529,7 +3054,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with("@@") {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(theme.diff_hunk),
             )));
             continue;
         }
@@ -1538,7 +3063,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
                 Style::default()
-                    .fg(Color::DarkGray)
+                    .fg(theme.diff_meta)
                     .add_modifier(Modifier::ITALIC),
             )));
             continue;
@@ -1547,7 +3072,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with('+') {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Green).bg(Color::Rgb(12, 32, 12)),
+                Style::default().fg(theme.diff_add).bg(theme.diff_add_bg),
             )));
             continue;
         }
@@ -1555,7 +3080,9 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with('-') {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Red).bg(Color::Rgb(40, 14, 14)),
+                Style::default()
+                    .fg(theme.diff_remove)
+                    .bg(theme.diff_remove_bg),
             )));
             continue;
         }
@@ -1563,7 +3090,7 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
         if raw.starts_with(' ') {
             out.push(Line::from(Span::styled(
                 raw.to_string(),
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme.diff_context),
             )));
             continue;
         }
@@ -1574,6 +3101,140 @@ fn colorized_diff_lines(diff: &str) -> Vec<Line<'static>> {
     out
 }
 
+fn split_diff_lines(diff: &str, width: usize, theme: &Theme) -> Vec<Line<'static>> {
+    if diff.trim().is_empty() {
+        return colorized_diff_lines(diff, theme);
+    }
+
+    let rows = split_rows(diff);
+    let max_number = rows
+        .iter()
+        .filter_map(|row| match &row.kind {
+            SplitRowKind::Lines { old, new } => {
+                old.iter().chain(new.iter()).map(|cell| cell.number).max()
+            }
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let number_width = max_number.to_string().len().max(3);
+    let separator = " │ ";
+    let column = width.saturating_sub(text_width(separator)) / 2;
+
+    rows.into_iter()
+        .map(|row| match row.kind {
+            SplitRowKind::FileHeader(text) => {
+                let style = if text.starts_with("diff --git ") {
+                    Style::default()
+                        .fg(theme.diff_file)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.diff_meta)
+                };
+                Line::from(Span::styled(text, style))
+            }
+            SplitRowKind::HunkHeader(text) => {
+                Line::from(Span::styled(text, Style::default().fg(theme.diff_hunk)))
+            }
+            SplitRowKind::Note(text) => Line::from(Span::styled(
+                text,
+                Style::default()
+                    .fg(theme.diff_meta)
+                    .add_modifier(Modifier::ITALIC),
+            )),
+            SplitRowKind::Lines { old, new } => {
+                let mut spans = split_cell_spans(
+                    old.as_ref(),
+                    new.as_ref(),
+                    true,
+                    number_width,
+                    column,
+                    theme,
+                );
+                spans.push(Span::styled(
+                    separator,
+                    Style::default().fg(theme.diff_meta),
+                ));
+                spans.extend(split_cell_spans(
+                    new.as_ref(),
+                    old.as_ref(),
+                    false,
+                    number_width,
+                    column,
+                    theme,
+                ));
+                Line::from(spans)
+            }
+        })
+        .collect()
+}
+
+/// Renders one side of a split row padded to `width`. Changed lines paired
+/// with a counterpart highlight only the differing middle part.
+fn split_cell_spans(
+    cell: Option<&SplitCell>,
+    other: Option<&SplitCell>,
+    is_old: bool,
+    number_width: usize,
+    width: usize,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let Some(cell) = cell else {
+        return vec![Span::raw(" ".repeat(width))];
+    };
+
+    let (fg, bg, strong_bg) = if is_old {
+        (
+            theme.diff_remove,
+            theme.diff_remove_bg,
+            theme.diff_remove_emphasis_bg,
+        )
+    } else {
+        (
+            theme.diff_add,
+            theme.diff_add_bg,
+            theme.diff_add_emphasis_bg,
+        )
+    };
+    let text = cell.text.replace('\t', "    ");
+    let mut spans = vec![Span::styled(
+        format!("{:>number_width$} ", cell.number),
+        Style::default().fg(theme.diff_meta),
+    )];
+
+    if !cell.changed {
+        spans.push(Span::styled(text, Style::default().fg(theme.diff_context)));
+    } else if let Some(other) = other.filter(|other| other.changed) {
+        let other_text = other.text.replace('\t', "    ");
+        let (old_range, new_range) = if is_old {
+            changed_span(&text, &other_text)
+        } else {
+            changed_span(&other_text, &text)
+        };
+        let (start, end) = if is_old { old_range } else { new_range };
+        let chars: Vec<char> = text.chars().collect();
+        let base = Style::default().fg(fg).bg(bg);
+        spans.push(Span::styled(
+            chars[..start].iter().collect::<String>(),
+            base,
+        ));
+        spans.push(Span::styled(
+            chars[start..end].iter().collect::<String>(),
+            base.bg(strong_bg).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(chars[end..].iter().collect::<String>(), base));
+    } else {
+        spans.push(Span::styled(text, Style::default().fg(fg).bg(bg)));
+    }
+
+    let mut clipped = clip_spans_to_width(spans, width);
+    let used: usize = clipped.iter().map(|span| text_width(&span.content)).sum();
+    if used < width {
+        clipped.push(Span::raw(" ".repeat(width - used)));
+    }
+    clipped
+}
+
 fn ansi_line_to_spans(input: &str) -> Option<Line<'static>> {
     if !input.contains('\u{1b}') {
         return None;
@@ -1695,237 +3356,36 @@ fn ansi_bright_color(code: u16) -> Color {
     }
 }
 
-fn colorized_preview_lines(path: Option<&Path>, content: &str) -> Vec<Line<'static>> {
-    let language = detect_preview_language(path);
-    content
-        .lines()
-        .map(|line| colorized_preview_line(line, language))
+fn colorized_preview_lines(
+    path: Option<&Path>,
+    content: &str,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    highlight(path, content)
+        .into_iter()
+        .map(|runs| {
+            Line::from(
+                runs.into_iter()
+                    .map(|(token, text)| Span::styled(text, token_style(token, theme)))
+                    .collect::<Vec<_>>(),
+            )
+        })
         .collect()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PreviewLanguage {
-    Rust,
-    Shell,
-    Lua,
-    Python,
-    JsTs,
-    Json,
-    Toml,
-    Yaml,
-    Plain,
-}
-
-fn detect_preview_language(path: Option<&Path>) -> PreviewLanguage {
-    let ext = path
-        .and_then(|p| p.extension().and_then(|e| e.to_str()))
-        .map(str::to_ascii_lowercase);
-    match ext.as_deref() {
-        Some("rs") => PreviewLanguage::Rust,
-        Some("sh" | "bash" | "zsh" | "fish") => PreviewLanguage::Shell,
-        Some("lua") => PreviewLanguage::Lua,
-        Some("py") => PreviewLanguage::Python,
-        Some("js" | "mjs" | "cjs" | "ts" | "tsx" | "jsx") => PreviewLanguage::JsTs,
-        Some("json") => PreviewLanguage::Json,
-        Some("toml") => PreviewLanguage::Toml,
-        Some("yaml" | "yml") => PreviewLanguage::Yaml,
-        _ => {
-            let name = path
-                .and_then(|p| p.file_name().and_then(|n| n.to_str()))
-                .unwrap_or_default()
-                .to_ascii_lowercase();
-            match name.as_str() {
-                ".zshrc" | ".bashrc" | ".bash_profile" => PreviewLanguage::Shell,
-                _ => PreviewLanguage::Plain,
-            }
-        }
-    }
-}
-
-fn colorized_preview_line(line: &str, language: PreviewLanguage) -> Line<'static> {
-    let (code, comment) = split_comment(line, language);
-    let mut spans = colorize_code_tokens(code, language);
-
-    if let Some(comment) = comment {
-        spans.push(Span::styled(
-            comment.to_string(),
-            Style::default().fg(Color::DarkGray),
-        ));
-    }
-
-    if spans.is_empty() {
-        Line::from(String::new())
-    } else {
-        Line::from(spans)
-    }
-}
-
-fn split_comment(line: &str, language: PreviewLanguage) -> (&str, Option<&str>) {
-    let marker = match language {
-        PreviewLanguage::Rust | PreviewLanguage::JsTs => Some("//"),
-        PreviewLanguage::Shell
-        | PreviewLanguage::Python
-        | PreviewLanguage::Toml
-        | PreviewLanguage::Yaml => Some("#"),
-        PreviewLanguage::Lua => Some("--"),
-        PreviewLanguage::Json | PreviewLanguage::Plain => None,
-    };
-
-    if let Some(marker) = marker
-        && let Some(idx) = line.find(marker)
-    {
-        return (&line[..idx], Some(&line[idx..]));
-    }
-
-    (line, None)
-}
-
-fn colorize_code_tokens(code: &str, language: PreviewLanguage) -> Vec<Span<'static>> {
-    let chars: Vec<char> = code.chars().collect();
-    let mut spans = Vec::new();
-    let mut i = 0usize;
-
-    while i < chars.len() {
-        let ch = chars[i];
-
-        if ch == '"' || ch == '\'' {
-            let quote = ch;
-            let start = i;
-            i += 1;
-            while i < chars.len() {
-                if chars[i] == quote && chars[i.saturating_sub(1)] != '\\' {
-                    i += 1;
-                    break;
-                }
-                i += 1;
-            }
-            let token: String = chars[start..i].iter().collect();
-            let key_style = if is_object_key(&chars, i, language) {
-                Style::default().fg(Color::LightCyan)
-            } else {
-                Style::default().fg(Color::Yellow)
-            };
-            spans.push(Span::styled(token, key_style));
-            continue;
-        }
-
-        if ch.is_ascii_digit() {
-            let start = i;
-            i += 1;
-            while i < chars.len()
-                && (chars[i].is_ascii_hexdigit()
-                    || chars[i] == '_'
-                    || chars[i] == '.'
-                    || chars[i] == 'x'
-                    || chars[i] == 'X')
-            {
-                i += 1;
-            }
-            let token: String = chars[start..i].iter().collect();
-            spans.push(Span::styled(token, Style::default().fg(Color::Magenta)));
-            continue;
-        }
-
-        if is_word_start(ch) {
-            let start = i;
-            i += 1;
-            while i < chars.len() && is_word(chars[i]) {
-                i += 1;
-            }
-            let token: String = chars[start..i].iter().collect();
-            if preview_keywords(language).contains(&token.as_str()) {
-                spans.push(Span::styled(
-                    token,
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ));
-            } else {
-                spans.push(Span::from(token));
-            }
-            continue;
-        }
-
-        let start = i;
-        i += 1;
-        while i < chars.len() && !is_word_start(chars[i]) && !chars[i].is_ascii_digit() {
-            if chars[i] == '"' || chars[i] == '\'' {
-                break;
-            }
-            i += 1;
-        }
-        let token: String = chars[start..i].iter().collect();
-        spans.push(Span::styled(token, Style::default().fg(Color::Gray)));
-    }
-
-    spans
-}
-
-fn is_word_start(ch: char) -> bool {
-    ch.is_ascii_alphabetic() || ch == '_'
-}
-
-fn is_word(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || ch == '_'
-}
-
-fn is_object_key(chars: &[char], from: usize, language: PreviewLanguage) -> bool {
-    if !matches!(
-        language,
-        PreviewLanguage::Json | PreviewLanguage::Toml | PreviewLanguage::Yaml
-    ) {
-        return false;
-    }
-
-    let mut i = from;
-    while i < chars.len() && chars[i].is_whitespace() {
-        i += 1;
-    }
-    i < chars.len() && chars[i] == ':'
-}
-
-fn preview_keywords(language: PreviewLanguage) -> &'static [&'static str] {
-    match language {
-        PreviewLanguage::Rust => &[
-            "fn", "let", "mut", "pub", "struct", "enum", "impl", "use", "mod", "match", "if",
-            "else", "for", "while", "loop", "return", "async", "await", "trait", "where", "self",
-            "Self",
-        ],
-        PreviewLanguage::Shell => &[
-            "if", "then", "else", "fi", "for", "in", "do", "done", "case", "esac", "function",
-            "export", "local",
-        ],
-        PreviewLanguage::Lua => &[
-            "local", "function", "if", "then", "else", "elseif", "end", "for", "in", "do", "while",
-            "repeat", "until", "return",
-        ],
-        PreviewLanguage::Python => &[
-            "def", "class", "if", "elif", "else", "for", "while", "try", "except", "finally",
-            "return", "import", "from", "as", "with", "lambda",
-        ],
-        PreviewLanguage::JsTs => &[
-            "function",
-            "const",
-            "let",
-            "var",
-            "if",
-            "else",
-            "for",
-            "while",
-            "return",
-            "import",
-            "from",
-            "export",
-            "class",
-            "extends",
-            "async",
-            "await",
-            "type",
-            "interface",
-        ],
-        PreviewLanguage::Json | PreviewLanguage::Yaml => &["true", "false", "null"],
-        PreviewLanguage::Toml => &["true", "false"],
-        PreviewLanguage::Plain => &[],
+fn token_style(token: Token, theme: &Theme) -> Style {
+    let style = Style::default();
+    match token {
+        Token::Plain => style,
+        Token::Keyword => style.fg(theme.syntax_keyword).add_modifier(Modifier::BOLD),
+        Token::Key => style.fg(theme.syntax_key),
+        Token::String => style.fg(theme.syntax_string),
+        Token::Number => style.fg(theme.syntax_number),
+        Token::Punctuation => style.fg(theme.syntax_punctuation),
+        Token::Comment => style.fg(theme.syntax_comment),
+        Token::Section => style.fg(theme.heading).add_modifier(Modifier::BOLD),
+        Token::Variable => style.fg(theme.syntax_variable),
+        Token::Template => style.fg(theme.syntax_template).add_modifier(Modifier::BOLD),
     }
 }
 
@@ -1954,14 +3414,21 @@ mod tests {
     use super::{
         ActionMenuRow, ActionMenuSection, action_menu_rows, action_menu_text,
         build_action_menu_rows, cheat_groups, cheat_groups_width, colorized_diff_lines,
-        fit_cheat_groups, footer_hints, footer_left, hints_width, layout_hints, log_scroll_offset,
+        finder_lines, fit_cheat_groups, footer_hints, footer_left, hex_preview_lines,
+        highlight_matches, hint_regions, hints_width, layout_hints, log_scroll_offset,
+        metadata_lines, render_hints, repo_diff_index_lines, repo_diff_section_lines,
+        split_diff_lines, text_width,
     };
     use crate::app::{App, PaneFocus};
     use crate::config::AppConfig;
     use crate::domain::Action;
     use crate::domain::ListView;
-    use ratatui::style::Color;
-    use ratatui::text::Line;
+    use crate::fuzzy::{FinderCandidate, FuzzyFinder};
+    use crate::repo_diff::RepoDiffBrowser;
+    use crate::theme::Theme;
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use std::path::PathBuf;
 
     fn render_line_text(line: &Line<'_>) -> String {
         line.spans
@@ -1981,7 +3448,7 @@ mod tests {
             "\u{1b}[32m+new\u{1b}[0m\n",
         );
 
-        let lines = colorized_diff_lines(diff);
+        let lines = colorized_diff_lines(diff, &Theme::default());
         let rendered: Vec<String> = lines.iter().map(render_line_text).collect();
 
         assert!(rendered.iter().all(|line| !line.contains('\u{1b}')));
@@ -1997,7 +3464,7 @@ mod tests {
     fn colorized_diff_lines_preserve_ansi_colors_when_present() {
         let diff = "\u{1b}[38;5;81mdiff --git a/.zshrc b/.zshrc\u{1b}[0m\n\u{1b}[38;5;203m-old\u{1b}[0m\n\u{1b}[38;5;149m+new\u{1b}[0m\n";
 
-        let lines = colorized_diff_lines(diff);
+        let lines = colorized_diff_lines(diff, &Theme::default());
 
         assert_eq!(render_line_text(&lines[0]), "diff --git a/.zshrc b/.zshrc");
         assert_eq!(lines[0].spans[0].style.fg, Some(Color::Indexed(81)));
@@ -2036,6 +3503,21 @@ mod tests {
         assert!(rendered.contains("2/3 items"));
     }
 
+    #[test]
+    fn footer_left_shows_read_only_badge() {
+        let app = App::new(AppConfig {
+            read_only: true,
+            ..AppConfig::default()
+        });
+        let (spans, _) = footer_left(&app, 40);
+        let rendered = spans
+            .into_iter()
+            .map(|span| span.content.to_string())
+            .collect::<String>();
+
+        assert!(rendered.contains("READ-ONLY"));
+    }
+
     #[test]
     fn action_menu_text_marks_only_danger_actions() {
         let safe = action_menu_text(Action::Apply);
@@ -2135,6 +3617,23 @@ mod tests {
         assert!(!labels.contains(&"Fold"));
     }
 
+    #[test]
+    fn footer_hints_offer_audit_history_for_log_focus() {
+        let mut app = App::new(AppConfig::default());
+        app.focus = PaneFocus::Log;
+
+        let hints = footer_hints(&app);
+        let enabled: Vec<&str> = hints
+            .iter()
+            .filter(|hint| hint.enabled)
+            .map(|hint| hint.label)
+            .collect();
+
+        assert!(enabled.contains(&"Scroll"));
+        assert!(enabled.contains(&"History"));
+        assert!(!enabled.contains(&"Session"));
+    }
+
     #[test]
     fn footer_hints_include_help_globally() {
         let app = App::new(AppConfig::default());
@@ -2206,4 +3705,176 @@ mod tests {
         assert!(kept_titles.contains(&"Global"));
         assert!(cheat_groups_width(&groups, &selected, omitted) <= 56);
     }
+
+    #[test]
+    fn split_diff_lines_align_columns_and_highlight_changed_span() {
+        let diff = concat!(
+            "@@ -1,2 +1,2 @@\n",
+            "-export EDITOR=vim\n",
+            "+export EDITOR=nvim\n",
+            " tail\n",
+        );
+        let lines = split_diff_lines(diff, 63, &Theme::default());
+        assert_eq!(lines.len(), 3);
+
+        let changed = render_line_text(&lines[1]);
+        assert!(changed.starts_with("  1 export EDITOR=vim"));
+        assert_eq!(text_width(&changed), 63);
+        let (left, right) = changed.split_once(" │ ").expect("separator");
+        assert_eq!(text_width(left), text_width(right));
+        assert!(right.starts_with("  1 export EDITOR=nvim"));
+
+        let bold: Vec<String> = lines[1]
+            .spans
+            .iter()
+            .filter(|span| span.style.add_modifier.contains(Modifier::BOLD))
+            .map(|span| span.content.to_string())
+            .collect();
+        assert!(bold.contains(&"n".to_string()));
+    }
+
+    #[test]
+    fn repo_diff_sections_follow_offsets_and_index_shows_marks() {
+        let diff = concat!(
+            "diff --git a/.zshrc b/.zshrc\n",
+            "@@ -1 +1 @@\n",
+            "-old\n",
+            "+new\n",
+            "diff --git a/.vimrc b/.vimrc\n",
+            "@@ -1 +1,2 @@\n",
+            " set nu\n",
+            "+set rnu\n",
+        );
+        let mut browser = RepoDiffBrowser::new(diff);
+        browser.collapsed.insert(0);
+        let lines = repo_diff_section_lines(&browser, &Theme::default());
+        assert_eq!(browser.section_offsets(), vec![0, 1]);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(render_line_text(&lines[0]), "▶ .zshrc  +1 -1");
+        assert_eq!(render_line_text(&lines[1]), "▼ .vimrc  +1 -0");
+
+        let mut app = App::new(AppConfig::default());
+        app.toggle_mark(PathBuf::from(".vimrc"));
+        let index: Vec<String> = repo_diff_index_lines(&app, &browser)
+            .iter()
+            .map(render_line_text)
+            .collect();
+        assert_eq!(index, vec!["[ ] .zshrc +1 -1", "[x] .vimrc +1 -0"]);
+    }
+
+    #[test]
+    fn highlight_matches_splits_spans_and_keeps_base_style() {
+        let line = Line::from(vec![
+            Span::styled("+export ", Style::default().fg(Color::Green)),
+            Span::styled("EDITOR=vim", Style::default().fg(Color::Green)),
+        ]);
+        let highlighted = highlight_matches(line, "t ed", &Theme::default());
+
+        assert_eq!(render_line_text(&highlighted), "+export EDITOR=vim");
+        let marked: Vec<(String, Option<Color>)> = highlighted
+            .spans
+            .iter()
+            .filter(|span| span.style.bg == Some(Color::Yellow))
+            .map(|span| (span.content.to_string(), span.style.fg))
+            .collect();
+        assert_eq!(
+            marked,
+            vec![
+                ("t ".to_string(), Some(Color::Black)),
+                ("ED".to_string(), Some(Color::Black)),
+            ]
+        );
+        assert_eq!(highlighted.spans[0].content, "+expor");
+        assert_eq!(highlighted.spans[0].style.fg, Some(Color::Green));
+    }
+
+    #[test]
+    fn finder_lines_tag_view_and_highlight_matched_characters() {
+        let mut finder = FuzzyFinder::new(vec![FinderCandidate {
+            view: ListView::Managed,
+            path: std::path::PathBuf::from(".config/nvim/init.lua"),
+        }]);
+        finder.set_query("nvinit".to_string());
+
+        let lines = finder_lines(&finder, &Theme::default());
+        assert_eq!(lines.len(), 1);
+        assert_eq!(
+            render_line_text(&lines[0]),
+            "Managed   .config/nvim/init.lua"
+        );
+        let highlighted: Vec<String> = lines[0]
+            .spans
+            .iter()
+            .filter(|span| span.style.fg == Some(Color::Yellow))
+            .map(|span| span.content.to_string())
+            .collect();
+        assert_eq!(highlighted, vec!["nv", "init"]);
+    }
+
+    #[test]
+    fn hint_regions_cover_keycaps_and_labels_in_render_order() {
+        let rendered = layout_hints(80, footer_hints(&App::new(AppConfig::default())));
+        let regions = hint_regions(&rendered, 10, 3);
+        let (_, width) = render_hints(&rendered, &Theme::default());
+        assert_eq!(regions.len(), rendered.len());
+        assert_eq!(regions[0].area.x, 10);
+        assert_eq!(regions[0].key, rendered[0].key);
+        let last = regions.last().expect("hints");
+        assert_eq!(usize::from(last.area.right()), 10 + width);
+        assert!(regions.iter().all(|region| region.area.y == 3));
+    }
+
+    #[test]
+    fn hex_preview_lines_split_offset_bytes_and_ascii() {
+        let lines = hex_preview_lines(
+            concat!(
+                "PNG image, 1 x 1, 8-bit RGBA\n",
+                "\n",
+                "hex page 1/1: bytes 0-3 of 4\n",
+                "00000000  89 50 4e 47                                       |.PNG|\n",
+            ),
+            &Theme::default(),
+        );
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].spans.len(), 1);
+        assert_eq!(lines[2].spans[0].style.fg, Some(Color::DarkGray));
+        let row = &lines[3];
+        assert_eq!(row.spans.len(), 3);
+        assert_eq!(row.spans[0].content, "00000000");
+        assert_eq!(row.spans[2].content, " |.PNG|");
+        assert_eq!(row.spans[2].style.fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn metadata_lines_show_source_attributes_and_permission_mismatch() {
+        use crate::chattr::source_info;
+        use crate::metadata::{EntryMetadata, FileKind, FileMetadata, SourceDetails};
+
+        let source = std::path::PathBuf::from("/src/private_dot_netrc");
+        let metadata = EntryMetadata {
+            file: FileMetadata {
+                kind: FileKind::File,
+                mode: 0o644,
+                owner: "me".to_string(),
+                group: "staff".to_string(),
+                size: 42,
+                modified: None,
+                symlink_target: None,
+            },
+            source: Some(SourceDetails {
+                info: source_info(&source, false),
+                path: source,
+            }),
+        };
+
+        let text: Vec<String> = metadata_lines(&metadata, &Theme::default(), 0o022)
+            .iter()
+            .map(render_line_text)
+            .collect();
+        assert_eq!(text[0], "mode     -rw-r--r-- (0644) file");
+        assert_eq!(text[1], "owner    me:staff  size 42 bytes");
+        assert_eq!(text[3], "source   /src/private_dot_netrc");
+        assert_eq!(text[4], "attrs    private");
+        assert!(text[5].starts_with("! source is private_"));
+    }
 }
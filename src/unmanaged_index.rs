@@ -0,0 +1,345 @@
+use crate::exclude::UnmanagedExcludes;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+/// Paths per streamed batch; each batch re-runs an active list filter.
+const INDEX_BATCH_SIZE: usize = 5_000;
+
+/// A background walk of the unmanaged tree for the list filter.
+#[derive(Debug, Clone)]
+pub(crate) struct UnmanagedIndexJob {
+    /// Ties streamed batches to the index they were started for.
+    pub generation: u64,
+    pub working_dir: PathBuf,
+    /// Visible unmanaged entries relative to `working_dir`; `.` stands for
+    /// the working directory's children.
+    pub roots: Vec<PathBuf>,
+    /// Absolute managed paths, never indexed.
+    pub managed: HashSet<PathBuf>,
+    /// Exclusion globs and `.gitignore` flag; empty / false while the view
+    /// shows everything.
+    pub exclude: Vec<String>,
+    pub gitignore: bool,
+    /// Where directory listings are cached between runs.
+    pub cache_path: Option<PathBuf>,
+    /// Set once a newer job replaces this one.
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// One streamed slice of the index.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexBatch {
+    pub paths: Vec<PathBuf>,
+    pub done: bool,
+    /// Why the listing cache could not be written; final batch only.
+    pub save_error: Option<String>,
+}
+
+/// Entries of one directory (name, is a real directory), valid while its
+/// modification time is unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirListing {
+    mtime: SystemTime,
+    entries: Vec<(String, bool)>,
+}
+
+/// Listings by absolute directory path. Directories with non-UTF-8 names
+/// are always read afresh.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ListingCache {
+    dirs: HashMap<String, DirListing>,
+}
+
+pub(crate) fn default_index_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("chezmoi-tui").join("unmanaged-index.json"))
+}
+
+/// Walks `job.roots` breadth-first, passing batches of paths relative to
+/// the working directory to `emit`; the last one has `done` set. Stops
+/// without saving once cancelled or when `emit` returns false.
+pub(crate) fn run_index_job(job: &UnmanagedIndexJob, mut emit: impl FnMut(IndexBatch) -> bool) {
+    let previous = job
+        .cache_path
+        .as_deref()
+        .map(load_listing_cache)
+        .unwrap_or_default();
+    let mut walker = Walker {
+        job,
+        excludes: UnmanagedExcludes::new(&job.exclude, job.gitignore),
+        previous,
+        visited: ListingCache::default(),
+    };
+
+    let mut frontier = VecDeque::new();
+    for root in &job.roots {
+        if root == Path::new(".") {
+            frontier.extend(walker.children(root));
+        } else {
+            let is_dir = fs::symlink_metadata(job.working_dir.join(root))
+                .is_ok_and(|meta| meta.file_type().is_dir());
+            frontier.push_back((root.clone(), is_dir));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut batch = Vec::new();
+    while let Some((path, is_dir)) = frontier.pop_front() {
+        if job.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        if is_dir {
+            frontier.extend(walker.children(&path));
+        }
+        batch.push(path);
+        if batch.len() >= INDEX_BATCH_SIZE {
+            let paths = std::mem::take(&mut batch);
+            if !emit(IndexBatch {
+                paths,
+                done: false,
+                save_error: None,
+            }) {
+                return;
+            }
+        }
+    }
+    if job.cancel.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let save_error = job
+        .cache_path
+        .as_deref()
+        .and_then(|path| walker.save(path).err().map(|err| format!("{err:#}")));
+    emit(IndexBatch {
+        paths: batch,
+        done: true,
+        save_error,
+    });
+}
+
+struct Walker<'a> {
+    job: &'a UnmanagedIndexJob,
+    excludes: UnmanagedExcludes,
+    previous: ListingCache,
+    visited: ListingCache,
+}
+
+impl Walker<'_> {
+    /// Shown children of `dir` in name order, each with whether it is a
+    /// directory to descend into (symlinks never are).
+    fn children(&mut self, dir: &Path) -> Vec<(PathBuf, bool)> {
+        let abs = if dir == Path::new(".") {
+            self.job.working_dir.clone()
+        } else {
+            self.job.working_dir.join(dir)
+        };
+        self.listing(&abs)
+            .into_iter()
+            .filter_map(|(name, is_dir)| {
+                let path = if dir == Path::new(".") {
+                    PathBuf::from(name)
+                } else {
+                    dir.join(name)
+                };
+                let hidden = self.job.managed.contains(&self.job.working_dir.join(&path))
+                    || self
+                        .excludes
+                        .is_excluded_entry(&self.job.working_dir, &path);
+                (!hidden).then_some((path, is_dir))
+            })
+            .collect()
+    }
+
+    fn listing(&mut self, abs: &Path) -> Vec<(OsString, bool)> {
+        let Ok(mtime) = fs::symlink_metadata(abs).and_then(|meta| meta.modified()) else {
+            return Vec::new();
+        };
+        let key = abs.to_str().map(str::to_string);
+        if let Some(key) = &key
+            && let Some(cached) = self.previous.dirs.remove(key)
+            && cached.mtime == mtime
+        {
+            let entries = cached
+                .entries
+                .iter()
+                .map(|(name, is_dir)| (OsString::from(name), *is_dir))
+                .collect();
+            self.visited.dirs.insert(key.clone(), cached);
+            return entries;
+        }
+
+        let Ok(read_dir) = fs::read_dir(abs) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<(OsString, bool)> = read_dir
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                (entry.file_name(), is_dir)
+            })
+            .collect();
+        entries.sort_by(|a, b| a.0.to_string_lossy().cmp(&b.0.to_string_lossy()));
+
+        let utf8: Option<Vec<(String, bool)>> = entries
+            .iter()
+            .map(|(name, is_dir)| name.to_str().map(|name| (name.to_string(), *is_dir)))
+            .collect();
+        if let (Some(key), Some(utf8)) = (key, utf8) {
+            self.visited.dirs.insert(
+                key,
+                DirListing {
+                    mtime,
+                    entries: utf8,
+                },
+            );
+        }
+        entries
+    }
+
+    /// Writes this walk's listings, keeping cached directories outside the
+    /// working directory for other sessions.
+    fn save(mut self, path: &Path) -> Result<()> {
+        let working_dir = self.job.working_dir.to_string_lossy().into_owned();
+        let mut cache = load_listing_cache(path);
+        cache
+            .dirs
+            .retain(|dir, _| !Path::new(dir).starts_with(&working_dir));
+        cache.dirs.extend(self.visited.dirs.drain());
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string(&cache).context("failed to encode index")?;
+        fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn load_listing_cache(path: &Path) -> ListingCache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "chezmoi_tui_index_{name}_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn job(working_dir: &Path, cache_path: Option<PathBuf>) -> UnmanagedIndexJob {
+        UnmanagedIndexJob {
+            generation: 1,
+            working_dir: working_dir.to_path_buf(),
+            roots: vec![PathBuf::from(".")],
+            managed: HashSet::new(),
+            exclude: Vec::new(),
+            gitignore: false,
+            cache_path,
+            cancel: Arc::default(),
+        }
+    }
+
+    fn collect(job: &UnmanagedIndexJob) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        let mut finished = false;
+        run_index_job(job, |batch| {
+            paths.extend(batch.paths);
+            finished = batch.done;
+            true
+        });
+        assert!(finished);
+        paths
+    }
+
+    #[test]
+    fn index_walks_breadth_first_without_managed_or_excluded_paths() {
+        let root = temp_dir("bfs");
+        fs::create_dir_all(root.join("a/sub")).expect("create a/sub");
+        fs::create_dir_all(root.join("b")).expect("create b");
+        fs::create_dir_all(root.join("node_modules/pkg")).expect("create node_modules");
+        fs::write(root.join("a/sub/deep.txt"), "deep").expect("write deep");
+        fs::write(root.join("b/root.txt"), "root").expect("write root");
+        fs::write(root.join("b/managed.txt"), "managed").expect("write managed");
+
+        let mut job = job(&root, None);
+        job.managed.insert(root.join("b/managed.txt"));
+        job.exclude = vec!["node_modules".to_string()];
+        assert_eq!(
+            collect(&job),
+            ["a", "b", "a/sub", "b/root.txt", "a/sub/deep.txt"]
+                .map(PathBuf::from)
+                .to_vec()
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn cached_listings_are_reused_until_the_directory_changes() {
+        let root = temp_dir("cache");
+        let cache_path = root.join("cache/index.json");
+        let tree = root.join("tree");
+        fs::create_dir_all(tree.join("dir")).expect("create dir");
+        fs::write(tree.join("dir/one"), "").expect("write");
+
+        let job = job(&tree, Some(cache_path.clone()));
+        assert_eq!(collect(&job).len(), 2);
+        let cached = load_listing_cache(&cache_path);
+        let key = tree.join("dir").to_string_lossy().into_owned();
+        assert_eq!(cached.dirs[&key].entries, vec![("one".to_string(), false)]);
+
+        // A stale listing with the current mtime wins over the disk...
+        let mut stale = cached;
+        stale
+            .dirs
+            .get_mut(&key)
+            .expect("listing")
+            .entries
+            .push(("ghost".to_string(), false));
+        fs::write(&cache_path, serde_json::to_string(&stale).expect("encode")).expect("write");
+        assert!(collect(&job).contains(&PathBuf::from("dir/ghost")));
+
+        // ...until the directory's mtime moves.
+        let listing = &stale.dirs[&key];
+        let older = listing.mtime - std::time::Duration::from_secs(10);
+        stale.dirs.get_mut(&key).expect("listing").mtime = older;
+        fs::write(&cache_path, serde_json::to_string(&stale).expect("encode")).expect("write");
+        assert!(!collect(&job).contains(&PathBuf::from("dir/ghost")));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn cancelled_jobs_stop_without_finishing() {
+        let root = temp_dir("cancel");
+        fs::write(root.join("file"), "").expect("write");
+        let job = job(&root, None);
+        job.cancel.store(true, Ordering::Relaxed);
+        let mut batches = 0;
+        run_index_job(&job, |_| {
+            batches += 1;
+            true
+        });
+        assert_eq!(batches, 0);
+        let _ = fs::remove_dir_all(root);
+    }
+}
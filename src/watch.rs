@@ -0,0 +1,302 @@
+use crate::app::BackendEvent;
+use crate::exclude::UnmanagedExcludes;
+use crate::infra::ChezmoiClient;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+
+/// How often watched paths are re-scanned. A change is reported once a
+/// scan finds nothing new, so bursts (an editor's save dance, a `git pull`
+/// in the source dir) coalesce into one event.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Stat calls per scan before a tree walk gives up, so a huge working
+/// directory cannot turn polling into a disk scan.
+const MAX_WATCHED_ENTRIES: usize = 10_000;
+
+/// Directory tree scanned on every poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WatchTree {
+    pub root: PathBuf,
+    /// Levels below `root` to descend; `None` for no limit.
+    pub max_depth: Option<usize>,
+    /// Track files too. Without them, directories are compared by the names
+    /// they contain, so files rewritten in place or renamed over go unseen.
+    pub files: bool,
+    /// Gitignore-style globs, relative to `root`, never scanned.
+    pub exclude: Vec<String>,
+    /// Also skip what `.gitignore` files inside the tree ignore.
+    pub gitignore: bool,
+}
+
+/// Paths the watcher polls: managed targets plus whole trees.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct WatchSet {
+    pub files: Vec<PathBuf>,
+    pub trees: Vec<WatchTree>,
+}
+
+/// What a poll records for one path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stamp {
+    Missing,
+    /// Modification time and size.
+    Modified(SystemTime, u64),
+    /// Hash of the shown entry names of a directory.
+    Listing(u64),
+}
+
+type Snapshot = BTreeMap<PathBuf, Stamp>;
+
+fn stamp(path: &Path) -> Stamp {
+    fs::symlink_metadata(path)
+        .ok()
+        .and_then(|metadata| Some(Stamp::Modified(metadata.modified().ok()?, metadata.len())))
+        .unwrap_or(Stamp::Missing)
+}
+
+fn snapshot(set: &WatchSet) -> Snapshot {
+    let mut snapshot: Snapshot = set
+        .files
+        .iter()
+        .map(|path| (path.clone(), stamp(path)))
+        .collect();
+    for tree in &set.trees {
+        let excludes = UnmanagedExcludes::new(&tree.exclude, tree.gitignore);
+        let mut pending = vec![(tree.root.clone(), 0)];
+        while let Some((dir, depth)) = pending.pop() {
+            if snapshot.len() >= MAX_WATCHED_ENTRIES {
+                break;
+            }
+            let Ok(entries) = fs::read_dir(&dir) else {
+                snapshot.insert(dir, Stamp::Missing);
+                continue;
+            };
+            let mut names = Vec::new();
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let relative = path.strip_prefix(&tree.root).unwrap_or(&path);
+                if excludes.is_excluded_entry(&tree.root, relative) {
+                    continue;
+                }
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                names.push(entry.file_name());
+                if file_type.is_dir() {
+                    if tree.max_depth.is_none_or(|max| depth < max) {
+                        pending.push((path, depth + 1));
+                    }
+                } else if tree.files {
+                    let stamp = stamp(&path);
+                    snapshot.insert(path, stamp);
+                }
+            }
+            let stamp = if tree.files {
+                stamp(&dir)
+            } else {
+                names.sort();
+                let mut hasher = DefaultHasher::new();
+                names.hash(&mut hasher);
+                Stamp::Listing(hasher.finish())
+            };
+            snapshot.insert(dir, stamp);
+        }
+    }
+    snapshot
+}
+
+/// Paths added, removed or modified between two snapshots.
+fn changed_paths(before: &Snapshot, after: &Snapshot) -> BTreeSet<PathBuf> {
+    let mut changed: BTreeSet<PathBuf> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        before
+            .keys()
+            .filter(|path| !after.contains_key(*path))
+            .cloned(),
+    );
+    changed
+}
+
+/// Polls the watch set (replaced whenever the managed list changes) plus
+/// the chezmoi source directory, and sends one `FilesChanged` per burst of
+/// changes, with those inside the source directory listed apart. Ends when
+/// the app stops listening.
+pub(crate) async fn watch_loop(
+    client: Arc<dyn ChezmoiClient>,
+    mut set_rx: watch::Receiver<WatchSet>,
+    event_tx: UnboundedSender<BackendEvent>,
+) {
+    let source_tree = tokio::task::spawn_blocking(move || client.source_dir())
+        .await
+        .ok()
+        .and_then(Result::ok)
+        .map(|root| WatchTree {
+            root,
+            max_depth: None,
+            files: true,
+            exclude: vec![".git".to_string()],
+            gitignore: false,
+        });
+
+    let mut previous: Option<Snapshot> = None;
+    let mut pending = BTreeSet::new();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if event_tx.is_closed() {
+            break;
+        }
+        // A new set follows a refresh; paths it newly lists are not changes.
+        let replaced = set_rx.has_changed().unwrap_or(false);
+        let mut set = set_rx.borrow_and_update().clone();
+        set.trees.extend(source_tree.clone());
+        let Ok(current) = tokio::task::spawn_blocking(move || snapshot(&set)).await else {
+            continue;
+        };
+
+        let mut changed = previous
+            .as_ref()
+            .map(|previous| changed_paths(previous, &current))
+            .unwrap_or_default();
+        if replaced && let Some(previous) = &previous {
+            changed.retain(|path| previous.contains_key(path));
+        }
+        previous = Some(current);
+        if !changed.is_empty() {
+            pending.extend(changed);
+            continue;
+        }
+        if !pending.is_empty() {
+            let (source_paths, paths) =
+                std::mem::take(&mut pending)
+                    .into_iter()
+                    .partition(|path: &PathBuf| {
+                        source_tree
+                            .as_ref()
+                            .is_some_and(|tree| path.starts_with(&tree.root))
+                    });
+            if event_tx
+                .send(BackendEvent::FilesChanged {
+                    paths,
+                    source_paths,
+                })
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "chezmoi_tui_watch_{name}_{}_{}",
+            std::process::id(),
+            line!()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn snapshots_report_edits_additions_and_removals() {
+        let root = temp_dir("snapshot");
+        let tracked = root.join("dot_zshrc");
+        fs::write(&tracked, "a").expect("write");
+        fs::create_dir_all(root.join(".git")).expect("create .git");
+        let set = WatchSet {
+            files: vec![root.join("missing")],
+            trees: vec![WatchTree {
+                root: root.clone(),
+                max_depth: None,
+                files: true,
+                exclude: vec![".git".to_string()],
+                gitignore: false,
+            }],
+        };
+        let before = snapshot(&set);
+        assert!(before.contains_key(&tracked));
+        assert!(!before.contains_key(&root.join(".git")));
+        assert_eq!(before.get(&root.join("missing")), Some(&Stamp::Missing));
+
+        fs::write(&tracked, "longer").expect("write");
+        fs::write(root.join("missing"), "").expect("write");
+        let after = snapshot(&set);
+        let changed = changed_paths(&before, &after);
+        assert!(changed.contains(&tracked));
+        assert!(changed.contains(&root.join("missing")));
+
+        fs::remove_file(&tracked).expect("remove");
+        assert!(changed_paths(&after, &snapshot(&set)).contains(&tracked));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn directory_only_trees_stop_at_max_depth() {
+        let root = temp_dir("depth");
+        fs::create_dir_all(root.join("a/b/c")).expect("create dirs");
+        fs::write(root.join("a/file"), "x").expect("write");
+        let set = WatchSet {
+            files: Vec::new(),
+            trees: vec![WatchTree {
+                root: root.clone(),
+                max_depth: Some(2),
+                files: false,
+                exclude: Vec::new(),
+                gitignore: false,
+            }],
+        };
+        let snapshot = snapshot(&set);
+        assert!(snapshot.contains_key(&root.join("a/b")));
+        assert!(!snapshot.contains_key(&root.join("a/b/c")));
+        assert!(!snapshot.contains_key(&root.join("a/file")));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn directory_only_trees_ignore_rewrites_and_excluded_entries() {
+        let root = temp_dir("listing");
+        fs::create_dir_all(root.join(".cache/app")).expect("create dirs");
+        fs::write(root.join(".zsh_history"), "a").expect("write");
+        let set = WatchSet {
+            files: Vec::new(),
+            trees: vec![WatchTree {
+                root: root.clone(),
+                max_depth: Some(2),
+                files: false,
+                exclude: vec![".cache".to_string()],
+                gitignore: false,
+            }],
+        };
+        let before = snapshot(&set);
+        assert!(!before.contains_key(&root.join(".cache")));
+
+        // A history rewrite through rename keeps the same names.
+        fs::write(root.join(".zsh_history.new"), "ab").expect("write");
+        fs::rename(root.join(".zsh_history.new"), root.join(".zsh_history")).expect("rename");
+        fs::write(root.join(".cache/app/blob"), "x").expect("write");
+        let after = snapshot(&set);
+        assert!(changed_paths(&before, &after).is_empty());
+
+        fs::write(root.join("new-file"), "").expect("write");
+        assert_eq!(
+            changed_paths(&after, &snapshot(&set)),
+            BTreeSet::from([root.clone()])
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+}